@@ -27,6 +27,11 @@ return {
             first_line = "# Changes in " .. version
         end
 
+        if ctx and ctx.notes then
+            -- hand-written release notes from `git bump --notes`
+            first_line = first_line .. "\n\n" .. ctx.notes
+        end
+
         return (format_string):format(first_line, content)
     end,
 
@@ -38,23 +43,25 @@ return {
                       'version = %b""', ('version = "%s"'):format(version), 1
                   )
 
-        local post_func = function()
-            -- run `cargo check` on the current package, which will then also
-            -- update Cargo.lock with the new version string
-
-            local pkgid
-            do
-                local process = io.popen("cargo pkgid")
-                pkgid = process:read("a")
-                process:close()
-            end
-            os.execute(("cargo check -p %q"):format(pkgid))
-        end
-
-        return content, {post_func = post_func}
+        -- let git-bump update Cargo.lock with the new version string
+        return content, {lockfile = "cargo"}
     end,
 
     VERSION = function(version)
         return version
-    end
+    end,
+
+    -- bundled helpers for packaging files, uncomment as needed:
+    --
+    -- the "version" and "appVersion" keys of a Helm chart, either of them can
+    -- be switched off with `false`
+    -- ["Chart.yaml"] = helpers.helm_chart {version = true, app_version = true},
+    --
+    -- a new entry of a Debian changelog, with the maintainer from DEBFULLNAME
+    -- and DEBEMAIL, or else from the Git config
+    -- ["debian/changelog"] = helpers.debian_changelog(),
+    --
+    -- the "Version" tag and a new changelog entry of an RPM spec file, rename
+    -- the key to match the name of your spec file
+    -- ["package.spec"] = helpers.rpm_spec(),
 }
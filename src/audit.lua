@@ -0,0 +1,136 @@
+-- Sandbox of `git bump audit`, replacing all functions that reach outside of
+-- the worktree with stubs that only record their calls. Reading files inside
+-- of the worktree is still allowed, so mappings see their real contents.
+local record, is_inside = ...
+
+local where = function()
+    -- location of the code that called the stub, taken from the position
+    -- that `error` prepends to its message, since the debug library is not
+    -- available
+
+    local _, message = pcall(error, "", 4)
+    return message:match("^(.-):?%s*$")
+end
+
+local stub = function(name, result)
+    return function(argument)
+        record(where(), name, tostring(argument))
+        return result
+    end
+end
+
+local fake_file = {
+    read = function()
+        return ""
+    end,
+    lines = function()
+        return function()
+            return nil
+        end
+    end,
+    write = function(self)
+        return self
+    end,
+    close = function()
+        return true
+    end
+}
+
+local recording_file = function(name)
+    -- standard file of the process, whose methods are recorded as well
+
+    return setmetatable(
+               {}, {
+            __index = function(_, key)
+                local method = stub(("%s:%s"):format(name, key), fake_file)
+                return function(_, ...)
+                    return method(...)
+                end
+            end
+        }
+           )
+end
+
+local proxy = function(name, allowed, result)
+    -- replacement of a whole library, where only the allowed members are
+    -- kept and every other member is a stub, including ones that do not even
+    -- exist in the original library
+
+    return setmetatable(
+               allowed, {
+            __index = function(_, key)
+                return stub(("%s.%s"):format(name, key), result)
+            end
+        }
+           )
+end
+
+local real_io, real_os = io, os
+
+os = proxy("os", {
+    clock = real_os.clock,
+    date = real_os.date,
+    difftime = real_os.difftime,
+    getenv = real_os.getenv,
+    time = real_os.time
+}, nil)
+io = proxy("io", {
+    type = real_io.type,
+    stdin = recording_file("io.stdin"),
+    stdout = recording_file("io.stdout"),
+    stderr = recording_file("io.stderr")
+}, fake_file)
+package.loaded.os, package.loaded.io = os, io
+dofile = stub("dofile")
+loadfile = stub("loadfile")
+package.loadlib = stub("package.loadlib")
+
+local open = real_io.open
+io.open = function(path, mode)
+    mode = mode or "r"
+    if is_inside(tostring(path)) and not mode:find("[wa+]") then
+        return open(path, mode)
+    end
+
+    record(where(), "io.open", ("%s (%s)"):format(tostring(path), mode))
+    return nil, "Blocked by audit"
+end
+
+local lines = real_io.lines
+io.lines = function(path, ...)
+    -- without a path, the lines of the standard input are read
+    if path ~= nil and is_inside(tostring(path)) then
+        return lines(path, ...)
+    end
+
+    record(where(), "io.lines", tostring(path))
+    return function()
+        return nil
+    end
+end
+
+-- modules that are commonly used to talk to network services
+local network = {socket = true, ssl = true, http = true, curl = true, cURL = true}
+
+local require_loaded = require
+require = function(name)
+    if package.loaded[name] ~= nil then
+        return require_loaded(name)
+    end
+
+    local root = tostring(name):match("^[^.]+")
+    if network[root] then
+        record(where(), "require (network)", tostring(name))
+    else
+        record(where(), "require", tostring(name))
+    end
+
+    -- every function of the module is a stub as well
+    return setmetatable(
+               {}, {
+            __index = function(_, key)
+                return stub(("%s.%s"):format(name, key))
+            end
+        }
+           )
+end
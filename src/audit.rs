@@ -0,0 +1,259 @@
+use std::cell::RefCell;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::rc::Rc;
+
+use mlua::prelude::*;
+
+use crate::engine::{self, Disk};
+use crate::mapping::{key_components, Mapping};
+use crate::state::State;
+use crate::Result;
+
+/// Source of the sandbox that records operations instead of executing them.
+const SANDBOX: &str = include_str!("audit.lua");
+
+/// Version the configs are evaluated with.
+const VERSION: &str = "9876.54.3210";
+
+/// Operation reaching outside of the worktree, as attempted by a config file.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Operation {
+    pub(crate) location: String,
+    pub(crate) name: String,
+    pub(crate) detail: String,
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {} {}", self.location, self.name, self.detail)
+    }
+}
+
+/// Record operations of all config files, without executing them.
+///
+/// The configs are evaluated, and all their mappings, `enabled` guards, and hook functions are run
+/// in a sandbox, where functions that reach outside of the worktree only record their calls.
+/// Declared hook commands and lockfile refreshes are recorded as well.
+pub(crate) fn audit(state: &mut State) -> Result<Vec<Operation>> {
+    let lua = state.get_lua()?;
+    let workdir = state.get_workdir()?;
+    let paths = state.get_paths()?;
+
+    let operations = Rc::new(RefCell::new(Vec::new()));
+    install_sandbox(&lua, &workdir, Rc::clone(&operations))?;
+    state.set_context()?;
+
+    for config in state.get_config_files()?.iter() {
+        let name = paths.show(config);
+        let source = match fs::read_to_string(config) {
+            Ok(source) => source,
+            Err(_) => continue,
+        };
+
+        let map = match engine::eval_config(&lua, &name, &source, Some(VERSION)) {
+            Ok(map) => map,
+            Err(err) => {
+                eprintln!("Warning: Skipping {}: {}", name, err);
+                continue;
+            }
+        };
+
+        for (key, value) in map {
+            let mapping = match Mapping::from_lua(&lua, &key, value, config) {
+                Ok(mapping) => mapping,
+                Err(_) => continue,
+            };
+
+            let location = format!("{}: {}", name, key);
+            let file = workdir.join(key_components(&key).iter().collect::<PathBuf>());
+            if let Err(err) = run_mapping(&lua, &mapping, &file, &location, &operations) {
+                eprintln!("Warning: Could not audit {} completely: {}", location, err);
+            }
+            mapping.remove(&lua)?;
+        }
+    }
+
+    if let Some(hooks) = lua.globals().get::<_, Option<LuaTable>>("hooks")? {
+        if let Err(err) = run_hooks(&hooks, "global hooks", &operations) {
+            eprintln!("Warning: Could not audit global hooks completely: {}", err);
+        }
+    }
+
+    let operations = operations.take();
+    Ok(operations)
+}
+
+/// Replace functions that reach outside of the worktree with stubs that record their calls.
+fn install_sandbox(
+    lua: &Lua,
+    workdir: &Path,
+    operations: Rc<RefCell<Vec<Operation>>>,
+) -> LuaResult<()> {
+    let record = lua.create_function(
+        move |_, (location, name, detail): (String, String, String)| {
+            operations.borrow_mut().push(Operation {
+                location,
+                name,
+                detail,
+            });
+            Ok(())
+        },
+    )?;
+
+    let workdir = workdir.to_path_buf();
+    let is_inside =
+        lua.create_function(move |_, path: String| Ok(is_inside(&workdir, Path::new(&path))))?;
+
+    engine::load_chunk(lua, "=git-bump.audit", SANDBOX)?.call::<_, ()>((record, is_inside))
+}
+
+/// Check if a path is inside of the worktree, without touching the file system.
+///
+/// Relative paths are relative to the current directory, just like for Lua's `io.open`.
+fn is_inside(workdir: &Path, path: &Path) -> bool {
+    let path = env::current_dir().unwrap_or_default().join(path);
+
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            Component::CurDir => {}
+            component => normalized.push(component),
+        }
+    }
+
+    normalized.starts_with(workdir)
+}
+
+/// Run all functions of a mapping on the contents of its file, and the hooks it returns.
+fn run_mapping(
+    lua: &Lua,
+    mapping: &Mapping,
+    file: &Path,
+    location: &str,
+    operations: &RefCell<Vec<Operation>>,
+) -> Result<()> {
+    // Disabled mappings are run anyway, since they might be enabled for other versions
+    engine::is_enabled(lua, mapping, VERSION)?;
+
+    let contents = engine::read(&Disk, mapping, file).unwrap_or_default();
+    let (_, returned) = engine::apply(lua, mapping, VERSION, &contents)?;
+
+    if let Some(formatter) = &mapping.format {
+        operations.borrow_mut().push(Operation {
+            location: location.to_string(),
+            name: String::from("format"),
+            detail: formatter.argv.join(" "),
+        });
+    }
+
+    returned
+        .hooks
+        .iter()
+        .try_for_each(|hooks| run_hooks(hooks, location, operations))
+}
+
+/// Run hook functions, and record declared commands and lockfile refreshes.
+fn run_hooks(hooks: &LuaTable, location: &str, operations: &RefCell<Vec<Operation>>) -> Result<()> {
+    let record = |name: &str, detail: String| {
+        operations.borrow_mut().push(Operation {
+            location: location.to_string(),
+            name: name.to_string(),
+            detail,
+        })
+    };
+
+    for name in ["pre_cmd", "post_cmd"] {
+        if let Some(argv) = hooks.get::<_, Option<Vec<String>>>(name)? {
+            record(name, argv.join(" "));
+        }
+    }
+    match hooks.get::<_, LuaValue>("lockfile")? {
+        LuaValue::Nil | LuaValue::Boolean(false) => {}
+        LuaValue::String(name) => record("lockfile", name.to_string_lossy().into_owned()),
+        value => record("lockfile", value.type_name().to_string()),
+    }
+
+    for name in ["pre_func", "post_func"] {
+        if let Some(func) = hooks.get::<_, Option<LuaFunction>>(name)? {
+            func.call::<_, ()>(())?;
+        }
+    }
+    if let Some(on_error) = hooks.get::<_, Option<LuaFunction>>("on_error")? {
+        on_error.call::<_, ()>(("VERSION", "func", "audit"))?;
+    }
+    for (name, argument) in [
+        ("post_commit", "0".repeat(40)),
+        ("post_tag", format!("v{}", VERSION)),
+    ] {
+        if let Some(func) = hooks.get::<_, Option<LuaFunction>>(name)? {
+            func.call::<_, ()>(argument)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn record_operations_in_sandbox() {
+    let lua = Lua::new();
+    let workdir = env::current_dir().unwrap();
+    let operations = Rc::new(RefCell::new(Vec::new()));
+    install_sandbox(&lua, &workdir, Rc::clone(&operations)).unwrap();
+    let tmpdir = crate::state::TempDir::create().unwrap();
+    let escape = tmpdir.path().join("escape.txt");
+    lua.globals()
+        .set("escape", escape.to_string_lossy().as_ref())
+        .unwrap();
+
+    lua.load(
+        r#"
+        os.execute("make install")
+        local process = io.popen("git describe")
+        assert(process:read("*a") == "")
+        assert(io.open("/etc/passwd") == nil)
+        assert(io.open("Cargo.toml", "w") == nil)
+        require("socket.http").request("https://example.com")
+        io.output(escape)
+        io.write("pwned")
+        io.tmpfile():write("pwned")
+        assert(os.tmpname() == nil)
+        io.stderr:write("warning")
+        assert(os.date("%Y") ~= nil)
+        "#,
+    )
+    .exec()
+    .unwrap();
+
+    let operations = operations.take();
+    assert!(operations[0].location.ends_with(":2"));
+    let operations = operations
+        .into_iter()
+        .map(|operation| format!("{} {}", operation.name, operation.detail))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        operations,
+        [
+            "os.execute make install",
+            "io.popen git describe",
+            "io.open /etc/passwd (r)",
+            "io.open Cargo.toml (w)",
+            "require (network) socket.http",
+            "socket.http.request https://example.com",
+            format!("io.output {}", escape.display()).as_str(),
+            "io.write pwned",
+            "io.tmpfile nil",
+            "os.tmpname nil",
+            "io.stderr:write warning",
+        ]
+    );
+    assert!(!escape.exists());
+
+    assert!(is_inside(&workdir, Path::new("src/../Cargo.toml")));
+    assert!(!is_inside(&workdir, Path::new("../outside")));
+}
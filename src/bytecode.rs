@@ -0,0 +1,147 @@
+use mlua::prelude::*;
+
+/// Get the number of fixed parameters of a Lua function, and whether it takes varargs.
+///
+/// Since the debug library is not available, the parameters are taken from the header of the
+/// function in its precompiled chunk, as returned by `string.dump`. Returns `None` for C functions
+/// and if the chunk cannot be parsed.
+pub(crate) fn parameters(lua: &Lua, func: &LuaFunction) -> Option<(u8, bool)> {
+    let dump = lua
+        .globals()
+        .get::<_, LuaTable>("string")
+        .and_then(|string| string.get::<_, LuaFunction>("dump"))
+        .and_then(|dump| dump.call::<_, LuaString>((func.clone(), true)))
+        .ok()?;
+
+    parse(dump.as_bytes())
+}
+
+/// Read an unsigned integer in the variable-length format of Lua 5.4 dumps.
+#[cfg(feature = "lua54")]
+fn varint(bytes: &[u8]) -> Option<(usize, &[u8])> {
+    let mut value = 0usize;
+    for (index, byte) in bytes.iter().enumerate() {
+        value = (value << 7) | usize::from(byte & 0x7f);
+        if byte & 0x80 != 0 {
+            return Some((value, &bytes[index + 1..]));
+        }
+    }
+    None
+}
+
+/// Read an unsigned LEB128 integer, as used in LuaJIT dumps.
+#[cfg(feature = "luajit")]
+fn uleb128(bytes: &[u8]) -> Option<(usize, &[u8])> {
+    let mut value = 0usize;
+    for (index, byte) in bytes.iter().enumerate() {
+        value |= usize::from(byte & 0x7f) << (7 * index);
+        if byte & 0x80 == 0 {
+            return Some((value, &bytes[index + 1..]));
+        }
+    }
+    None
+}
+
+#[cfg(feature = "lua54")]
+fn parse(dump: &[u8]) -> Option<(u8, bool)> {
+    // Header of 31 bytes and the number of upvalues, followed by the source, which is empty in
+    // stripped dumps, and the first and last line
+    let (source, rest) = varint(dump.get(32..)?)?;
+    let rest = rest.get(source.saturating_sub(1)..)?;
+    let (_, rest) = varint(rest)?;
+    let (_, rest) = varint(rest)?;
+
+    Some((*rest.first()?, *rest.get(1)? != 0))
+}
+
+#[cfg(feature = "lua53")]
+fn parse(dump: &[u8]) -> Option<(u8, bool)> {
+    // Header of 33 bytes and the number of upvalues, followed by the source, which is empty in
+    // stripped dumps, and the first and last line as native integers
+    let int_size = usize::from(*dump.get(12)?);
+    let rest = match *dump.get(34)? {
+        0 => dump.get(35..)?,
+        0xff => return None,
+        size => dump.get(35 + usize::from(size) - 1..)?,
+    };
+    let rest = rest.get(2 * int_size..)?;
+
+    Some((*rest.first()?, *rest.get(1)? != 0))
+}
+
+#[cfg(feature = "lua52")]
+fn parse(dump: &[u8]) -> Option<(u8, bool)> {
+    // Header of 18 bytes, followed by the first and last line as native integers
+    let int_size = usize::from(*dump.get(7)?);
+    let rest = dump.get(18 + 2 * int_size..)?;
+
+    Some((*rest.first()?, *rest.get(1)? != 0))
+}
+
+#[cfg(feature = "lua51")]
+fn parse(dump: &[u8]) -> Option<(u8, bool)> {
+    // Header of 12 bytes, followed by the source with its length as native size, the first and
+    // last line as native integers, and the number of upvalues
+    let little_endian = *dump.get(6)? == 1;
+    let (int_size, size_size) = (usize::from(*dump.get(7)?), usize::from(*dump.get(8)?));
+
+    let size_bytes = dump.get(12..12 + size_size)?;
+    let mut source = 0usize;
+    for index in 0..size_size {
+        let byte = match little_endian {
+            true => size_bytes[size_size - 1 - index],
+            false => size_bytes[index],
+        };
+        source = source.checked_shl(8)? | usize::from(byte);
+    }
+
+    let rest = dump.get(12 + size_size + source + 2 * int_size + 1..)?;
+    // The vararg flag is a bit field in Lua 5.1, functions that use `...` have at least bit 2 set
+    Some((*rest.first()?, *rest.get(1)? & 2 != 0))
+}
+
+#[cfg(feature = "luajit")]
+fn parse(dump: &[u8]) -> Option<(u8, bool)> {
+    // Header of 4 bytes and flags, followed by the chunk name in non-stripped dumps, and the
+    // prototypes of all nested functions, with the function itself last
+    let (flags, mut rest) = uleb128(dump.get(4..)?)?;
+    if flags & 0x02 == 0 {
+        let (name, after) = uleb128(rest)?;
+        rest = after.get(name..)?;
+    }
+
+    let mut last = None;
+    loop {
+        let (size, after) = uleb128(rest)?;
+        if size == 0 {
+            break;
+        }
+        last = Some(after.get(..size)?);
+        rest = after.get(size..)?;
+    }
+
+    let prototype = last?;
+    Some((*prototype.get(1)?, prototype.first()? & 0x02 != 0))
+}
+
+#[test]
+fn read_parameters_from_dumps() {
+    let lua = Lua::new();
+    let parameters = |code: &str| {
+        let func = lua.load(code).eval::<LuaFunction>().unwrap();
+        parameters(&lua, &func)
+    };
+
+    assert_eq!(parameters("function(version) end"), Some((1, false)));
+    assert_eq!(
+        parameters("function(version, content) return content end"),
+        Some((2, false))
+    );
+    assert_eq!(parameters("function(...) return ... end"), Some((0, true)));
+
+    let upvalue = "local x = 1; return function(a, b, c) local f = function(y) return x end end";
+    assert_eq!(parameters(upvalue), Some((3, false)));
+
+    let print = lua.globals().get::<_, LuaFunction>("print").unwrap();
+    assert_eq!(self::parameters(&lua, &print), None);
+}
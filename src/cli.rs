@@ -1,45 +1,633 @@
-use clap::{ArgGroup, Parser};
+use std::path::PathBuf;
+use std::time::Instant;
 
-use crate::{bump, list_files, print_sample_config, Result};
+use clap::{ArgGroup, CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
+
+use crate::diff::DiffMode;
+use crate::import::Tool;
+use crate::metrics;
+use crate::sample::Ecosystem;
+use crate::semver::Increment;
+use crate::state::{Settings, State as BumpState};
+use crate::{
+    audit_configs, bump, canonical_version, complete_versions, describe_version, hook_check,
+    import_config, init_config, lint_configs, list_files, next_version, print_notes,
+    print_sample_config, print_status, replace_everywhere, run_doctor, verify_files, BumpOptions,
+    Result,
+};
 
 #[derive(Parser)]
 #[clap(author, version, about, long_about = None)]
+#[clap(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 #[clap(group(
     ArgGroup::new("action")
         .required(true)
         .args(&[
             "new_version",
+            "from_describe",
+            "major",
+            "minor",
+            "patch",
             "list_files",
             "print_sample_config",
         ]),
 ))]
+#[clap(group(ArgGroup::new("increment").args(&["major", "minor", "patch"])))]
+#[clap(group(ArgGroup::new("committing").args(&["commit", "commit_each"]).multiple(true)))]
 struct Cli {
+    #[clap(subcommand)]
+    command: Option<Command>,
+
     /// Version to set
     new_version: Option<String>,
 
+    #[clap(value_name = "NEXT_VERSION", requires = "commit_each")]
+    /// Further versions to set afterwards, each in a bump of its own, like `1.3.0-SNAPSHOT`
+    next_versions: Vec<String>,
+
+    #[clap(long)]
+    /// Use version derived from the nearest tag, as given by `git describe --tags`
+    from_describe: bool,
+
+    #[clap(long)]
+    /// Increment major version of the nearest tag
+    major: bool,
+
+    #[clap(long)]
+    /// Increment minor version of the nearest tag
+    minor: bool,
+
+    #[clap(long)]
+    /// Increment patch version of the nearest tag
+    patch: bool,
+
+    #[clap(long, value_name = "IDENTIFIERS", requires = "increment")]
+    /// Add pre-release identifiers to the incremented version, like `rc.1`
+    pre: Option<String>,
+
+    #[clap(long, value_name = "METADATA", requires = "increment")]
+    /// Add build metadata to the incremented version, like a commit hash
+    build: Option<String>,
+
     #[clap(long)]
     /// List files that would be updated
     list_files: bool,
 
-    #[clap(long)]
-    /// Print sample config file
-    print_sample_config: bool,
+    #[clap(
+        long,
+        conflicts_with_all = ["new_version", "from_describe", "increment", "print_sample_config"]
+    )]
+    /// Also show descriptions and config files of listed files
+    long: bool,
+
+    #[clap(long, value_enum, value_name = "ECOSYSTEM", num_args = 0..=1)]
+    /// Print sample config file, only with the recipes of an ecosystem if given
+    print_sample_config: Option<Option<Ecosystem>>,
+
+    #[clap(long, conflicts_with_all = ["list_files", "print_sample_config"])]
+    /// Show changes instead of writing files, without running any hooks
+    diff: bool,
+
+    #[clap(long, conflicts_with_all = ["diff", "list_files", "print_sample_config"])]
+    /// Print the new contents of the files with a header each instead of writing them, without
+    /// running any hooks
+    stdout: bool,
+
+    #[cfg(feature = "tui")]
+    #[clap(
+        long,
+        conflicts_with_all = ["diff", "stdout", "jobs", "list_files", "print_sample_config"]
+    )]
+    /// Review changes in a full-screen terminal interface and choose which files to bump
+    review: bool,
+
+    #[clap(long, value_enum, value_name = "MODE", requires = "diff")]
+    /// How to show changes
+    diff_mode: Option<DiffMode>,
+
+    #[clap(long, requires = "diff")]
+    /// Do not pipe shown changes through a pager
+    no_pager: bool,
+
+    #[clap(
+        long,
+        value_name = "FILE",
+        num_args = 0..=1,
+        require_equals = true,
+        conflicts_with_all = ["diff", "stdout"]
+    )]
+    /// Open written files in an editor before running post hooks, optionally only the given one
+    edit: Option<Vec<PathBuf>>,
+
+    #[clap(long, conflicts_with_all = ["list_files", "print_sample_config"])]
+    /// Ask for release notes in an editor and pass them to the config files as `ctx.notes`
+    notes: bool,
+
+    #[clap(
+        long,
+        value_name = "TEMPLATE",
+        conflicts_with_all = ["diff", "stdout", "list_files", "print_sample_config"]
+    )]
+    /// Create and switch to a branch before bumping, like `release/{version}`
+    branch: Option<String>,
+
+    #[clap(long, requires = "branch")]
+    /// Do not switch to the created branch, so the bump stays on the current branch
+    no_switch: bool,
+
+    #[clap(
+        long,
+        value_name = "BRANCH",
+        conflicts_with_all = ["list_files", "print_sample_config"]
+    )]
+    /// Refuse to bump unless the branch is checked out [default: bump.requireBranch from Git config]
+    require_branch: Option<String>,
+
+    #[clap(long, conflicts_with_all = ["diff", "stdout", "list_files", "print_sample_config"])]
+    /// Commit the bumped files
+    commit: bool,
+
+    #[clap(
+        long,
+        conflicts_with_all = ["diff", "stdout", "amend", "branch", "list_files", "print_sample_config"]
+    )]
+    /// Bump to every given version in turn, committing after each bump; only the first is tagged
+    commit_each: bool,
+
+    #[clap(long, requires = "commit", conflicts_with = "branch")]
+    /// Amend the previous commit with the bumped files instead of creating a new one
+    amend: bool,
+
+    #[clap(long, value_name = "NAME <EMAIL>", requires = "committing")]
+    /// Commit and tag as this identity [default: bump.identity or user.name and user.email from Git config]
+    identity: Option<String>,
+
+    #[clap(
+        long,
+        value_name = "FILE",
+        requires = "committing",
+        conflicts_with = "amend"
+    )]
+    /// Build the commit message from a template with {version}, {notes}, and {files} placeholders
+    commit_template: Option<PathBuf>,
+
+    #[clap(long, requires = "committing")]
+    /// Add a Signed-off-by trailer of the committer to the commit message
+    signoff: bool,
+
+    #[clap(long = "trailer", value_name = "KEY=VALUE", requires = "committing")]
+    /// Add a trailer to the commit message, can be given multiple times
+    trailers: Vec<String>,
+
+    #[clap(long, requires = "committing")]
+    /// Tag the bump commit, refusing to bump if the tag already exists
+    tag: bool,
+
+    #[clap(long, requires = "tag")]
+    /// Move the tag if it already exists
+    force_tag: bool,
+
+    #[clap(
+        long,
+        value_name = "REMOTE",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "origin",
+        conflicts_with_all = ["list_files", "print_sample_config"]
+    )]
+    /// Refuse to bump if the tag already exists on the remote, `origin` by default
+    check_remote_tags: Option<String>,
+
+    #[clap(long, requires = "committing")]
+    /// Push the bump commit and the tag to `origin`
+    push: bool,
+
+    #[cfg(feature = "release")]
+    #[clap(long, requires_all = ["tag", "push"])]
+    /// Create a release on GitHub or GitLab with the notes from the changelog
+    create_release: bool,
+
+    #[clap(long, conflicts_with_all = ["diff", "stdout", "list_files", "print_sample_config"])]
+    /// Print a report of the bump as JSON
+    json: bool,
+
+    #[clap(long, conflicts_with_all = ["list_files", "print_sample_config"])]
+    /// Print durations and written bytes of all files, to find slow mappings
+    timings: bool,
+
+    #[clap(long, conflicts_with_all = ["list_files", "print_sample_config"])]
+    /// Succeed without doing anything if no config file or no file to bump is found
+    allow_empty: bool,
+
+    #[clap(long = "group", value_name = "GROUP")]
+    /// Only process files of the given group, can be given multiple times
+    groups: Vec<String>,
+
+    #[clap(
+        long,
+        value_name = "REF",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "",
+        conflicts_with_all = ["list_files", "print_sample_config"]
+    )]
+    /// Only bump files changed since the given revision, or since the nearest tag
+    since: Option<String>,
+
+    #[clap(long, conflicts_with_all = ["list_files", "print_sample_config"])]
+    /// Skip files that are not tracked by Git, like build artifacts
+    tracked_only: bool,
+
+    #[clap(long, conflicts_with_all = ["list_files", "print_sample_config"])]
+    /// Check out mapped files that are excluded by sparse-checkout, instead of skipping them
+    materialize: bool,
+
+    #[clap(
+        long,
+        conflicts_with_all = ["index_only", "list_files", "print_sample_config"]
+    )]
+    /// Write read-only files anyway, making them read-only again afterwards
+    force_writable: bool,
+
+    #[clap(
+        long,
+        short,
+        value_name = "N",
+        conflicts_with_all = ["edit", "index_only", "list_files", "print_sample_config"]
+    )]
+    /// Bump files on N threads in parallel, each evaluating the config files on its own
+    jobs: Option<usize>,
+
+    #[clap(
+        long,
+        conflicts_with_all = ["edit", "list_files", "print_sample_config"]
+    )]
+    /// Write bumped files only into the index, leaving the worktree untouched
+    index_only: bool,
+
+    #[clap(long, global = true)]
+    /// Fail if a config file overrides a mapping of a previous config file
+    no_override: bool,
+
+    #[clap(long, global = true)]
+    /// Ignore the per-user config file [default on CI, see GIT_BUMP_NO_GLOBAL]
+    no_user_config: bool,
+
+    #[clap(long, global = true)]
+    /// Ignore the per-repository config file in $GIT_DIR
+    no_local_config: bool,
+
+    #[clap(long, global = true)]
+    /// Ignore the per-repository config file in the worktree
+    no_shared_config: bool,
+
+    #[clap(long, global = true)]
+    /// Also use the config files of all repositories the current one is nested in
+    outer_config: bool,
+
+    #[clap(long, global = true)]
+    /// Keep the paths of config files as found instead of resolving symlinks, like for dotfiles
+    no_canonicalize: bool,
+
+    #[clap(long, global = true)]
+    /// Show full paths in messages instead of relative ones
+    absolute_paths: bool,
+
+    #[clap(long, global = true)]
+    /// Allow mappings for files outside of the worktree, like absolute paths
+    allow_outside_worktree: bool,
+
+    #[clap(long, global = true)]
+    /// Show the Lua stack traceback of errors in config files
+    trace: bool,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Check that files match a version, for use with the pre-commit framework
+    HookCheck {
+        #[clap(long, value_name = "FILE", num_args = 0..)]
+        /// Files to check, usually the staged files given by the framework
+        files: Vec<PathBuf>,
+
+        #[clap(long, value_name = "VERSION")]
+        /// Expected version [default: version of the nearest tag]
+        expect: Option<String>,
+    },
+
+    /// Create .git-bump.lua in the root of the worktree, with the sample config by default
+    Init {
+        #[clap(long)]
+        /// Generate the config from the manifests and other version files found in the worktree
+        detect: bool,
+
+        #[clap(long, value_enum, value_name = "ECOSYSTEM", conflicts_with = "detect")]
+        /// Use the sample config with only the recipes of this ecosystem
+        ecosystem: Option<Ecosystem>,
+    },
+
+    /// Create .git-bump.lua in the root of the worktree from the config of another tool
+    Import {
+        #[clap(value_enum, value_name = "TOOL")]
+        /// Tool whose config is converted
+        tool: Tool,
+    },
+
+    /// Show what the config files would execute or access outside of the worktree
+    Audit,
+
+    /// Check config files for mistakes and questionable constructs
+    Lint {
+        #[clap(long)]
+        /// Also fail on warnings, not only on errors
+        deny_warnings: bool,
+    },
+
+    /// Show the last bump and whether its files have drifted from its version since
+    Status,
+
+    /// Check the repository, config files, and commit setup for problems
+    Doctor,
+
+    /// Check that all mapped files already match a version byte for byte
+    Verify {
+        #[clap(value_name = "VERSION")]
+        /// Version the files are expected to match
+        version: String,
+    },
+
+    /// Replace the previous version in all tracked text files, after confirmation
+    Replace {
+        #[clap(value_name = "VERSION")]
+        /// Version to replace the previous one with
+        version: String,
+
+        #[clap(long, value_name = "VERSION")]
+        /// Version to replace [default: version of the nearest tag]
+        previous: Option<String>,
+
+        #[clap(long, value_name = "PATHSPEC")]
+        /// Only search files matching this pathspec, can be given multiple times
+        include: Vec<String>,
+
+        #[clap(long, value_name = "PATHSPEC")]
+        /// Do not search files matching this pathspec, in addition to lockfiles and vendored code
+        exclude: Vec<String>,
+
+        #[clap(long, short)]
+        /// Replace without asking for confirmation
+        yes: bool,
+    },
+
+    /// Print release notes of a version, extracted from the changelog
+    Notes {
+        #[clap(value_name = "VERSION")]
+        /// Version whose section is printed
+        version: String,
+
+        #[clap(long, value_name = "FILE")]
+        /// Changelog to read [default: bump.changelog from Git config, or CHANGELOG.md]
+        changelog: Option<PathBuf>,
+    },
+
+    /// Print shell completions
+    Completions {
+        #[clap(value_name = "SHELL")]
+        /// Shell to print completions for
+        shell: Shell,
+    },
+
+    /// Print candidates for the version, used by the shell completions
+    #[clap(name = "__complete-versions", hide = true)]
+    CompleteVersions,
 }
 
-pub(crate) fn run() -> Result<()> {
+/// Completion of versions from existing tags for Bash, both as `git-bump` and `git bump`.
+const BASH_VERSIONS: &str = r#"
+_git-bump-versions() {
+    {function} "$@"
+    if [[ ${COMP_CWORD} -eq 1 && ${COMP_WORDS[COMP_CWORD]} != -* ]]; then
+        COMPREPLY+=($(compgen -W "$(git-bump __complete-versions 2>/dev/null)" -- "${COMP_WORDS[COMP_CWORD]}"))
+    fi
+}
+complete -F _git-bump-versions -o bashdefault -o default git-bump
+
+_git_bump() {
+    case "$cur" in
+    -*) ;;
+    *) __gitcomp "$(git-bump __complete-versions 2>/dev/null)" ;;
+    esac
+}
+"#;
+
+/// Completion of versions from existing tags for Fish.
+const FISH_VERSIONS: &str = r#"
+complete -c git-bump -n "test (count (commandline -opc)) -eq 1" -f -a "(git-bump __complete-versions 2>/dev/null)"
+"#;
+
+/// Print completions for a shell, including versions from existing tags where supported.
+fn print_completions(shell: Shell) {
+    let mut completions = Vec::new();
+    clap_complete::generate(shell, &mut Cli::command(), "git-bump", &mut completions);
+    let completions = String::from_utf8_lossy(&completions);
+    print!("{}", completions);
+
+    match shell {
+        Shell::Bash => {
+            // The name of the generated function differs between versions of clap_complete
+            let function = completions
+                .split("complete -F ")
+                .nth(1)
+                .and_then(|rest| rest.split_whitespace().next())
+                .unwrap_or("_git-bump");
+            print!("{}", BASH_VERSIONS.replace("{function}", function));
+        }
+        Shell::Fish => print!("{}", FISH_VERSIONS),
+        _ => {}
+    }
+}
+
+/// Run the command line interface.
+pub fn run() -> Result<()> {
     let cli = Cli::parse();
+    let trace = cli.trace;
+
+    let result = dispatch(cli);
+    if let (Err(err), true) = (&result, trace) {
+        if let Some(traceback) = err.traceback() {
+            eprintln!("Lua stack traceback:\n{}", traceback);
+        }
+    }
+
+    result
+}
 
-    if let Some(version) = cli.new_version {
-        bump(version)?
+fn dispatch(cli: Cli) -> Result<()> {
+    let settings = Settings {
+        no_override: cli.no_override,
+        no_user_config: cli.no_user_config || no_global_from_env(),
+        no_local_config: cli.no_local_config,
+        no_shared_config: cli.no_shared_config,
+        allow_outside_worktree: cli.allow_outside_worktree,
+        outer_config: cli.outer_config,
+        no_canonicalize: cli.no_canonicalize,
+        absolute_paths: cli.absolute_paths,
+    };
+
+    match cli.command {
+        Some(Command::HookCheck { files, expect }) => {
+            return hook_check(&files, expect, settings);
+        }
+        Some(Command::Init { detect, ecosystem }) => {
+            return init_config(detect, ecosystem, settings);
+        }
+        Some(Command::Import { tool }) => {
+            return import_config(tool, settings);
+        }
+        Some(Command::Audit) => {
+            return audit_configs(settings);
+        }
+        Some(Command::Lint { deny_warnings }) => {
+            return lint_configs(deny_warnings, settings);
+        }
+        Some(Command::Status) => {
+            return print_status(settings);
+        }
+        Some(Command::Doctor) => {
+            return run_doctor(settings);
+        }
+        Some(Command::Verify { version }) => {
+            return verify_files(&version, settings);
+        }
+        Some(Command::Replace {
+            version,
+            previous,
+            include,
+            exclude,
+            yes,
+        }) => {
+            return replace_everywhere(&version, previous, &include, &exclude, yes, settings);
+        }
+        Some(Command::Notes { version, changelog }) => {
+            return print_notes(&version, changelog, settings);
+        }
+        Some(Command::Completions { shell }) => {
+            print_completions(shell);
+            return Ok(());
+        }
+        Some(Command::CompleteVersions) => {
+            return complete_versions();
+        }
+        None => {}
+    }
+
+    let increment = if cli.major {
+        Some(Increment::Major)
+    } else if cli.minor {
+        Some(Increment::Minor)
+    } else if cli.patch {
+        Some(Increment::Patch)
+    } else {
+        None
+    };
+
+    let versions = if cli.from_describe {
+        vec![describe_version()?]
+    } else if let Some(increment) = increment {
+        vec![next_version(
+            increment,
+            cli.pre.as_deref(),
+            cli.build.as_deref(),
+        )?]
+    } else {
+        cli.new_version
+            .iter()
+            .chain(&cli.next_versions)
+            .map(|version| canonical_version(version))
+            .collect::<Result<Vec<_>>>()?
+    };
+
+    if !versions.is_empty() {
+        let mut options = BumpOptions {
+            groups: cli.groups,
+            since: cli.since,
+            tracked_only: cli.tracked_only,
+            materialize: cli.materialize,
+            force_writable: cli.force_writable,
+            index_only: cli.index_only,
+            diff: cli.diff.then(|| cli.diff_mode.unwrap_or_default()),
+            stdout: cli.stdout,
+            allow_empty: cli.allow_empty,
+            #[cfg(feature = "tui")]
+            review: cli.review,
+            no_pager: cli.no_pager,
+            edit: cli.edit,
+            notes: cli.notes,
+            branch: cli.branch,
+            no_switch: cli.no_switch,
+            require_branch: cli.require_branch,
+            jobs: cli.jobs.unwrap_or(1),
+            commit: cli.commit || cli.commit_each,
+            amend: cli.amend,
+            identity: cli.identity,
+            commit_template: cli.commit_template,
+            signoff: cli.signoff,
+            trailers: cli.trailers,
+            tag: cli.tag,
+            force_tag: cli.force_tag,
+            check_remote_tags: cli.check_remote_tags,
+            push: cli.push,
+            #[cfg(feature = "release")]
+            create_release: cli.create_release,
+        };
+
+        for version in versions {
+            let start = Instant::now();
+            let result = bump(version.clone(), &options, settings.clone());
+            metrics::send(&version, options.is_preview(), &result, start.elapsed());
+
+            let report = result?;
+            if cli.json {
+                println!("{}", report.to_json());
+            }
+            if cli.timings {
+                let paths = BumpState::new(None, settings.clone()).get_paths()?;
+                eprint!("{}", report.timings(|path| paths.show(path)));
+            }
+
+            // Only the first version is released, the next ones usually reopen development
+            options.tag = false;
+            options.force_tag = false;
+            options.check_remote_tags = None;
+            #[cfg(feature = "release")]
+            {
+                options.create_release = false;
+            }
+        }
     } else if cli.list_files {
-        list_files()?
-    } else if cli.print_sample_config {
-        print_sample_config()
+        list_files(&cli.groups, cli.long, settings)?
+    } else if let Some(ecosystem) = cli.print_sample_config {
+        print_sample_config(ecosystem)
     }
 
     Ok(())
 }
 
+/// Check if the environment asks to ignore the per-user config file.
+///
+/// `GIT_BUMP_NO_GLOBAL` explicitly enables or disables ignoring the per-user config file. If it is
+/// not set, the per-user config file is ignored on CI systems, which are detected by `CI=true`.
+fn no_global_from_env() -> bool {
+    let is_set =
+        |value: String| matches!(value.to_lowercase().as_str(), "1" | "true" | "yes" | "on");
+
+    match std::env::var("GIT_BUMP_NO_GLOBAL") {
+        Ok(value) => is_set(value),
+        Err(_) => std::env::var("CI").map(is_set).unwrap_or(false),
+    }
+}
+
 #[test]
 fn verify_app() {
     use clap::CommandFactory;
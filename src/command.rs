@@ -0,0 +1,50 @@
+use std::process::{Command, Output};
+
+/// Render command line for messages.
+pub(crate) fn display(command: &Command) -> String {
+    std::iter::once(command.get_program())
+        .chain(command.get_args())
+        .map(|arg| arg.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Run command with captured output.
+///
+/// If the command cannot be started or exits unsuccessfully, the error contains a message with the
+/// captured output, so the caller can decide how to report it.
+pub(crate) fn run(command: &mut Command) -> Result<Output, String> {
+    let output = command.output().map_err(|err| err.to_string())?;
+
+    if output.status.success() {
+        Ok(output)
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let captured = if stderr.trim().is_empty() {
+            stdout.trim()
+        } else {
+            stderr.trim()
+        };
+
+        Err(format!("{}\n{}", output.status, captured)
+            .trim()
+            .to_string())
+    }
+}
+
+/// Build command that runs the given command line through the system shell.
+///
+/// This is used for user-configured commands like pagers and editors, which may contain
+/// arguments, just like Git does it.
+pub(crate) fn shell(command_line: &str) -> Command {
+    if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.arg("/C").arg(command_line);
+        command
+    } else {
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(command_line);
+        command
+    }
+}
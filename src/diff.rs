@@ -0,0 +1,429 @@
+use std::fmt::Write;
+
+/// Number of unchanged lines shown around changes.
+const CONTEXT: usize = 3;
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const CYAN: &str = "\x1b[36m";
+const HIGHLIGHT: &str = "\x1b[7m";
+
+/// Presentation of changes.
+#[derive(Clone, Copy, Default, clap::ValueEnum)]
+pub enum DiffMode {
+    /// Classic unified diff
+    #[default]
+    Unified,
+    /// Old and new lines in two columns
+    SideBySide,
+    /// Changed words inline, like `git diff --word-diff`
+    Word,
+}
+
+/// Single step to transform one sequence into another.
+#[derive(Debug, PartialEq)]
+enum Op<'a> {
+    Equal(&'a str),
+    Delete(&'a str),
+    Insert(&'a str),
+}
+
+/// Compute the steps to transform `old` into `new`, based on their longest common subsequence.
+///
+/// Common prefixes and suffixes are stripped beforehand, which keeps the quadratic part small for
+/// the typical version bump that only touches a few lines.
+fn diff<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<Op<'a>> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+    let (n, m) = (old_middle.len(), new_middle.len());
+
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old_middle[i] == new_middle[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut ops = old[..prefix]
+        .iter()
+        .map(|token| Op::Equal(token))
+        .collect::<Vec<_>>();
+
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if old_middle[i] == new_middle[j] {
+            ops.push(Op::Equal(old_middle[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            ops.push(Op::Delete(old_middle[i]));
+            i += 1;
+        } else {
+            ops.push(Op::Insert(new_middle[j]));
+            j += 1;
+        }
+    }
+    ops.extend(old_middle[i..].iter().map(|token| Op::Delete(token)));
+    ops.extend(new_middle[j..].iter().map(|token| Op::Insert(token)));
+    ops.extend(
+        old[old.len() - suffix..]
+            .iter()
+            .map(|token| Op::Equal(token)),
+    );
+
+    ops
+}
+
+/// Split a line into tokens for word-level comparison.
+///
+/// Runs of alphanumeric characters and the characters commonly found in version strings form a
+/// single token, so a version like `1.2.3-rc.1` is always compared as a whole. Every other
+/// character is a token on its own.
+fn tokenize(line: &str) -> Vec<&str> {
+    let is_word = |c: char| c.is_alphanumeric() || ".-+_".contains(c);
+
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    for (index, c) in line.char_indices() {
+        let end = index + c.len_utf8();
+        let next_is_word = line[end..].chars().next().map(is_word);
+        if !is_word(c) || next_is_word != Some(true) {
+            tokens.push(&line[start..end]);
+            start = end;
+        }
+    }
+
+    tokens
+}
+
+/// Block of consecutive line operations, along with its position in the old and new text.
+struct Hunk<'a> {
+    old_start: usize,
+    new_start: usize,
+    ops: &'a [Op<'a>],
+}
+
+impl Hunk<'_> {
+    /// Render header line of the hunk.
+    fn header(&self) -> String {
+        let old_len = self
+            .ops
+            .iter()
+            .filter(|op| !matches!(op, Op::Insert(_)))
+            .count();
+        let new_len = self
+            .ops
+            .iter()
+            .filter(|op| !matches!(op, Op::Delete(_)))
+            .count();
+
+        format!(
+            "@@ -{},{} +{},{} @@",
+            self.old_start, old_len, self.new_start, new_len
+        )
+    }
+}
+
+/// Group line operations into hunks of changes with some surrounding context.
+fn hunks<'a>(ops: &'a [Op<'a>]) -> Vec<Hunk<'a>> {
+    let changes = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| !matches!(op, Op::Equal(_)))
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for index in changes {
+        let start = index.saturating_sub(CONTEXT);
+        let end = (index + CONTEXT + 1).min(ops.len());
+        match ranges.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => ranges.push((start, end)),
+        }
+    }
+
+    ranges
+        .into_iter()
+        .map(|(start, end)| {
+            let old_start = 1 + ops[..start]
+                .iter()
+                .filter(|op| !matches!(op, Op::Insert(_)))
+                .count();
+            let new_start = 1 + ops[..start]
+                .iter()
+                .filter(|op| !matches!(op, Op::Delete(_)))
+                .count();
+
+            Hunk {
+                old_start,
+                new_start,
+                ops: &ops[start..end],
+            }
+        })
+        .collect()
+}
+
+/// Line of a hunk, with changed lines paired up for word-level comparison.
+enum Row<'a> {
+    Equal(&'a str),
+    Changed(&'a str, &'a str),
+    Delete(&'a str),
+    Insert(&'a str),
+}
+
+/// Pair deleted and inserted lines of each block of changes.
+fn rows<'a>(ops: &[Op<'a>]) -> Vec<Row<'a>> {
+    let mut rows = Vec::new();
+
+    let mut index = 0;
+    while index < ops.len() {
+        if let Op::Equal(line) = ops[index] {
+            rows.push(Row::Equal(line));
+            index += 1;
+            continue;
+        }
+
+        let mut deleted = Vec::new();
+        let mut inserted = Vec::new();
+        while let Some(op) = ops.get(index) {
+            match op {
+                Op::Delete(line) => deleted.push(*line),
+                Op::Insert(line) => inserted.push(*line),
+                Op::Equal(_) => break,
+            }
+            index += 1;
+        }
+
+        let paired = deleted.len().min(inserted.len());
+        for (old, new) in deleted.iter().zip(&inserted) {
+            rows.push(Row::Changed(old, new));
+        }
+        rows.extend(deleted[paired..].iter().map(|line| Row::Delete(line)));
+        rows.extend(inserted[paired..].iter().map(|line| Row::Insert(line)));
+    }
+
+    rows
+}
+
+/// Helper for optionally colored output.
+struct Painter {
+    color: bool,
+}
+
+impl Painter {
+    /// Wrap text in the given style, if colors are enabled.
+    fn paint(&self, style: &str, text: &str) -> String {
+        if self.color && !text.is_empty() {
+            format!("{}{}{}", style, text, RESET)
+        } else {
+            text.to_string()
+        }
+    }
+
+    /// Render one side of a changed line, highlighting the tokens that differ.
+    ///
+    /// At most `width` characters are rendered, the returned number is the count of characters
+    /// actually rendered.
+    fn tokens(&self, ops: &[Op], old: bool, style: &str, width: usize) -> (String, usize) {
+        let mut rendered = String::new();
+        let mut used = 0;
+
+        for op in ops {
+            let (token, changed) = match (op, old) {
+                (Op::Equal(token), _) => (*token, false),
+                (Op::Delete(token), true) | (Op::Insert(token), false) => (*token, true),
+                _ => continue,
+            };
+
+            let token = token.chars().take(width - used).collect::<String>();
+            used += token.chars().count();
+
+            if changed && self.color {
+                rendered.push_str(&self.paint(&format!("{}{}", style, HIGHLIGHT), &token));
+            } else {
+                rendered.push_str(&self.paint(style, &token));
+            }
+
+            if used == width {
+                break;
+            }
+        }
+
+        (rendered, used)
+    }
+}
+
+/// Render changes between the old and new contents of a file.
+///
+/// Returns an empty string if there are no changes. Full paths are shown without the `a/` and `b/`
+/// prefixes, which only make sense for relative ones.
+pub(crate) fn render(path: &str, old: &str, new: &str, mode: DiffMode, color: bool) -> String {
+    let old_lines = old.lines().collect::<Vec<_>>();
+    let new_lines = new.lines().collect::<Vec<_>>();
+
+    let ops = diff(&old_lines, &new_lines);
+    let hunks = hunks(&ops);
+    if hunks.is_empty() {
+        return String::new();
+    }
+
+    let painter = Painter { color };
+    let mut out = String::new();
+
+    let (old_prefix, new_prefix) = match std::path::Path::new(path).is_absolute() {
+        true => ("", ""),
+        false => ("a/", "b/"),
+    };
+    let _ = writeln!(
+        out,
+        "{}",
+        painter.paint(BOLD, &format!("--- {}{}", old_prefix, path))
+    );
+    let _ = writeln!(
+        out,
+        "{}",
+        painter.paint(BOLD, &format!("+++ {}{}", new_prefix, path))
+    );
+
+    let width = std::env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse::<usize>().ok())
+        .unwrap_or(120);
+    let column = width.saturating_sub(3) / 2;
+
+    for hunk in hunks {
+        let _ = writeln!(out, "{}", painter.paint(CYAN, &hunk.header()));
+
+        for row in rows(hunk.ops) {
+            match mode {
+                DiffMode::Unified => render_unified(&mut out, &painter, &row),
+                DiffMode::Word => render_word(&mut out, &painter, &row),
+                DiffMode::SideBySide => render_side_by_side(&mut out, &painter, &row, column),
+            }
+        }
+    }
+
+    out
+}
+
+/// Render a row as unified diff.
+fn render_unified(out: &mut String, painter: &Painter, row: &Row) {
+    let _ = match row {
+        Row::Equal(line) => writeln!(out, " {}", line),
+        Row::Delete(line) => writeln!(out, "{}", painter.paint(RED, &format!("-{}", line))),
+        Row::Insert(line) => writeln!(out, "{}", painter.paint(GREEN, &format!("+{}", line))),
+        Row::Changed(old, new) => {
+            let (old_tokens, new_tokens) = (tokenize(old), tokenize(new));
+            let ops = diff(&old_tokens, &new_tokens);
+            let (old, _) = painter.tokens(&ops, true, RED, usize::MAX);
+            let (new, _) = painter.tokens(&ops, false, GREEN, usize::MAX);
+            writeln!(out, "{}{}", painter.paint(RED, "-"), old)
+                .and_then(|_| writeln!(out, "{}{}", painter.paint(GREEN, "+"), new))
+        }
+    };
+}
+
+/// Render a row as word diff.
+fn render_word(out: &mut String, painter: &Painter, row: &Row) {
+    let deleted = |text: &str| {
+        if painter.color {
+            painter.paint(RED, text)
+        } else {
+            format!("[-{}-]", text)
+        }
+    };
+    let inserted = |text: &str| {
+        if painter.color {
+            painter.paint(GREEN, text)
+        } else {
+            format!("{{+{}+}}", text)
+        }
+    };
+
+    let _ = match row {
+        Row::Equal(line) => writeln!(out, "{}", line),
+        Row::Delete(line) => writeln!(out, "{}", deleted(line)),
+        Row::Insert(line) => writeln!(out, "{}", inserted(line)),
+        Row::Changed(old, new) => {
+            let (old_tokens, new_tokens) = (tokenize(old), tokenize(new));
+
+            let mut line = String::new();
+            for op in diff(&old_tokens, &new_tokens) {
+                match op {
+                    Op::Equal(token) => line.push_str(token),
+                    Op::Delete(token) => line.push_str(&deleted(token)),
+                    Op::Insert(token) => line.push_str(&inserted(token)),
+                }
+            }
+
+            writeln!(out, "{}", line)
+        }
+    };
+}
+
+/// Render a row as two columns of the given width.
+fn render_side_by_side(out: &mut String, painter: &Painter, row: &Row, column: usize) {
+    let plain = |line: &str, style: &str| {
+        let line = line.chars().take(column).collect::<String>();
+        let used = line.chars().count();
+        (painter.paint(style, &line), used)
+    };
+
+    let ((left, left_used), marker, right) = match row {
+        Row::Equal(line) => (plain(line, ""), ' ', plain(line, "").0),
+        Row::Delete(line) => (plain(line, RED), '<', String::new()),
+        Row::Insert(line) => ((String::new(), 0), '>', plain(line, GREEN).0),
+        Row::Changed(old, new) => {
+            let (old_tokens, new_tokens) = (tokenize(old), tokenize(new));
+            let ops = diff(&old_tokens, &new_tokens);
+            (
+                painter.tokens(&ops, true, RED, column),
+                '|',
+                painter.tokens(&ops, false, GREEN, column).0,
+            )
+        }
+    };
+
+    let padding = " ".repeat(column - left_used);
+    let line = format!("{}{} {} {}", left, padding, marker, right);
+    let _ = writeln!(out, "{}", line.trim_end());
+}
+
+#[test]
+fn render_unified_diff() {
+    let old = "a\nb\nversion = \"1.2.3\"\nc\nd\ne\nf\n";
+    let new = "a\nb\nversion = \"1.3.0\"\nc\nd\ne\nf\n";
+
+    assert_eq!(
+        render("Cargo.toml", old, new, DiffMode::Unified, false),
+        "--- a/Cargo.toml\n+++ b/Cargo.toml\n@@ -1,6 +1,6 @@\n a\n b\n\
+         -version = \"1.2.3\"\n+version = \"1.3.0\"\n c\n d\n e\n"
+    );
+}
+
+#[test]
+fn render_word_diff() {
+    let old = "version = \"1.2.3-rc.1\"\n";
+    let new = "version = \"1.2.3\"\n";
+
+    assert_eq!(
+        render("Cargo.toml", old, new, DiffMode::Word, false),
+        "--- a/Cargo.toml\n+++ b/Cargo.toml\n@@ -1,1 +1,1 @@\n\
+         version = \"[-1.2.3-rc.1-]{+1.2.3+}\"\n"
+    );
+}
@@ -0,0 +1,215 @@
+use std::fmt;
+use std::fs;
+use std::process::{Command, Stdio};
+
+use mlua::prelude::*;
+
+use crate::engine::{self, Disk, Workspace};
+use crate::git::{self, Identity};
+use crate::state::{Settings, State};
+
+/// Outcome of a diagnostic check.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Status {
+    Pass,
+    Warn,
+    Fail,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(match self {
+            Status::Pass => "pass",
+            Status::Warn => "warn",
+            Status::Fail => "fail",
+        })
+    }
+}
+
+/// Result of a single diagnostic check.
+pub(crate) struct Check {
+    pub(crate) status: Status,
+    /// Aspect of the environment that was checked.
+    pub(crate) topic: &'static str,
+    pub(crate) message: String,
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:<6}{}: {}", self.status, self.topic, self.message)
+    }
+}
+
+/// Check the environment git-bump runs in, without changing anything.
+///
+/// Checked are the repository, the config files and the Lua runtime they run in, whether the
+/// mapped files can be written, and the identity and signing setup for commits and tags. If there
+/// is no repository, the other checks are skipped.
+pub(crate) fn diagnose(settings: Settings) -> Vec<Check> {
+    let mut checks = Vec::new();
+    let mut check = |status, topic, message: String| {
+        checks.push(Check {
+            status,
+            topic,
+            message,
+        })
+    };
+
+    let mut state = State::new(None, settings);
+    let (repository, workdir) = match state.get_repository().and_then(|repository| {
+        let workdir = state.get_workdir()?;
+        Ok((repository, workdir))
+    }) {
+        Ok(found) => found,
+        Err(err) => {
+            check(Status::Fail, "repository", err.to_string());
+            return checks;
+        }
+    };
+    check(
+        Status::Pass,
+        "repository",
+        format!("Found worktree {}", workdir.to_string_lossy()),
+    );
+
+    let config = match repository.config() {
+        Ok(config) => config,
+        Err(err) => {
+            check(Status::Fail, "git config", err.to_string());
+            return checks;
+        }
+    };
+    let paths = match state.get_paths() {
+        Ok(paths) => paths,
+        Err(err) => {
+            check(Status::Fail, "git config", err.to_string());
+            return checks;
+        }
+    };
+
+    let lua = Lua::new();
+    let runtime = lua
+        .globals()
+        .get::<_, Option<LuaTable>>("jit")
+        .ok()
+        .flatten()
+        .and_then(|jit| jit.get::<_, String>("version").ok())
+        .or_else(|| lua.globals().get::<_, String>("_VERSION").ok())
+        .unwrap_or_else(|| String::from("unknown Lua version"));
+    check(Status::Pass, "lua", format!("Running {}", runtime));
+
+    let configs = match state.get_config_files() {
+        Ok(configs) => configs,
+        Err(err) => {
+            check(Status::Fail, "config", err.to_string());
+            return checks;
+        }
+    };
+    if configs.is_empty() {
+        check(
+            Status::Warn,
+            "config",
+            String::from("No config file found, create one with `git bump init`"),
+        );
+    }
+    let mut configs_valid = true;
+    for file in configs.iter() {
+        let name = paths.show(file);
+        match fs::read_to_string(file) {
+            Ok(source) => match engine::load_chunk(&lua, &format!("@{}", name), &source) {
+                Ok(_) => check(Status::Pass, "config", format!("Parsed {}", name)),
+                Err(err) => {
+                    configs_valid = false;
+                    check(Status::Fail, "config", err.to_string());
+                }
+            },
+            Err(err) => {
+                configs_valid = false;
+                check(Status::Fail, "config", format!("{}: {}", name, err));
+            }
+        }
+    }
+
+    if configs_valid {
+        match state.get_file_mapping() {
+            Ok(map) => {
+                let mut files = map.iter().collect::<Vec<_>>();
+                files.sort_by_key(|(file, _)| *file);
+
+                let mut writable = 0;
+                for (file, mapping) in files {
+                    match Disk.check_writable(file) {
+                        Ok(()) => writable += 1,
+                        Err(err) => check(
+                            match mapping.optional {
+                                true => Status::Warn,
+                                false => Status::Fail,
+                            },
+                            "files",
+                            format!("{} is not writable: {}", paths.show(file), err),
+                        ),
+                    }
+                }
+                check(
+                    Status::Pass,
+                    "files",
+                    format!("{} mapped file(s) can be written", writable),
+                );
+            }
+            Err(err) => check(Status::Fail, "files", err.to_string()),
+        }
+    }
+
+    match Identity::resolve(&repository, None) {
+        Ok(identity) => check(
+            Status::Pass,
+            "identity",
+            format!("Committing as {}", identity),
+        ),
+        Err(_) => check(
+            Status::Warn,
+            "identity",
+            String::from("No identity found, set user.name and user.email, or bump.identity, to use --commit"),
+        ),
+    }
+
+    match git::signing_program(&config) {
+        Ok(Some(program)) => {
+            // Only check that the program can be started, ssh-keygen has no `--version`
+            let runs = Command::new(&program)
+                .arg("--version")
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .is_ok();
+            match runs {
+                true => check(
+                    Status::Pass,
+                    "signing",
+                    format!("Signing commits with {}", program),
+                ),
+                false => check(
+                    Status::Fail,
+                    "signing",
+                    format!("Commits are to be signed, but {} cannot be run", program),
+                ),
+            }
+        }
+        Ok(None) => check(
+            Status::Pass,
+            "signing",
+            String::from("Commits are not signed"),
+        ),
+        Err(err) => check(Status::Fail, "signing", err.to_string()),
+    }
+    if config.get_bool("tag.gpgSign").unwrap_or(false) {
+        check(
+            Status::Warn,
+            "signing",
+            String::from("tag.gpgSign is set, but tags created with --tag are not signed"),
+        );
+    }
+
+    checks
+}
@@ -0,0 +1,62 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+
+use crate::{command, Error, Result};
+
+/// Get editor command line of the user.
+///
+/// The editor is looked up just like Git does it: `GIT_EDITOR`, `core.editor`, `VISUAL`,
+/// `EDITOR`, and finally `vi`.
+pub(crate) fn resolve(core_editor: Option<String>) -> String {
+    env::var("GIT_EDITOR")
+        .ok()
+        .or(core_editor)
+        .or_else(|| env::var("VISUAL").ok())
+        .or_else(|| env::var("EDITOR").ok())
+        .unwrap_or_else(|| String::from("vi"))
+}
+
+/// Open file in the given editor and wait until it is closed.
+pub(crate) fn edit(editor: &str, file: &Path) -> Result<()> {
+    let mut command = if cfg!(windows) {
+        command::shell(&format!("{} \"{}\"", editor, file.to_string_lossy()))
+    } else {
+        let mut command = command::shell(&format!("{} \"$@\"", editor));
+        command.arg(editor).arg(file);
+        command
+    };
+
+    let status = command.status().map_err(|source| Error::EditorFailed {
+        editor: editor.to_string(),
+        reason: source.to_string(),
+    })?;
+
+    if !status.success() {
+        return Err(Error::EditorFailed {
+            editor: editor.to_string(),
+            reason: status.to_string(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Let the user write a text in the given editor, using the given file as buffer.
+///
+/// The buffer is prefilled with the template. Just like with commit messages, lines starting with
+/// `#` are removed afterwards. Returns `None` if nothing but whitespace is left.
+pub(crate) fn prompt(editor: &str, file: &Path, template: &str) -> Result<Option<String>> {
+    fs::write(file, template).map_err(|source| Error::WriteFailed { source })?;
+    edit(editor, file)?;
+    let text = fs::read_to_string(file).map_err(|source| Error::ReadFailed { source })?;
+
+    let text = text
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let text = text.trim();
+
+    Ok((!text.is_empty()).then(|| text.to_string()))
+}
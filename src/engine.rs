@@ -0,0 +1,834 @@
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io;
+use std::path::Path;
+use std::sync::PoisonError;
+
+use mlua::prelude::*;
+
+use crate::hooks::CURRENT_DIR;
+use crate::mapping::Mapping;
+use crate::{project, Error, Result};
+
+/// Source of the bundled helpers library.
+const HELPERS: &str = include_str!("helpers.lua");
+
+/// Members of the table returned by a bumping function that configure the hooks of its file.
+const HOOK_OPTIONS: &[&str] = &[
+    "pre_func",
+    "pre_cmd",
+    "post_func",
+    "post_cmd",
+    "pre_write",
+    "post_retries",
+    "post_retry_delay",
+    "lockfile",
+    "dir",
+];
+
+/// Further members of the table returned by a bumping function.
+const FILE_OPTIONS: &[&str] = &["skip", "create", "mode", "encoding"];
+
+/// Access to the files that are bumped.
+///
+/// Files are read and written through this trait instead of the file system directly, so that
+/// `--index-only` can bump them in the index instead of the worktree.
+pub(crate) trait Workspace {
+    /// Read contents of a file.
+    fn read(&self, file: &Path) -> io::Result<String>;
+
+    /// Write contents of a file.
+    fn write(&self, file: &Path, contents: &str) -> io::Result<()>;
+
+    /// Read contents of a file as bytes, for mappings with `raw = true`.
+    fn read_bytes(&self, file: &Path) -> io::Result<Vec<u8>> {
+        self.read(file).map(String::into_bytes)
+    }
+
+    /// Write contents of a file as bytes, which may not be valid UTF-8 for raw mappings.
+    fn write_bytes(&self, file: &Path, contents: &[u8]) -> io::Result<()> {
+        let contents = std::str::from_utf8(contents)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        self.write(file, contents)
+    }
+
+    /// Check if a file exists.
+    fn exists(&self, file: &Path) -> bool;
+
+    /// Check if a file can be written, without changing it.
+    fn check_writable(&self, _file: &Path) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Workspace on the local disk, with paths relative to the current working directory.
+pub(crate) struct Disk;
+
+impl Workspace for Disk {
+    fn read(&self, file: &Path) -> io::Result<String> {
+        fs::read_to_string(file)
+    }
+
+    fn write(&self, file: &Path, contents: &str) -> io::Result<()> {
+        fs::write(file, contents)
+    }
+
+    fn read_bytes(&self, file: &Path) -> io::Result<Vec<u8>> {
+        fs::read(file)
+    }
+
+    fn write_bytes(&self, file: &Path, contents: &[u8]) -> io::Result<()> {
+        fs::write(file, contents)
+    }
+
+    fn exists(&self, file: &Path) -> bool {
+        file.exists()
+    }
+
+    fn check_writable(&self, file: &Path) -> io::Result<()> {
+        // Opening for appending catches missing permissions as well as read-only file systems
+        OpenOptions::new().append(true).open(file).map(|_| ())
+    }
+}
+
+/// Change of a single file.
+#[cfg(any(test, feature = "tui"))]
+#[derive(Debug)]
+pub(crate) struct Change {
+    /// File name as given in the config.
+    pub(crate) file: String,
+    /// Contents before bumping.
+    pub(crate) old: String,
+    /// Contents after bumping.
+    pub(crate) new: String,
+}
+
+/// Read contents of a file for a mapping.
+///
+/// Only raw mappings accept contents that are not valid UTF-8.
+pub(crate) fn read(
+    workspace: &dyn Workspace,
+    mapping: &Mapping,
+    file: &Path,
+) -> io::Result<Vec<u8>> {
+    match mapping.raw {
+        true => workspace.read_bytes(file),
+        false => workspace.read(file).map(String::into_bytes),
+    }
+}
+
+/// Load Lua source as a function, named like in Lua's own `load`.
+pub(crate) fn load_chunk<'lua>(
+    lua: &'lua Lua,
+    name: &str,
+    source: &str,
+) -> LuaResult<LuaFunction<'lua>> {
+    // Lua's own loader is used since it takes the chunk name the same way in all Lua versions, but
+    // Lua 5.1 only accepts strings in `loadstring`
+    let load = match lua.globals().get::<_, Option<LuaFunction>>("loadstring")? {
+        Some(loadstring) => loadstring,
+        None => lua.globals().get::<_, LuaFunction>("load")?,
+    };
+    match load.call::<_, (Option<LuaFunction>, Option<String>)>((source, name))? {
+        (Some(chunk), _) => Ok(chunk),
+        (None, message) => Err(LuaError::SyntaxError {
+            message: message.unwrap_or_default(),
+            incomplete_input: false,
+        }),
+    }
+}
+
+/// Function finding the names and directories of the members of a workspace.
+type FindMembers = fn(&Path) -> Vec<(String, String)>;
+
+/// Provide the bundled helpers library to the config files.
+///
+/// The library is available as the global `helpers` table, and via `require("git-bump.helpers")`.
+/// Its combinators are available in the global `bump` table as well.
+pub(crate) fn load_helpers(lua: &Lua) -> LuaResult<()> {
+    let helpers = load_chunk(lua, "=git-bump.helpers", HELPERS)?.call::<_, LuaTable>(())?;
+    let finders: [(&str, FindMembers); 2] = [
+        ("cargo_workspace_members", project::cargo_workspace_members),
+        ("npm_workspace_members", project::npm_workspace_members),
+    ];
+    for (name, find_members) in finders {
+        helpers.set(
+            name,
+            lua.create_function(move |lua, root: String| {
+                let members = find_members(Path::new(&root))
+                    .into_iter()
+                    .map(|(name, path)| lua.create_table_from([("name", name), ("path", path)]))
+                    .collect::<LuaResult<Vec<_>>>()?;
+                lua.create_sequence_from(members)
+            })?,
+        )?;
+    }
+
+    let loaded = lua
+        .globals()
+        .get::<_, LuaTable>("package")?
+        .get::<_, LuaTable>("loaded")?;
+    loaded.set("git-bump.helpers", helpers.clone())?;
+
+    let bump = lua.create_table()?;
+    for name in ["chain", "when"] {
+        bump.set(name, helpers.get::<_, LuaFunction>(name)?)?;
+    }
+    lua.globals().set("bump", bump)?;
+    lua.globals().set("helpers", helpers)
+}
+
+/// Evaluate config source to a map of file names and mappings.
+///
+/// A config file can either return the map directly or a function that takes the version to bump
+/// to and returns the map, so the set of mappings itself can depend on the version.
+///
+/// The chunk is named after the config file, so that error messages and tracebacks point to its
+/// lines, like `.git-bump.lua:12: attempt to index a nil value`.
+pub(crate) fn eval_config<'lua>(
+    lua: &'lua Lua,
+    name: &str,
+    source: &str,
+    version: Option<&str>,
+) -> Result<BTreeMap<String, LuaValue<'lua>>> {
+    let loading_failed = |source| Error::LuaLoadingFailed { source };
+    let chunk = load_chunk(lua, &format!("@{}", name), source).map_err(loading_failed)?;
+
+    let (value, returned_by) = match chunk.call::<_, LuaValue>(()).map_err(loading_failed)? {
+        LuaValue::Function(func) => (
+            func.call::<_, LuaValue>(version).map_err(loading_failed)?,
+            "the function returned by it",
+        ),
+        value => (value, "it"),
+    };
+
+    let table = match value {
+        LuaValue::Table(table) => table,
+        value => {
+            let hint = match value {
+                LuaValue::Nil => format!("did {} forget to `return` the mappings?", returned_by),
+                _ => String::from(
+                    "return a table that maps file names to functions, like `return {VERSION = helpers.plain_version_file}`",
+                ),
+            };
+            return Err(Error::ConfigNotATable {
+                config: name.to_string(),
+                type_name: value.type_name().to_string(),
+                hint,
+            });
+        }
+    };
+
+    let mut map = BTreeMap::new();
+    for pair in table.pairs::<LuaValue, LuaValue>() {
+        let (key, value) = pair.map_err(loading_failed)?;
+        let key = match key {
+            LuaValue::String(key) => key.to_str().map_err(loading_failed)?.to_string(),
+            key => {
+                return Err(Error::InvalidConfigKey {
+                    config: name.to_string(),
+                    type_name: key.type_name().to_string(),
+                })
+            }
+        };
+        map.insert(key, value);
+    }
+
+    Ok(map)
+}
+
+/// Check the `enabled` guard of a mapping for the given version.
+///
+/// The guard gets the version and the global `ctx` table. Mappings without a guard are always
+/// enabled.
+pub(crate) fn is_enabled(lua: &Lua, mapping: &Mapping, version: &str) -> Result<bool> {
+    let enabled = match &mapping.enabled {
+        Some(enabled) => lua.registry_value::<LuaFunction>(enabled)?,
+        None => return Ok(true),
+    };
+
+    let ctx = lua.globals().get::<_, LuaValue>("ctx")?;
+    let _current_dir = CURRENT_DIR.read().unwrap_or_else(PoisonError::into_inner);
+    enabled
+        .call::<_, bool>((version, ctx))
+        .map_err(|source| Error::LuaEnabledFuncFailed { source })
+}
+
+/// Byte order mark of UTF-8.
+const BOM: &[u8] = "\u{feff}".as_bytes();
+
+/// Encoding of the new contents of a file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Encoding {
+    /// UTF-8 without byte order mark.
+    Utf8,
+    /// UTF-8 starting with a byte order mark, as expected by some Windows tools.
+    Utf8Bom,
+}
+
+/// Values returned by a bumping function along with the new contents.
+///
+/// Besides the new contents, a bumping function may return a table with the hooks of its file
+/// and further options. For chained mappings, the hooks of all tables are run, while later
+/// options override earlier ones.
+#[derive(Default)]
+pub(crate) struct Returned<'lua> {
+    /// Tables with hooks, in the order they were returned.
+    pub(crate) hooks: Vec<LuaTable<'lua>>,
+    /// Leave the file untouched, ignoring the new contents.
+    pub(crate) skip: bool,
+    /// Create the file if it does not exist.
+    pub(crate) create: bool,
+    /// Unix permissions of the written file.
+    pub(crate) mode: Option<u32>,
+    /// Encoding of the new contents, left as returned if not given.
+    pub(crate) encoding: Option<Encoding>,
+}
+
+impl<'lua> Returned<'lua> {
+    /// Validate values returned by a bumping function, and get the new contents.
+    ///
+    /// The options of the returned table, if any, are added to the ones returned before. Unless
+    /// the mapping is raw, the new contents have to be valid UTF-8.
+    fn add(&mut self, lua: &'lua Lua, values: LuaMultiValue<'lua>, raw: bool) -> Result<Vec<u8>> {
+        let invalid = |returned: String, expected: &str| Error::InvalidReturnValue {
+            returned,
+            expected: expected.to_string(),
+        };
+
+        if values.len() > 2 {
+            return Err(invalid(
+                format!("{} values", values.len()),
+                "the new contents, optionally followed by a table of hooks and options",
+            ));
+        }
+        let mut values = values.into_iter();
+        let contents = checked_contents(lua, values.next(), raw, "contents")?;
+
+        let table = match values.next() {
+            None | Some(LuaValue::Nil) => return Ok(contents),
+            Some(LuaValue::Table(table)) => table,
+            Some(value) => {
+                return Err(invalid(
+                    format!("{} as options", value.type_name()),
+                    "a table of hooks and options",
+                ))
+            }
+        };
+
+        for pair in table.clone().pairs::<LuaValue, LuaValue>() {
+            let (name, _) = pair?;
+            let returned = match &name {
+                LuaValue::String(name) => match name.to_str() {
+                    Ok(name) if HOOK_OPTIONS.contains(&name) || FILE_OPTIONS.contains(&name) => {
+                        continue
+                    }
+                    _ => format!("unknown option `{}`", name.to_string_lossy()),
+                },
+                name => format!("an option named by a {}", name.type_name()),
+            };
+            return Err(invalid(
+                returned,
+                "hooks like `pre_cmd`, or one of the options `skip`, `create`, `mode`, and \
+                 `encoding`",
+            ));
+        }
+
+        for (name, option) in [("skip", &mut self.skip), ("create", &mut self.create)] {
+            match table.get::<_, LuaValue>(name)? {
+                LuaValue::Nil => {}
+                LuaValue::Boolean(value) => *option = value,
+                value => {
+                    return Err(invalid(
+                        format!("{} as `{}`", value.type_name(), name),
+                        "a boolean",
+                    ))
+                }
+            }
+        }
+
+        match table.get::<_, LuaValue>("mode")? {
+            LuaValue::Nil => {}
+            LuaValue::String(mode) => {
+                let mode = mode.to_string_lossy();
+                let parsed = u32::from_str_radix(&mode, 8)
+                    .ok()
+                    .filter(|mode| *mode <= 0o7777);
+                match parsed {
+                    Some(parsed) => self.mode = Some(parsed),
+                    None => {
+                        return Err(invalid(
+                            format!("{:?} as `mode`", mode),
+                            "octal permissions, like \"755\"",
+                        ))
+                    }
+                }
+            }
+            value => {
+                return Err(invalid(
+                    format!("{} as `mode`", value.type_name()),
+                    "octal permissions as string, like \"755\"",
+                ))
+            }
+        }
+
+        match table.get::<_, LuaValue>("encoding")? {
+            LuaValue::Nil => {}
+            LuaValue::String(encoding) => match encoding.to_str() {
+                Ok("utf-8") => self.encoding = Some(Encoding::Utf8),
+                Ok("utf-8-bom") => self.encoding = Some(Encoding::Utf8Bom),
+                _ => {
+                    return Err(invalid(
+                        format!("{:?} as `encoding`", encoding.to_string_lossy()),
+                        "\"utf-8\" or \"utf-8-bom\"",
+                    ))
+                }
+            },
+            value => {
+                return Err(invalid(
+                    format!("{} as `encoding`", value.type_name()),
+                    "\"utf-8\" or \"utf-8-bom\"",
+                ))
+            }
+        }
+
+        self.hooks.push(table);
+        Ok(contents)
+    }
+}
+
+/// Validate contents returned by a bumping function or a `pre_write` hook.
+///
+/// Unless the mapping is raw, the contents have to be valid UTF-8. The description of what was
+/// returned is used in errors.
+fn checked_contents(
+    lua: &Lua,
+    value: Option<LuaValue>,
+    raw: bool,
+    description: &str,
+) -> Result<Vec<u8>> {
+    let invalid = |returned: String, expected: &str| Error::InvalidReturnValue {
+        returned,
+        expected: expected.to_string(),
+    };
+
+    let contents = match value {
+        Some(value @ (LuaValue::String(_) | LuaValue::Integer(_) | LuaValue::Number(_))) => {
+            lua.unpack::<LuaString>(value)?.as_bytes().to_vec()
+        }
+        value => {
+            let type_name = value.as_ref().map_or("nothing", LuaValue::type_name);
+            return Err(invalid(
+                format!("{} as {}", type_name, description),
+                "the new contents as string",
+            ));
+        }
+    };
+
+    if !raw && std::str::from_utf8(&contents).is_err() {
+        return Err(invalid(
+            format!("{} that are not valid UTF-8", description),
+            "text, or `raw = true` in the mapping for binary contents",
+        ));
+    }
+
+    Ok(contents)
+}
+
+/// Run bumping function of a mapping on the given contents.
+///
+/// Chained mappings are run one after another, each on the result of the previous one, unless
+/// their `enabled` guard says otherwise. The guard of the mapping itself is left to the caller.
+///
+/// Contents are passed to the functions as Lua strings, which are byte strings anyway, so raw
+/// mappings get and return them verbatim.
+///
+/// Afterwards, the `pre_write` hooks of the returned tables get the contents one after another,
+/// along with a table of the key of the mapping and the version, and return them modified, for
+/// example by a formatter. Unlike other hooks, they run for previews and checks as well, so those
+/// show exactly what would be written.
+///
+/// Returns the new contents, ending with a newline unless the mapping opts out and encoded as
+/// requested, and the values returned along the way.
+pub(crate) fn apply<'lua>(
+    lua: &'lua Lua,
+    mapping: &Mapping,
+    version: &str,
+    contents: &[u8],
+) -> Result<(Vec<u8>, Returned<'lua>)> {
+    let mut contents = contents.to_vec();
+    let mut returned = Returned::default();
+
+    for (index, link) in mapping.links().enumerate() {
+        if index > 0 && !is_enabled(lua, link, version)? {
+            continue;
+        }
+
+        let f = lua.registry_value::<LuaFunction>(&link.func)?;
+        let _current_dir = CURRENT_DIR.read().unwrap_or_else(PoisonError::into_inner);
+        let values = f
+            .call::<_, LuaMultiValue>((version, lua.create_string(&contents)?))
+            .map_err(|source| Error::LuaExecutionFailed { source })?;
+        contents = returned.add(lua, values, mapping.raw)?;
+    }
+
+    for hooks in &returned.hooks {
+        if let Some(pre_write) = hooks.get::<_, Option<LuaFunction>>("pre_write")? {
+            let ctx = lua.create_table()?;
+            ctx.set("key", mapping.key.as_str())?;
+            ctx.set("version", version)?;
+
+            let _current_dir = CURRENT_DIR.read().unwrap_or_else(PoisonError::into_inner);
+            let value = pre_write
+                .call::<_, LuaValue>((lua.create_string(&contents)?, ctx))
+                .map_err(|source| Error::LuaHookFailed {
+                    hook: "pre_write",
+                    source,
+                })?;
+            contents =
+                checked_contents(lua, Some(value), mapping.raw, "contents from `pre_write`")?;
+        }
+    }
+
+    if mapping.ensure_trailing_newline && !contents.ends_with(b"\n") {
+        contents.push(b'\n')
+    }
+    match returned.encoding {
+        Some(Encoding::Utf8) => {
+            if let Some(stripped) = contents.strip_prefix(BOM) {
+                contents = stripped.to_vec();
+            }
+        }
+        Some(Encoding::Utf8Bom) if !contents.starts_with(BOM) => {
+            contents.splice(0..0, BOM.iter().copied());
+        }
+        _ => {}
+    }
+
+    Ok((contents, returned))
+}
+
+/// Minimal bump pipeline for tests, independent of Git and the file system.
+///
+/// It evaluates config sources and transforms file contents just like a bump, but leaves finding
+/// the config files, running hooks, and writing files out.
+#[cfg(test)]
+struct Engine {
+    lua: Lua,
+    mappings: BTreeMap<String, Mapping>,
+}
+
+#[cfg(test)]
+impl Engine {
+    /// Create engine without any mappings.
+    ///
+    /// Just like when running on a repository, the configs can fill the global `hooks` table, and
+    /// a global `ctx` table is available, but without any information about a repository. The
+    /// bundled helpers library is available as well.
+    fn new() -> Result<Self> {
+        let lua = Lua::new();
+        load_helpers(&lua)?;
+        lua.globals().set("hooks", lua.create_table()?)?;
+        lua.globals().set("ctx", lua.create_table()?)?;
+
+        Ok(Engine {
+            lua,
+            mappings: BTreeMap::new(),
+        })
+    }
+
+    /// Load mappings from the source of a config file.
+    ///
+    /// Mappings of later configs override those of earlier ones, unless they are chained to them.
+    /// The name is used for error messages only.
+    fn load_config(&mut self, name: &str, source: &str, version: Option<&str>) -> Result<()> {
+        let map = eval_config(&self.lua, name, source, version)?;
+
+        for (key, value) in map {
+            let mapping = Mapping::from_lua(&self.lua, &key, value, Path::new(name))?;
+            let key = crate::mapping::key_components(&key).join("/");
+            if mapping.chain {
+                if let Some(previous) = self.mappings.get_mut(&key) {
+                    previous.append(mapping);
+                    continue;
+                }
+            }
+            if let Some(mapping) = self.mappings.insert(key, mapping) {
+                mapping.remove(&self.lua)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get the file names of all loaded mappings, sorted.
+    fn files(&self) -> impl Iterator<Item = &str> {
+        self.mappings.keys().map(String::as_str)
+    }
+
+    /// Compute changes for all existing files of the workspace, without writing anything.
+    ///
+    /// Files whose contents do not change are left out.
+    fn preview(&self, workspace: &dyn Workspace, version: &str) -> Result<Vec<Change>> {
+        let mut changes = Vec::new();
+
+        for (file, mapping) in &self.mappings {
+            let path = Path::new(file);
+            if !workspace.exists(path) || !is_enabled(&self.lua, mapping, version)? {
+                continue;
+            }
+
+            let old =
+                read(workspace, mapping, path).map_err(|source| Error::ReadFailed { source })?;
+            let (new, returned) = apply(&self.lua, mapping, version, &old)?;
+
+            // Changes are text, so bytes of raw mappings that are not valid UTF-8 are replaced
+            if new != old && !returned.skip {
+                changes.push(Change {
+                    file: file.clone(),
+                    old: String::from_utf8_lossy(&old).into_owned(),
+                    new: String::from_utf8_lossy(&new).into_owned(),
+                });
+            }
+        }
+
+        Ok(changes)
+    }
+}
+
+#[test]
+fn preview_changes_of_config() {
+    struct Files;
+
+    impl Workspace for Files {
+        fn read(&self, file: &Path) -> io::Result<String> {
+            Ok(format!("{} 1.0.0\n", file.display()))
+        }
+
+        fn write(&self, _file: &Path, _contents: &str) -> io::Result<()> {
+            unreachable!()
+        }
+
+        fn exists(&self, file: &Path) -> bool {
+            file != Path::new("missing")
+        }
+    }
+
+    let mut engine = Engine::new().unwrap();
+    engine
+        .load_config(
+            "first",
+            r#"return {
+                VERSION = function(version) return "old" end,
+                missing = function(version) return version end,
+                ["data.json"] = {
+                    func = function(version) return "{}" end,
+                    ensure_trailing_newline = false,
+                },
+                CHANGES = {
+                    function(version) return "v" end,
+                    function(version, content) return content .. version end,
+                },
+            }"#,
+            None,
+        )
+        .unwrap();
+    engine
+        .load_config(
+            "second",
+            r#"return function(version)
+                return {
+                    VERSION = function(version) return version end,
+                    same = function(version, content) return content end,
+                    ["data.json"] = {
+                        func = function(version, content) return content .. version end,
+                        chain = true,
+                    },
+                }
+            end"#,
+            Some("2.0.0"),
+        )
+        .unwrap();
+
+    assert_eq!(
+        engine.files().collect::<Vec<_>>(),
+        ["CHANGES", "VERSION", "data.json", "missing", "same"]
+    );
+
+    let changes = engine.preview(&Files, "2.0.0").unwrap();
+    assert_eq!(changes.len(), 3);
+    assert_eq!(changes[0].new, "v2.0.0\n");
+    assert_eq!(changes[1].file, "VERSION");
+    assert_eq!(changes[1].old, "VERSION 1.0.0\n");
+    assert_eq!(changes[1].new, "2.0.0\n");
+    assert_eq!(changes[2].new, "{}2.0.0");
+
+    let err = engine
+        .load_config("third", "local mappings = {}", None)
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Config third must return a table, got nil: did it forget to `return` the mappings?"
+    );
+    let err = engine
+        .load_config("fourth", "return {function(version) end}", None)
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .starts_with("Keys of config fourth must be file names, got integer"));
+}
+
+#[test]
+fn use_bundled_helpers() {
+    struct Files;
+
+    impl Workspace for Files {
+        fn read(&self, file: &Path) -> io::Result<String> {
+            Ok(match file.to_str() {
+                Some("Cargo.toml") => concat!(
+                    "[package]\nname = \"x\"\nversion = \"1.0.0\"\n\n",
+                    "[dependencies]\ny = { version = \"1.0.0\" }\n"
+                )
+                .to_string(),
+                Some("package.json") => String::from("{\n  \"version\": \"1.0.0\"\n}\n"),
+                _ => String::from("1.0.0\n"),
+            })
+        }
+
+        fn write(&self, _file: &Path, _contents: &str) -> io::Result<()> {
+            unreachable!()
+        }
+
+        fn exists(&self, _file: &Path) -> bool {
+            true
+        }
+    }
+
+    let mut engine = Engine::new().unwrap();
+    engine
+        .load_config(
+            "config",
+            r#"return {
+                VERSION = helpers.plain_version_file,
+                ["Cargo.toml"] = helpers.cargo_toml,
+                ["package.json"] = require("git-bump.helpers").package_json,
+                CHANGES = bump.chain(
+                    helpers.plain_version_file,
+                    bump.when(
+                        function(version) return not version:find("-") end,
+                        function(version, content) return content .. " (stable)" end
+                    )
+                ),
+                FORMATTED = function(version)
+                    return "v" .. version, {
+                        pre_write = function(content, ctx) return content:upper() .. " " .. ctx.key end,
+                    }
+                end,
+            }"#,
+            None,
+        )
+        .unwrap();
+
+    let changes = engine.preview(&Files, "2.0.0").unwrap();
+    let new = changes
+        .iter()
+        .map(|change| change.new.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        new,
+        [
+            "2.0.0 (stable)\n",
+            "[package]\nname = \"x\"\nversion = \"2.0.0\"\n\n[dependencies]\ny = { version = \"1.0.0\" }\n",
+            "V2.0.0 FORMATTED\n",
+            "2.0.0\n",
+            "{\n  \"version\": \"2.0.0\"\n}\n",
+        ]
+    );
+
+    let (_, hooks) = engine
+        .lua
+        .load(
+            r#"helpers.chain(
+                function(version) return version, {pre_cmd = {"a"}, post_func = function() n = 1 end} end,
+                function(version) return version, {pre_cmd = {"b"}, post_func = function() n = n + 1 end} end
+            )("2.0.0", "")"#,
+        )
+        .eval::<(String, LuaTable)>()
+        .unwrap();
+    assert_eq!(hooks.get::<_, Vec<String>>("pre_cmd").unwrap(), ["b"]);
+    hooks
+        .get::<_, LuaFunction>("post_func")
+        .unwrap()
+        .call::<_, ()>(())
+        .unwrap();
+    assert_eq!(engine.lua.globals().get::<_, u32>("n").unwrap(), 2);
+}
+
+#[test]
+fn bump_packaging_files() {
+    let lua = Lua::new();
+    load_helpers(&lua).unwrap();
+    let bump = |code: &str, content: &str| {
+        lua.load(code)
+            .eval::<LuaFunction>()
+            .unwrap()
+            .call::<_, String>(("2.0.0", content))
+            .unwrap()
+    };
+
+    let chart = "apiVersion: v2\nversion: \"1.0.0\" # chart\nappVersion: '1.0.0'\ndependencies:\n  - version: 1.0.0\n";
+    assert_eq!(
+        bump("helpers.helm_chart()", chart),
+        "apiVersion: v2\nversion: \"2.0.0\" # chart\nappVersion: '2.0.0'\ndependencies:\n  - version: 1.0.0\n"
+    );
+    assert_eq!(
+        bump("helpers.helm_chart {version = false}", chart),
+        "apiVersion: v2\nversion: \"1.0.0\" # chart\nappVersion: '2.0.0'\ndependencies:\n  - version: 1.0.0\n"
+    );
+
+    let changelog = "x (1.0.0-1) unstable; urgency=medium\n\n  * Initial release.\n";
+    let debian = "helpers.debian_changelog {maintainer = 'A <a@x>', distribution = 'bookworm'}";
+    let bumped = bump(debian, changelog);
+    assert!(bumped.starts_with(
+        "x (2.0.0-1) bookworm; urgency=medium\n\n  * New upstream release 2.0.0.\n\n -- A <a@x>  "
+    ));
+    assert!(bumped.ends_with(&format!("\n\n{}", changelog)));
+    assert_eq!(bump(debian, &bumped), bumped);
+
+    let spec = "Name: x\nVersion: 1.0.0\nRelease: 3%{?dist}\n\n%changelog\n- Initial\n";
+    let rpm = "helpers.rpm_spec {packager = 'A <a@x>'}";
+    let bumped = bump(rpm, spec);
+    assert!(bumped.starts_with("Name: x\nVersion: 2.0.0\nRelease: 1%{?dist}\n\n%changelog\n* "));
+    assert!(bumped.ends_with(" A <a@x> - 2.0.0-1\n- Update to 2.0.0\n\n- Initial\n"));
+    assert_eq!(bump(rpm, &bumped), bumped);
+}
+
+#[test]
+fn validate_returned_values() {
+    let lua = Lua::new();
+    let add_raw = |code: &str, raw: bool| {
+        let values = lua.load(code).eval::<LuaMultiValue>().unwrap();
+        let mut returned = Returned::default();
+        returned
+            .add(&lua, values, raw)
+            .map(|contents| (contents, returned.skip, returned.mode, returned.hooks.len()))
+    };
+    let add = |code: &str| add_raw(code, false);
+
+    assert_eq!(
+        add("'1.0', {skip = true, mode = '644', pre_cmd = {'true'}}").unwrap(),
+        (b"1.0".to_vec(), true, Some(0o644), 1)
+    );
+    assert_eq!(add("42").unwrap(), (b"42".to_vec(), false, None, 0));
+    assert_eq!(add_raw(r"'\0\255'", true).unwrap().0, b"\0\xff");
+    assert!(add(r"'\0\255'").is_err());
+
+    assert_eq!(
+        add("'1.0', {skp = true}").unwrap_err().to_string(),
+        "Bumping function returned unknown option `skp`, expected hooks like `pre_cmd`, or one of \
+         the options `skip`, `create`, `mode`, and `encoding`"
+    );
+    for code in ["nil", "'1.0', 'x'", "'1.0', {mode = 'rwx'}", "'1.0', {}, 3"] {
+        assert!(add(code).is_err(), "{}", code);
+    }
+}
@@ -1,26 +1,220 @@
-pub(crate) type Result<T> = std::result::Result<T, Error>;
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Marker that separates the message of a Lua error from its stack traceback.
+const TRACEBACK: &str = "\nstack traceback:\n";
 
 #[derive(Debug, thiserror::Error)]
 #[non_exhaustive]
-pub(crate) enum Error {
+pub enum Error {
     #[error("Not a Git repository")]
     NotARepository,
     #[error("Not supported on bare repositories")]
     BareRepositoryNotSupported,
-    #[error("Failed to load Lua code: {source}")]
+    #[error("Failed to load Lua code: {}", lua_message(.source))]
     LuaLoadingFailed { source: mlua::Error },
-    #[error("Failed to execute Lua code: {source}")]
+    #[error("Config {config} must return a table, got {type_name}: {hint}")]
+    ConfigNotATable {
+        config: String,
+        type_name: String,
+        hint: String,
+    },
+    #[error("Keys of config {config} must be file names, got {type_name}: map each file to its function, like `[\"src/version.txt\"] = helpers.plain_version_file`")]
+    InvalidConfigKey { config: String, type_name: String },
+    #[error("Invalid mapping for {key}: expected a function, a table with the function in its `func` member and options, or a list of those, got {type_name}")]
+    InvalidMapping { key: String, type_name: String },
+    #[error("Invalid option `{option}` for {key}: expected {expected}, got {type_name}")]
+    InvalidOption {
+        key: String,
+        option: String,
+        expected: String,
+        type_name: String,
+    },
+    #[error("Mapping for {key} from {config} points outside of the worktree, use --allow-outside-worktree to allow it")]
+    OutsideWorktree { key: String, config: String },
+    #[error("Mapping for {file} from {previous} is overridden by {config}")]
+    MappingOverridden {
+        file: String,
+        previous: String,
+        config: String,
+    },
+    #[error("Failed to execute Lua code: {}", lua_message(.source))]
     LuaExecutionFailed { source: mlua::Error },
-    #[error("Failed to execute pre function: {source}")]
+    #[error("Failed to execute enabled function: {}", lua_message(.source))]
+    LuaEnabledFuncFailed { source: mlua::Error },
+    #[error("Failed to execute pre function: {}", lua_message(.source))]
     LuaPreFuncFailed { source: mlua::Error },
-    #[error("Failed to execute post function: {source}")]
+    #[error("Failed to execute post function: {}", lua_message(.source))]
     LuaPostFuncFailed { source: mlua::Error },
+    #[error("Failed to execute {hook} hook: {}", lua_message(.source))]
+    LuaHookFailed {
+        hook: &'static str,
+        source: mlua::Error,
+    },
+    #[error("Failed to bump {key} from {config}: {source}")]
+    MappingFailed {
+        key: String,
+        config: String,
+        source: Box<Error>,
+    },
     #[error(transparent)]
     LuaError(#[from] mlua::Error),
+    #[error("Invalid hook directory {dir:?}, expected \"worktree\", \"file\" for hooks of mappings, or a directory inside of the worktree")]
+    InvalidHookDir { dir: String },
+    #[error("Hook command must not be empty")]
+    EmptyCommand,
+    #[error("Failed to execute command `{command}`: {output}")]
+    CommandFailed { command: String, output: String },
+    #[error("Invalid semantic version: {version}")]
+    InvalidVersion { version: String },
+    #[error("{count} file(s) cannot be written, nothing was changed")]
+    NotWritable { count: usize },
+    #[error("Bumping is only allowed on branch {required}, not on {branch}")]
+    WrongBranch { required: String, branch: String },
+    #[error("Config file {file} from bump.config does not exist")]
+    MissingConfig { file: String },
+    #[error("Bumping function returned {returned}, expected {expected}")]
+    InvalidReturnValue { returned: String, expected: String },
+    #[error("No config file found, create one with `git bump init`")]
+    NoConfig,
+    #[error(
+        "Nothing to do, no mapping of the {count} config file(s) matches an existing file{}",
+        missing_files(.missing)
+    )]
+    NothingToDo { count: usize, missing: Vec<String> },
+    #[error("Tag {tag} already exists, use --force-tag to move it")]
+    TagExists { tag: String },
+    #[error("Tag {tag} already exists on remote {remote}")]
+    RemoteTagExists { tag: String, remote: String },
+    #[error("No section for version {version} found in {changelog}")]
+    NotesNotFound { version: String, changelog: String },
+    #[error("Missing API token, set {variable}")]
+    MissingToken { variable: String },
+    #[error("Remote origin is not hosted on GitHub or GitLab")]
+    UnknownForge,
+    #[error("Failed to create release: {reason}")]
+    ReleaseFailed { reason: String },
+    #[error("No {tool} config found to import")]
+    NothingToImport { tool: String },
+    #[error("Cannot import {file}, only JSON configs are supported")]
+    ImportUnsupported { file: String },
+    #[error("Config file {file} already exists")]
+    ConfigExists { file: String },
+    #[error("{count} file(s) drifted from version {version}")]
+    VersionDrift { count: usize, version: String },
+    #[error("Config files have {errors} error(s) and {warnings} warning(s)")]
+    LintFailed { errors: usize, warnings: usize },
+    #[error("{count} check(s) failed")]
+    DoctorFailed { count: usize },
+    #[error("Failed to run editor `{editor}`: {reason}")]
+    EditorFailed { editor: String, reason: String },
+    #[error("Unknown lockfile updater: {name}")]
+    UnknownLockfile { name: String },
+    #[error("Failed to refresh lockfile with `{command}`: {output}")]
+    LockfileRefreshFailed { command: String, output: String },
+    #[error("Cannot refresh lockfile, {manifest} has no package name")]
+    MissingPackageName { manifest: String },
+    #[error("Failed to format file with `{command}`: {output}")]
+    FormatFailed { command: String, output: String },
+    #[error("Invalid commit type or scope {value:?}, check bump.commitType and bump.commitScope")]
+    InvalidCommitType { value: String },
+    #[error("Invalid identity {value:?}, expected `Name <email>`")]
+    InvalidIdentity { value: String },
+    #[error("Invalid trailer {value:?}, expected `key=value` or `key: value`")]
+    InvalidTrailer { value: String },
+    #[error("Failed to read commit template {file}: {source}")]
+    CommitTemplateFailed {
+        file: String,
+        source: std::io::Error,
+    },
+    #[error("Cannot amend, there is no commit yet")]
+    NothingToAmend,
+    #[error("Failed to sign commit: {reason}")]
+    SigningFailed { reason: String },
+    #[error("Aborted while reviewing changes, nothing was changed")]
+    ReviewAborted,
+    #[error("Cannot ask for confirmation without a terminal, use --yes")]
+    NotConfirmed,
+    #[error("{file} was modified while bumping, not overwriting it")]
+    ModifiedConcurrently { file: String },
     #[error("Failed to read to file: {source}")]
     ReadFailed { source: std::io::Error },
     #[error("Failed to write to file: {source}")]
     WriteFailed { source: std::io::Error },
     #[error(transparent)]
     IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    GitError(#[from] git2::Error),
+}
+
+impl Error {
+    /// Get the Lua stack traceback of the error, if any.
+    ///
+    /// Tracebacks are left out of the error messages to keep them short, but can be shown on
+    /// demand for debugging config files.
+    pub fn traceback(&self) -> Option<String> {
+        match self {
+            Error::LuaLoadingFailed { source }
+            | Error::LuaExecutionFailed { source }
+            | Error::LuaEnabledFuncFailed { source }
+            | Error::LuaPreFuncFailed { source }
+            | Error::LuaPostFuncFailed { source }
+            | Error::LuaHookFailed { source, .. }
+            | Error::LuaError(source) => source
+                .to_string()
+                .split_once(TRACEBACK)
+                .map(|(_, traceback)| traceback.to_string()),
+            Error::MappingFailed { source, .. } => source.traceback(),
+            _ => None,
+        }
+    }
+
+    /// Get the exit code of the command line tool for the error.
+    ///
+    /// Pipelines can tell a bump without any matching files from actual failures by its own code.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::NothingToDo { .. } => 3,
+            _ => 1,
+        }
+    }
+}
+
+/// Render the keys of mappings whose files do not exist, as suffix of an error message.
+fn missing_files(keys: &[String]) -> String {
+    match keys {
+        [] => String::new(),
+        keys => format!(", missing: {}", keys.join(", ")),
+    }
+}
+
+/// Render Lua error without its stack traceback.
+pub(crate) fn lua_message(err: &mlua::Error) -> String {
+    let message = err.to_string();
+    match message.split_once(TRACEBACK) {
+        Some((message, _)) => message.to_string(),
+        None => message,
+    }
+}
+
+#[test]
+fn split_lua_tracebacks() {
+    let err = Error::MappingFailed {
+        key: String::from("VERSION"),
+        config: String::from(".git-bump.lua"),
+        source: Box::new(Error::LuaExecutionFailed {
+            source: mlua::Error::RuntimeError(String::from(
+                ".git-bump.lua:2: boom\nstack traceback:\n\t[C]: in function 'error'",
+            )),
+        }),
+    };
+
+    assert_eq!(
+        err.to_string(),
+        "Failed to bump VERSION from .git-bump.lua: \
+         Failed to execute Lua code: runtime error: .git-bump.lua:2: boom"
+    );
+    assert_eq!(
+        err.traceback().as_deref(),
+        Some("\t[C]: in function 'error'")
+    );
 }
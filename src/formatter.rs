@@ -0,0 +1,117 @@
+use std::path::Path;
+use std::process::Command;
+
+use mlua::prelude::*;
+
+use crate::{command, Error, Result};
+
+/// What happens if a formatter fails, as given by the `format_failure` option of a mapping.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum FormatFailure {
+    /// Abort the bump with an error.
+    Error,
+    /// Keep the file as bumped and show a warning.
+    Warn,
+    /// Keep the file as bumped without further notice.
+    Ignore,
+}
+
+/// External formatter that is run on a file after writing it, as given by the `format` option of
+/// a mapping.
+pub(crate) struct Formatter {
+    /// Program and its arguments, followed by the file when run.
+    pub(crate) argv: Vec<String>,
+    pub(crate) on_failure: FormatFailure,
+}
+
+impl Formatter {
+    /// Get formatter from the options of a mapping.
+    ///
+    /// The command is given as list of program and arguments, or as a single program. Returns
+    /// `None` if the mapping has no formatter.
+    pub(crate) fn from_options(lua: &Lua, key: &str, options: &LuaTable) -> Result<Option<Self>> {
+        let argv = match options.get::<_, LuaValue>("format")? {
+            LuaValue::Nil => return Ok(None),
+            LuaValue::String(program) => vec![program.to_str()?.to_string()],
+            value => lua.unpack::<Vec<String>>(value)?,
+        };
+        if argv.is_empty() {
+            return Err(Error::InvalidOption {
+                key: key.to_string(),
+                option: String::from("format"),
+                expected: String::from("a program, optionally followed by its arguments"),
+                type_name: String::from("an empty list"),
+            });
+        }
+
+        let on_failure = match options
+            .get::<_, Option<String>>("format_failure")?
+            .as_deref()
+        {
+            None | Some("error") => FormatFailure::Error,
+            Some("warn") => FormatFailure::Warn,
+            Some("ignore") => FormatFailure::Ignore,
+            Some(value) => {
+                return Err(Error::InvalidOption {
+                    key: key.to_string(),
+                    option: String::from("format_failure"),
+                    expected: String::from("\"error\", \"warn\", or \"ignore\""),
+                    type_name: format!("{:?}", value),
+                })
+            }
+        };
+
+        Ok(Some(Formatter { argv, on_failure }))
+    }
+
+    /// Run formatter on a file, given by its full path as last argument.
+    ///
+    /// The command is run in the directory of the file, without involving a shell. Its output is
+    /// captured and only shown if the command fails.
+    pub(crate) fn run(&self, file: &Path) -> Result<()> {
+        let (program, args) = self.argv.split_first().ok_or(Error::EmptyCommand)?;
+
+        let mut command = Command::new(program);
+        command.args(args).arg(file);
+        if let Some(dir) = file.parent() {
+            command.current_dir(dir);
+        }
+
+        let display = command::display(&command);
+        command::run(&mut command).map_err(|output| Error::FormatFailed {
+            command: display,
+            output,
+        })?;
+
+        Ok(())
+    }
+}
+
+#[test]
+fn read_formatter_options() {
+    let lua = Lua::new();
+    let formatter = |code: &str| {
+        let options = lua.load(code).eval::<LuaTable>().unwrap();
+        Formatter::from_options(&lua, "x", &options)
+            .map(|formatter| formatter.map(|formatter| (formatter.argv, formatter.on_failure)))
+    };
+
+    assert_eq!(formatter("{}").unwrap(), None);
+    assert_eq!(
+        formatter("{format = 'gofmt'}").unwrap(),
+        Some((vec![String::from("gofmt")], FormatFailure::Error))
+    );
+    assert_eq!(
+        formatter("{format = {'rustfmt', '--edition', '2021'}, format_failure = 'warn'}").unwrap(),
+        Some((
+            vec![
+                String::from("rustfmt"),
+                String::from("--edition"),
+                String::from("2021")
+            ],
+            FormatFailure::Warn
+        ))
+    );
+    assert!(formatter("{format = {}}").is_err());
+    assert!(formatter("{format = 'gofmt', format_failure = 'never'}").is_err());
+}
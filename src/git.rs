@@ -0,0 +1,934 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fmt;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use git2::{
+    AttrCheckFlags, AttrValue, Commit, Config, DescribeFormatOptions, DescribeOptions, DiffOptions,
+    ErrorCode, Index, IndexEntry, IndexEntryExtendedFlag, IndexTime, Oid, Repository, Signature,
+    Tree,
+};
+
+use crate::engine::Workspace;
+use crate::mapping::{is_outside_worktree, key_components, resolve_parents};
+use crate::state::TempDir;
+use crate::{command, Error, Result};
+
+/// Workspace on the index of a repository, leaving the worktree untouched.
+///
+/// Files are read from and written to their staged blobs. Only files that are already in the index
+/// can be bumped this way.
+pub(crate) struct IndexWorkspace<'a> {
+    repository: &'a Repository,
+    workdir: &'a Path,
+    index: RefCell<Index>,
+}
+
+impl<'a> IndexWorkspace<'a> {
+    pub(crate) fn new(repository: &'a Repository, workdir: &'a Path) -> Result<Self> {
+        Ok(IndexWorkspace {
+            repository,
+            workdir,
+            index: RefCell::new(repository.index()?),
+        })
+    }
+
+    /// Get index entry of a file.
+    fn entry(&self, file: &Path) -> io::Result<IndexEntry> {
+        file.strip_prefix(self.workdir)
+            .ok()
+            .and_then(|path| self.index.borrow().get_path(path, 0))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "File is not in the index"))
+    }
+}
+
+impl Workspace for IndexWorkspace<'_> {
+    fn read(&self, file: &Path) -> io::Result<String> {
+        String::from_utf8(self.read_bytes(file)?)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    fn write(&self, file: &Path, contents: &str) -> io::Result<()> {
+        self.write_bytes(file, contents.as_bytes())
+    }
+
+    fn read_bytes(&self, file: &Path) -> io::Result<Vec<u8>> {
+        let entry = self.entry(file)?;
+        let blob = self
+            .repository
+            .find_blob(entry.id)
+            .map_err(io::Error::other)?;
+
+        Ok(blob.content().to_vec())
+    }
+
+    fn write_bytes(&self, file: &Path, contents: &[u8]) -> io::Result<()> {
+        let mut entry = self.entry(file)?;
+        entry.id = self.repository.blob(contents).map_err(io::Error::other)?;
+        entry.file_size = contents.len() as u32;
+
+        // Just like `git update-index --cacheinfo`, forget the stat data of the file in the
+        // worktree, so Git compares its contents to the new blob instead of trusting the index
+        entry.ctime = IndexTime::new(0, 0);
+        entry.mtime = IndexTime::new(0, 0);
+        entry.dev = 0;
+        entry.ino = 0;
+
+        let mut index = self.index.borrow_mut();
+        index.add(&entry).map_err(io::Error::other)?;
+        index.write().map_err(io::Error::other)
+    }
+
+    fn exists(&self, file: &Path) -> bool {
+        self.entry(file).is_ok()
+    }
+}
+
+/// Create a branch pointing to `HEAD`, optionally switching to it.
+///
+/// Since the new branch points to the current commit, switching to it does not touch the worktree
+/// or the index, so any uncommitted changes are kept, just like with `git switch -c`.
+pub(crate) fn create_branch(repository: &Repository, name: &str, switch: bool) -> Result<()> {
+    let head = repository.head()?.peel_to_commit()?;
+    repository.branch(name, &head, false)?;
+
+    if switch {
+        repository.set_head(&format!("refs/heads/{}", name))?;
+    }
+
+    Ok(())
+}
+
+/// Line endings Git checks out a text file with, as configured with `.gitattributes`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum LineEnding {
+    Lf,
+    Crlf,
+}
+
+impl LineEnding {
+    /// Get the line endings of a file in the worktree, or `None` if Git does not convert them.
+    ///
+    /// Just like with Git, the `eol` attribute takes precedence, then the `text` attribute along
+    /// with `core.eol`, and finally `core.autocrlf` for files without attributes. Files that are
+    /// detected as binary by `text=auto` are not converted.
+    pub(crate) fn of(repository: &Repository, workdir: &Path, file: &Path) -> Option<Self> {
+        let path = file.strip_prefix(workdir).unwrap_or(file);
+        let attr = |name| {
+            AttrValue::from_string(
+                repository
+                    .get_attr(path, name, AttrCheckFlags::default())
+                    .ok()
+                    .flatten(),
+            )
+        };
+        let config = repository.config().ok()?;
+        let autocrlf = config.get_string("core.autocrlf").unwrap_or_default();
+
+        let text = attr("text");
+        if matches!(text, AttrValue::False) {
+            return None;
+        }
+        match attr("eol") {
+            AttrValue::String("crlf") => return Some(LineEnding::Crlf),
+            AttrValue::String("lf") => return Some(LineEnding::Lf),
+            _ => {}
+        }
+        match text {
+            AttrValue::True | AttrValue::String("auto") => {}
+            AttrValue::Unspecified if autocrlf == "true" => {}
+            _ => return None,
+        }
+        if matches!(text, AttrValue::String("auto") | AttrValue::Unspecified)
+            && std::fs::read(file).is_ok_and(|contents| contents.contains(&0))
+        {
+            return None;
+        }
+
+        let native = match cfg!(windows) {
+            true => LineEnding::Crlf,
+            false => LineEnding::Lf,
+        };
+        match (autocrlf.as_str(), config.get_string("core.eol").as_deref()) {
+            ("true", _) => Some(LineEnding::Crlf),
+            ("input", _) => Some(LineEnding::Lf),
+            (_, Ok("crlf")) => Some(LineEnding::Crlf),
+            (_, Ok("lf")) => Some(LineEnding::Lf),
+            _ => Some(native),
+        }
+    }
+
+    /// Convert all line endings of the contents, including mixed ones.
+    pub(crate) fn apply(self, contents: &[u8]) -> Vec<u8> {
+        let mut converted = Vec::with_capacity(contents.len());
+        for (index, byte) in contents.iter().enumerate() {
+            match byte {
+                b'\r' if contents.get(index + 1) == Some(&b'\n') => {}
+                b'\n' if self == LineEnding::Crlf => converted.extend(b"\r\n"),
+                _ => converted.push(*byte),
+            }
+        }
+        converted
+    }
+}
+
+/// Get the line endings of the given files, skipping those whose line endings are not converted.
+pub(crate) fn line_endings<'a>(
+    repository: &Repository,
+    workdir: &Path,
+    files: impl IntoIterator<Item = &'a PathBuf>,
+) -> HashMap<PathBuf, LineEnding> {
+    files
+        .into_iter()
+        .filter_map(|file| Some((file.clone(), LineEnding::of(repository, workdir, file)?)))
+        .collect()
+}
+
+/// Get the mapped files that are excluded from the worktree by sparse-checkout.
+///
+/// Such files are in the index with the skip-worktree bit set, but missing on disk, so the keys
+/// of missing files are checked, relative to the worktree.
+pub(crate) fn sparse_excluded(
+    repository: &Repository,
+    workdir: &Path,
+    keys: &[String],
+) -> Result<Vec<PathBuf>> {
+    let index = repository.index()?;
+
+    let mut excluded = Vec::new();
+    for key in keys.iter().filter(|key| !is_outside_worktree(key)) {
+        let components = resolve_parents(&key_components(key));
+        let skipped = index
+            .get_path(Path::new(&components.join("/")), 0)
+            .is_some_and(|entry| {
+                entry.flags_extended & IndexEntryExtendedFlag::SKIP_WORKTREE.bits() != 0
+            });
+        if skipped {
+            excluded.push(workdir.join(components.iter().collect::<PathBuf>()));
+        }
+    }
+    Ok(excluded)
+}
+
+/// Check out files that are excluded by sparse-checkout, by adding them to its patterns.
+///
+/// In cone mode, the patterns are directories, so the whole parent directories of the files are
+/// checked out.
+pub(crate) fn materialize(workdir: &Path, files: &[PathBuf]) -> Result<()> {
+    // Git keeps the settings of sparse-checkout in the worktree config, which libgit2 ignores
+    let cone = Command::new("git")
+        .args(["config", "--type=bool", "core.sparseCheckoutCone"])
+        .current_dir(workdir)
+        .output()
+        .is_ok_and(|output| output.stdout.starts_with(b"true"));
+
+    let mut patterns = Vec::new();
+    for file in files {
+        let path = file.strip_prefix(workdir).unwrap_or(file);
+        let path = path
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>();
+        let pattern = match cone {
+            // Files at the top level are always checked out in cone mode
+            true if path.len() < 2 => continue,
+            true => path[..path.len() - 1].join("/"),
+            // Escape wildcards, which file names may contain as well
+            false => format!("/{}", path.join("/"))
+                .chars()
+                .flat_map(|c| match c {
+                    '*' | '?' | '[' | '\\' => vec!['\\', c],
+                    c => vec![c],
+                })
+                .collect(),
+        };
+        if !patterns.contains(&pattern) {
+            patterns.push(pattern);
+        }
+    }
+
+    if patterns.is_empty() {
+        return Ok(());
+    }
+
+    let mut command = Command::new("git");
+    command
+        .args(["sparse-checkout", "add"])
+        .args(&patterns)
+        .current_dir(workdir);
+
+    let display = command::display(&command);
+    command::run(&mut command).map_err(|output| Error::CommandFailed {
+        command: display,
+        output,
+    })?;
+
+    Ok(())
+}
+
+/// Check if a file is tracked, that is, if it is in the index.
+pub(crate) fn is_tracked(index: &Index, workdir: &Path, file: &Path) -> bool {
+    match file.strip_prefix(workdir) {
+        Ok(path) => index.get_path(path, 0).is_some(),
+        Err(_) => false,
+    }
+}
+
+/// Get the contents of a file as committed in `HEAD`, given relative to the worktree.
+///
+/// Returns `None` if there is no such file in `HEAD`, or no commit at all.
+pub(crate) fn committed_contents(repository: &Repository, path: &Path) -> Option<Vec<u8>> {
+    let tree = repository.head().ok()?.peel_to_tree().ok()?;
+    let object = tree.get_path(path).ok()?.to_object(repository).ok()?;
+    Some(object.peel_to_blob().ok()?.content().to_vec())
+}
+
+/// Get files that changed since a revision, relative to the worktree.
+///
+/// Staged, unstaged, and untracked changes are included. An empty revision stands for the nearest
+/// tag, as given by `git describe --tags`.
+pub(crate) fn changed_since(repository: &Repository, revision: &str) -> Result<HashSet<PathBuf>> {
+    let revision = match revision {
+        "" => repository
+            .describe(DescribeOptions::new().describe_tags())?
+            .format(Some(DescribeFormatOptions::new().abbreviated_size(0)))?,
+        revision => revision.to_string(),
+    };
+    let tree = repository.revparse_single(&revision)?.peel_to_tree()?;
+
+    let diff = repository.diff_tree_to_workdir_with_index(
+        Some(&tree),
+        Some(
+            DiffOptions::new()
+                .include_untracked(true)
+                .recurse_untracked_dirs(true),
+        ),
+    )?;
+
+    Ok(diff
+        .deltas()
+        .flat_map(|delta| [delta.old_file().path(), delta.new_file().path()])
+        .flatten()
+        .map(Path::to_path_buf)
+        .collect())
+}
+
+/// Check if a tag exists locally.
+pub(crate) fn tag_exists(repository: &Repository, name: &str) -> bool {
+    repository
+        .find_reference(&format!("refs/tags/{}", name))
+        .is_ok()
+}
+
+/// Check if a tag exists on a remote.
+///
+/// This runs `git ls-remote`, so the remote is reached with the transports and credentials that are
+/// configured for Git itself. Nothing is fetched into the local repository.
+pub(crate) fn remote_tag_exists(workdir: &Path, remote: &str, name: &str) -> Result<bool> {
+    let mut command = Command::new("git");
+    command
+        .args(["ls-remote", "--tags", remote])
+        .arg(format!("refs/tags/{}", name))
+        .current_dir(workdir);
+
+    let display = command::display(&command);
+    let output = command::run(&mut command).map_err(|output| Error::CommandFailed {
+        command: display,
+        output,
+    })?;
+
+    Ok(!output.stdout.is_empty())
+}
+
+/// Push refs to a remote.
+///
+/// Just like checking remote tags, this runs `git push` to use the configured transports and
+/// credentials.
+pub(crate) fn push(workdir: &Path, remote: &str, refs: &[String]) -> Result<()> {
+    let mut command = Command::new("git");
+    command
+        .args(["push", remote])
+        .args(refs)
+        .current_dir(workdir);
+
+    let display = command::display(&command);
+    command::run(&mut command).map_err(|output| Error::CommandFailed {
+        command: display,
+        output,
+    })?;
+
+    Ok(())
+}
+
+/// Author and committer of bump commits, where the committer also tags them.
+pub(crate) struct Identity {
+    author: Signature<'static>,
+    committer: Signature<'static>,
+}
+
+impl Identity {
+    /// Resolve the identity, either the given one or else from the environment and the Git config.
+    ///
+    /// Just like with Git, `GIT_AUTHOR_NAME`, `GIT_AUTHOR_EMAIL`, `GIT_COMMITTER_NAME`, and
+    /// `GIT_COMMITTER_EMAIL` take precedence over the Git config. In there, `bump.identity`
+    /// overrides `user.name` and `user.email`, for example to commit as a bot in CI.
+    pub(crate) fn resolve(repository: &Repository, identity: Option<&str>) -> Result<Self> {
+        if let Some(identity) = identity {
+            let signature = parse_identity(identity)?;
+            return Ok(Identity {
+                author: signature.clone(),
+                committer: signature,
+            });
+        }
+
+        let configured = match repository.config()?.get_string("bump.identity") {
+            Ok(identity) => Some(parse_identity(&identity)?),
+            Err(_) => repository
+                .signature()
+                .ok()
+                .map(|signature| signature.to_owned()),
+        };
+        let person = |role: &str| -> Result<Signature<'static>> {
+            let var = |field: &str| env::var(format!("GIT_{}_{}", role, field)).ok();
+            let name = var("NAME").or_else(|| configured.as_ref()?.name().map(String::from));
+            let email = var("EMAIL").or_else(|| configured.as_ref()?.email().map(String::from));
+
+            match (name, email) {
+                (Some(name), Some(email)) => Ok(Signature::now(&name, &email)?),
+                // Fail just like Git does without any identity
+                _ => Ok(repository.signature()?.to_owned()),
+            }
+        };
+
+        Ok(Identity {
+            author: person("AUTHOR")?,
+            committer: person("COMMITTER")?,
+        })
+    }
+}
+
+impl fmt::Display for Identity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let show = |signature: &Signature| {
+            format!(
+                "{} <{}>",
+                signature.name().unwrap_or_default(),
+                signature.email().unwrap_or_default()
+            )
+        };
+
+        let (author, committer) = (show(&self.author), show(&self.committer));
+        match author == committer {
+            true => write!(f, "{}", committer),
+            false => write!(f, "{}, committed by {}", author, committer),
+        }
+    }
+}
+
+/// Parse identity in the format `Name <email>`.
+fn parse_identity(identity: &str) -> Result<Signature<'static>> {
+    let invalid = || Error::InvalidIdentity {
+        value: identity.to_string(),
+    };
+
+    let (name, email) = identity.trim().split_once('<').ok_or_else(invalid)?;
+    let email = email.strip_suffix('>').ok_or_else(invalid)?;
+    if name.trim().is_empty() || email.is_empty() || email.contains(['<', '>']) {
+        return Err(invalid());
+    }
+
+    Ok(Signature::now(name.trim(), email)?)
+}
+
+/// Create an annotated tag pointing to `HEAD`, moving an existing one if forced.
+pub(crate) fn create_tag(
+    repository: &Repository,
+    identity: &Identity,
+    name: &str,
+    message: &str,
+    force: bool,
+) -> Result<Oid> {
+    let head = repository.head()?.peel_to_commit()?;
+
+    let oid = repository.tag(name, head.as_object(), &identity.committer, message, force)?;
+
+    Ok(oid)
+}
+
+/// Get message of the bump commit.
+///
+/// If `bump.commitType` and optionally `bump.commitScope` are set in the Git config, the message
+/// follows the Conventional Commits format, like `chore(release): bump version to 1.2.3`.
+pub(crate) fn commit_message(config: &Config, version: &str) -> Result<String> {
+    let kind = config.get_string("bump.commitType").ok();
+    let scope = config.get_string("bump.commitScope").ok();
+
+    conventional_message(kind.as_deref(), scope.as_deref(), version)
+}
+
+/// Read a commit message template, to be rendered with [`render_commit_template`].
+pub(crate) fn read_commit_template(file: &Path) -> Result<String> {
+    std::fs::read_to_string(file).map_err(|source| Error::CommitTemplateFailed {
+        file: file.to_string_lossy().into_owned(),
+        source,
+    })
+}
+
+/// Render a commit message template, replacing `{version}`, `{notes}`, and `{files}`.
+///
+/// Files are listed one per line. Just like Git cleans up commit messages, consecutive empty
+/// lines, which are left by empty notes for example, are collapsed into one.
+pub(crate) fn render_commit_template(
+    template: &str,
+    version: &str,
+    notes: Option<&str>,
+    files: &[String],
+) -> String {
+    let files = files.join("\n");
+    let placeholders = [
+        ("{version}", version),
+        ("{notes}", notes.unwrap_or_default().trim()),
+        ("{files}", files.as_str()),
+    ];
+
+    // Replace in a single pass, so placeholders in the replacements are kept as they are
+    let mut rendered = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        rest = &rest[start..];
+        match placeholders
+            .iter()
+            .find(|(placeholder, _)| rest.starts_with(placeholder))
+        {
+            Some((placeholder, value)) => {
+                rendered.push_str(value);
+                rest = &rest[placeholder.len()..];
+            }
+            None => {
+                rendered.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    rendered.push_str(rest);
+
+    let mut message = String::new();
+    for line in rendered.trim().lines().map(str::trim_end) {
+        if !(line.is_empty() && message.ends_with("\n\n")) {
+            message.push_str(line);
+            message.push('\n');
+        }
+    }
+    message
+}
+
+/// Build commit message of the given type and scope, validating both.
+fn conventional_message(kind: Option<&str>, scope: Option<&str>, version: &str) -> Result<String> {
+    let invalid = |value: &str| Error::InvalidCommitType {
+        value: value.to_string(),
+    };
+
+    let kind = match kind {
+        Some(kind) => kind,
+        None if scope.is_some() => return Err(invalid("")),
+        None => return Ok(format!("Bump version to {}", version)),
+    };
+    if kind.is_empty() || !kind.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(invalid(kind));
+    }
+
+    match scope {
+        Some(scope) => {
+            if scope.is_empty() || scope.contains(|c: char| "():".contains(c) || c.is_whitespace())
+            {
+                return Err(invalid(scope));
+            }
+            Ok(format!("{}({}): bump version to {}", kind, scope, version))
+        }
+        None => Ok(format!("{}: bump version to {}", kind, version)),
+    }
+}
+
+/// Get the message of `HEAD`, to be kept when amending it.
+pub(crate) fn head_message(repository: &Repository) -> Result<String> {
+    match repository.head() {
+        Ok(head) => Ok(head
+            .peel_to_commit()?
+            .message()
+            .unwrap_or_default()
+            .to_string()),
+        Err(err) if err.code() == ErrorCode::UnbornBranch => Err(Error::NothingToAmend),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Parse trailers given as `key=value` or `key: value`, followed by a sign-off of the committer.
+pub(crate) fn trailers(
+    trailers: &[String],
+    signoff: Option<&Identity>,
+) -> Result<Vec<(String, String)>> {
+    let mut parsed = trailers
+        .iter()
+        .map(|trailer| parse_trailer(trailer))
+        .collect::<Result<Vec<_>>>()?;
+
+    if let Some(identity) = signoff {
+        let committer = &identity.committer;
+        parsed.push((
+            String::from("Signed-off-by"),
+            format!(
+                "{} <{}>",
+                committer.name().unwrap_or_default(),
+                committer.email().unwrap_or_default()
+            ),
+        ));
+    }
+
+    Ok(parsed)
+}
+
+/// Parse a single trailer, with a key of letters, digits, and dashes.
+fn parse_trailer(trailer: &str) -> Result<(String, String)> {
+    let invalid = || Error::InvalidTrailer {
+        value: trailer.to_string(),
+    };
+
+    let (key, value) = trailer.split_once(['=', ':']).ok_or_else(invalid)?;
+    let key = key.trim();
+    if key.is_empty()
+        || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        || value.contains('\n')
+    {
+        return Err(invalid());
+    }
+
+    Ok((key.to_string(), value.trim().to_string()))
+}
+
+/// Append trailers to a commit message.
+///
+/// Just like with `git interpret-trailers`, trailers are added to an existing trailer block in the
+/// last paragraph, but never to the subject, and trailers that are already there are not repeated.
+pub(crate) fn add_trailers(message: &str, trailers: &[(String, String)]) -> String {
+    let message = message.trim_end();
+    let (body, last) = match message.rsplit_once("\n\n") {
+        Some((body, last)) => (Some(body), last),
+        None => (None, message),
+    };
+    let has_block = body.is_some()
+        && last
+            .lines()
+            .all(|line| matches!(line.split_once(": "), Some((key, _)) if !key.contains(' ')));
+
+    let mut message = message.to_string();
+    let mut separator = if has_block { "\n" } else { "\n\n" };
+    for (key, value) in trailers {
+        let line = format!("{}: {}", key, value);
+        if has_block && last.lines().any(|existing| existing == line) {
+            continue;
+        }
+        message.push_str(separator);
+        message.push_str(&line);
+        separator = "\n";
+    }
+    message.push('\n');
+
+    message
+}
+
+/// Commit the given files on top of `HEAD`, or amend `HEAD` with them.
+///
+/// The files are staged from the worktree first, unless they are already staged. Just like with
+/// `git commit --only`, the commit is built from a temporary index with only these files on top of
+/// `HEAD`, so any other staged changes stay staged, but are not committed. When amending, the
+/// author and parents of `HEAD` are kept, while its message is replaced with the given one.
+pub(crate) fn commit(
+    repository: &Repository,
+    identity: &Identity,
+    workdir: &Path,
+    files: &[PathBuf],
+    message: &str,
+    stage: bool,
+    amend: bool,
+) -> Result<Oid> {
+    let paths = files
+        .iter()
+        .map(|file| file.strip_prefix(workdir).unwrap_or(file))
+        .collect::<Vec<_>>();
+
+    let mut index = repository.index()?;
+    if stage {
+        for path in &paths {
+            index.add_path(path)?;
+        }
+        index.write()?;
+    }
+
+    let head = match repository.head() {
+        Ok(head) => Some(head.peel_to_commit()?),
+        Err(err) if err.code() == ErrorCode::UnbornBranch => None,
+        Err(err) => return Err(err.into()),
+    };
+
+    let mut commit_index = Index::new()?;
+    if let Some(head) = &head {
+        commit_index.read_tree(&head.tree()?)?;
+    }
+    for path in &paths {
+        if let Some(entry) = index.get_path(path, 0) {
+            commit_index.add(&entry)?;
+        }
+    }
+
+    let tree = repository.find_tree(commit_index.write_tree_to(repository)?)?;
+
+    match (head, amend) {
+        (Some(head), true) => create_commit(
+            repository,
+            &head.author().to_owned(),
+            &identity.committer,
+            message,
+            &tree,
+            &head.parents().collect::<Vec<_>>(),
+            "commit (amend)",
+        ),
+        (None, true) => Err(Error::NothingToAmend),
+        (head, false) => create_commit(
+            repository,
+            &identity.author,
+            &identity.committer,
+            message,
+            &tree,
+            &head.into_iter().collect::<Vec<_>>(),
+            "commit",
+        ),
+    }
+}
+
+/// Create a commit and move `HEAD`, or the branch it points to, to it.
+///
+/// If `commit.gpgSign` is set in the Git config, the commit is signed just like Git does it.
+fn create_commit(
+    repository: &Repository,
+    author: &Signature,
+    committer: &Signature,
+    message: &str,
+    tree: &Tree,
+    parents: &[Commit],
+    reflog: &str,
+) -> Result<Oid> {
+    let parents = parents.iter().collect::<Vec<_>>();
+    let buffer = repository.commit_create_buffer(author, committer, message, tree, &parents)?;
+    let buffer = String::from_utf8_lossy(&buffer);
+
+    let oid = match sign(repository, &buffer, committer)? {
+        Some(signature) => repository.commit_signed(&buffer, &signature, None)?,
+        None => repository.commit(None, author, committer, message, tree, &parents)?,
+    };
+
+    let reflog = format!("{}: {}", reflog, message.lines().next().unwrap_or_default());
+    match repository.find_reference("HEAD")?.symbolic_target() {
+        Some(branch) => {
+            repository.reference(branch, oid, true, &reflog)?;
+        }
+        None => repository.set_head_detached(oid)?,
+    }
+
+    Ok(oid)
+}
+
+/// Get the program bump commits are signed with, as configured with `gpg.format`.
+///
+/// Returns `None` if commits are not to be signed, as configured with `bump.sign`, or else with
+/// `commit.gpgSign`. Fails if the format is unknown, or if SSH signatures are requested without
+/// `user.signingKey`, so this can be checked before any file is written.
+pub(crate) fn signing_program(config: &Config) -> Result<Option<String>> {
+    let enabled = config
+        .get_bool("bump.sign")
+        .or_else(|_| config.get_bool("commit.gpgSign"))
+        .unwrap_or(false);
+    if !enabled {
+        return Ok(None);
+    }
+
+    let format = config
+        .get_string("gpg.format")
+        .unwrap_or_else(|_| String::from("openpgp"));
+    let default_program = match format.as_str() {
+        "openpgp" => "gpg",
+        "x509" => "gpgsm",
+        "ssh" => "ssh-keygen",
+        format => {
+            return Err(Error::SigningFailed {
+                reason: format!("Format {} is not supported", format),
+            })
+        }
+    };
+    let program = config
+        .get_string(&format!("gpg.{}.program", format))
+        .or_else(|err| match format.as_str() {
+            "openpgp" => config.get_string("gpg.program"),
+            _ => Err(err),
+        })
+        .unwrap_or_else(|_| default_program.to_string());
+
+    if format == "ssh" && config.get_string("user.signingKey").is_err() {
+        return Err(Error::SigningFailed {
+            reason: String::from("SSH signatures need user.signingKey to be set"),
+        });
+    }
+
+    Ok(Some(program))
+}
+
+/// Sign a commit as configured with `gpg.format` and `user.signingKey`.
+///
+/// Returns `None` if commits are not to be signed. Just like with Git, the signing program gets
+/// the commit on standard input and prints the detached, armored signature. For SSH signatures,
+/// `user.signingKey` is either the path of a key, or a public key prefixed with `key::`, whose
+/// private key is then taken from the SSH agent.
+fn sign(repository: &Repository, buffer: &str, committer: &Signature) -> Result<Option<String>> {
+    let config = repository.config()?;
+    let program = match signing_program(&config)? {
+        Some(program) => program,
+        None => return Ok(None),
+    };
+
+    let failed = |reason: String| Error::SigningFailed { reason };
+
+    let key = config.get_string("user.signingKey").unwrap_or_else(|_| {
+        format!(
+            "{} <{}>",
+            committer.name().unwrap_or_default(),
+            committer.email().unwrap_or_default()
+        )
+    });
+
+    let mut command = Command::new(&program);
+    // Literal public keys are handed to ssh-keygen in a file that lives as long as the command
+    let mut _key_dir = None;
+    match config.get_string("gpg.format").as_deref() {
+        Ok("ssh") => {
+            let literal = key
+                .strip_prefix("key::")
+                .or_else(|| key.starts_with("ssh-").then_some(key.as_str()));
+            let key_file = match literal {
+                Some(literal) => {
+                    let dir = TempDir::create()?;
+                    let file = dir.path().join("key.pub");
+                    std::fs::write(&file, format!("{}\n", literal.trim()))?;
+                    command.arg("-U");
+                    _key_dir = Some(dir);
+                    file
+                }
+                None => match key.strip_prefix("~/") {
+                    Some(path) => home::home_dir().unwrap_or_default().join(path),
+                    None => PathBuf::from(&key),
+                },
+            };
+            command
+                .args(["-Y", "sign", "-n", "git", "-f"])
+                .arg(key_file);
+        }
+        _ => {
+            command.args(["--status-fd=2", "-bsau", &key]);
+        }
+    }
+
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| failed(format!("{}: {}", program, err)))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(buffer.as_bytes())?;
+    }
+
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(failed(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+
+    Ok(Some(String::from_utf8_lossy(&output.stdout).into_owned()))
+}
+
+#[test]
+fn build_conventional_messages() {
+    assert_eq!(
+        conventional_message(None, None, "1.2.3").unwrap(),
+        "Bump version to 1.2.3"
+    );
+    assert_eq!(
+        conventional_message(Some("release"), None, "1.2.3").unwrap(),
+        "release: bump version to 1.2.3"
+    );
+    assert_eq!(
+        conventional_message(Some("chore"), Some("release"), "1.2.3").unwrap(),
+        "chore(release): bump version to 1.2.3"
+    );
+
+    assert!(conventional_message(Some("chore:"), None, "1.2.3").is_err());
+    assert!(conventional_message(Some("chore"), Some("a b"), "1.2.3").is_err());
+    assert!(conventional_message(None, Some("release"), "1.2.3").is_err());
+}
+
+#[test]
+fn parse_identities() {
+    let signature = parse_identity(" Release Bot <bot@example.com>").unwrap();
+    assert_eq!(signature.name(), Some("Release Bot"));
+    assert_eq!(signature.email(), Some("bot@example.com"));
+
+    for identity in ["Release Bot", "<bot@example.com>", "Bot <>", "Bot <a> <b>"] {
+        assert!(parse_identity(identity).is_err(), "{}", identity);
+    }
+}
+
+#[test]
+fn append_trailers() {
+    let given = [
+        String::from("Ticket=REL-1"),
+        String::from("Reviewed-by: Jane"),
+    ];
+    let trailers = trailers(&given, None).unwrap();
+    assert_eq!(
+        add_trailers("Bump version to 1.2.3", &trailers),
+        "Bump version to 1.2.3\n\nTicket: REL-1\nReviewed-by: Jane\n"
+    );
+    assert_eq!(
+        add_trailers("Release 1.2.3\n\nDetails.\n\nTicket: REL-1\n", &trailers),
+        "Release 1.2.3\n\nDetails.\n\nTicket: REL-1\nReviewed-by: Jane\n"
+    );
+    assert!(parse_trailer("Reviewed by=Jane").is_err());
+    assert!(parse_trailer("Ticket").is_err());
+}
+
+#[test]
+fn render_commit_templates() {
+    let template = "Release {version}\n\n{notes}\n\nFiles:\n{files}\n";
+    assert_eq!(
+        render_commit_template(
+            template,
+            "1.2.3",
+            Some("Fixed {version}.\n"),
+            &[String::from("Cargo.toml"), String::from("Cargo.lock")]
+        ),
+        "Release 1.2.3\n\nFixed {version}.\n\nFiles:\nCargo.toml\nCargo.lock\n"
+    );
+    assert_eq!(
+        render_commit_template(template, "1.2.3", None, &[String::from("VERSION")]),
+        "Release 1.2.3\n\nFiles:\nVERSION\n"
+    );
+}
+
+#[test]
+fn convert_line_endings() {
+    assert_eq!(LineEnding::Crlf.apply(b"a\r\nb\nc"), b"a\r\nb\r\nc");
+    assert_eq!(LineEnding::Lf.apply(b"a\r\nb\n"), b"a\nb\n");
+}
@@ -0,0 +1,612 @@
+-- Bundled helpers of git-bump, available as the global `helpers` table and
+-- via `require("git-bump.helpers")`. All helpers are bumping functions, so
+-- they can be used as mappings directly.
+local helpers = {}
+
+helpers.plain_version_file = function(version)
+    -- the whole file is the version, like a conventional VERSION file
+
+    return version
+end
+
+local toml_version = '(\nversion[ \t]*=[ \t]*)%b""'
+
+local replace_in_section = function(name, content, pattern, value)
+    -- replace the first match of the pattern in the given section of a TOML
+    -- or INI file, where the pattern captures everything in front of the
+    -- value
+
+    local padded = "\n" .. content
+
+    local header = padded:find("\n%[" .. name:gsub("%p", "%%%0") .. "%]")
+    if header == nil then
+        return content
+    end
+    local next_section = padded:find("\n%[", header + 1) or #padded + 1
+
+    local section = padded:sub(header, next_section - 1):gsub(
+                        pattern, function(prefix)
+            return prefix .. value
+        end, 1
+                    )
+
+    return (padded:sub(1, header - 1) .. section .. padded:sub(next_section))
+               :sub(2)
+end
+
+helpers.cargo_toml = function(version, content)
+    -- replace the version in the [package] section, leaving the versions of
+    -- dependencies and other sections untouched
+
+    return replace_in_section(
+               "package", content, toml_version, '"' .. version .. '"'
+           )
+end
+
+helpers.package_json = function(version, content)
+    -- replace the first "version" member, which is the top-level one in any
+    -- conventional package.json, keeping the formatting of the file
+
+    return (content:gsub(
+               '("version"%s*:%s*)%b""', function(prefix)
+            return prefix .. '"' .. version .. '"'
+        end, 1
+           ))
+end
+
+helpers.pyproject_toml = function(version, content)
+    -- replace the version in the [project] section, or in the [tool.poetry]
+    -- section of Poetry projects
+
+    local value = '"' .. version .. '"'
+    content = replace_in_section("project", content, toml_version, value)
+    return replace_in_section("tool.poetry", content, toml_version, value)
+end
+
+helpers.setup_cfg = function(version, content)
+    -- replace the version in the [metadata] section of a setup.cfg
+
+    return replace_in_section(
+               "metadata", content, "(\nversion[ \t]*=[ \t]*)[^\n]*", version
+           )
+end
+
+helpers.go_version = function(version, content)
+    -- replace the first string constant or variable whose name ends with
+    -- "version", keeping a "v" prefix of the current value
+
+    return (content:gsub(
+               '([%w_]*[Vv]ersion[ \t]*=[ \t]*")(v?)[^"\n]*"',
+               function(prefix, v)
+            return prefix .. v .. version .. '"'
+        end, 1
+           ))
+end
+
+helpers.keep_a_changelog = function(version, content)
+    -- add a heading for the version below the "Unreleased" heading of a
+    -- changelog in the Keep a Changelog format, so all unreleased changes
+    -- belong to the new version; without that heading, the new one is added
+    -- above the latest version, and snapshots leave the changelog untouched
+
+    local padded = "\n" .. content
+    local escaped = version:gsub("%p", "%%%0")
+    if version:find("%-SNAPSHOT$") or
+        padded:find("\n## %[" .. escaped .. "%]") then
+        return content
+    end
+
+    local heading = ("## [%s] - %s"):format(version, os.date("%Y-%m-%d"))
+
+    local _, unreleased = padded:find("\n## %[[Uu]nreleased%][^\n]*\n")
+    if unreleased ~= nil then
+        return (padded:sub(1, unreleased) .. "\n" .. heading .. "\n" ..
+                   padded:sub(unreleased + 1)):sub(2)
+    end
+
+    local latest = padded:find("\n## ")
+    if latest ~= nil then
+        return (padded:sub(1, latest) .. heading .. "\n\n" ..
+                   padded:sub(latest + 1)):sub(2)
+    end
+
+    return content .. "\n" .. heading .. "\n"
+end
+
+helpers.replace = function(current_version, rules)
+    -- build a bumping function that replaces all occurrences of the search
+    -- template of each rule with its replace template, where
+    -- "{current_version}" and "{new_version}" stand for the versions, and
+    -- "{now:FORMAT}" and "{utcnow:FORMAT}" for the local and UTC date in the
+    -- given os.date format; a search template that is not found is an error
+    --
+    -- with `pattern = true`, the search template is a Lua pattern, and the
+    -- replace template can refer to its captures like in string.gsub; the
+    -- number of occurrences can be restricted with `min`, `max`, and
+    -- `exactly`, and `prerelease = false` skips the rule for pre-releases;
+    -- with `lines = true`, the pattern is matched against each line, so
+    -- anchors match at the start and end of lines, like in sed
+
+    local expand = function(template, version, escape)
+        return (template:gsub(
+                   "{([%w_]+):?([^}]*)}", function(name, format)
+                local value
+                if name == "current_version" then
+                    value = current_version
+                elseif name == "new_version" then
+                    value = version
+                elseif name == "now" then
+                    value = os.date(format)
+                elseif name == "utcnow" then
+                    value = os.date("!" .. format)
+                end
+                return value and escape(value)
+            end
+               ))
+    end
+
+    local verbatim = function(value)
+        return value
+    end
+
+    local replace_plain = function(content, search, replace)
+        local parts = {}
+        local position = 1
+        while search ~= "" do
+            local start, stop = content:find(search, position, true)
+            if start == nil then
+                break
+            end
+            parts[#parts + 1] = content:sub(position, start - 1) .. replace
+            position = stop + 1
+        end
+
+        return table.concat(parts) .. content:sub(position), #parts
+    end
+
+    local apply = function(rule, version, content)
+        local search = rule.search or "{current_version}"
+        local replace = rule.replace or "{new_version}"
+        local count
+
+        if rule.pattern then
+            search = expand(
+                         search, version, function(value)
+                    return (value:gsub("%p", "%%%0"))
+                end
+                     )
+            replace = expand(
+                          replace, version, function(value)
+                    return (value:gsub("%%", "%%%%"))
+                end
+                      )
+            if rule.lines then
+                count = 0
+                content = content:gsub(
+                              "[^\n]+", function(line)
+                        local replaced, n = line:gsub(search, replace)
+                        count = count + n
+                        return replaced
+                    end
+                          )
+            else
+                content, count = content:gsub(search, replace)
+            end
+        else
+            search = expand(search, version, verbatim)
+            replace = expand(replace, version, verbatim)
+            content, count = replace_plain(content, search, replace)
+        end
+
+        local min = rule.exactly or rule.min or 1
+        local max = rule.exactly or rule.max
+        if count == 0 and min > 0 then
+            error(("%q not found"):format(search), 0)
+        elseif count < min or (max ~= nil and count > max) then
+            error(("%q found %d times"):format(search, count), 0)
+        end
+
+        return content
+    end
+
+    return function(version, content)
+        for _, rule in ipairs(rules) do
+            if rule.prerelease ~= false or not version:find("-", 1, true) then
+                content = apply(rule, version, content)
+            end
+        end
+
+        return content
+    end
+end
+
+local replace_dependencies = function(names, version, content)
+    -- replace the versions of the named dependencies in all dependency
+    -- sections, keeping any version operator in front of them
+
+    local replace = function(text)
+        return (text:gsub(
+                   '(version[ \t]*=[ \t]*"[=^~<>%s]*)[^"]*"', function(prefix)
+                return prefix .. version .. '"'
+            end, 1
+               ))
+    end
+
+    local lines = {}
+    local section
+    local position = 1
+    while position <= #content do
+        local stop = content:find("\n", position, true) or #content
+        local line = content:sub(position, stop)
+        position = stop + 1
+
+        if line:match("^%s*%[") then
+            -- arrays of tables like [[bin]] end the current section as well
+            local header = line:match("^%s*%[([^%[%]]+)%]")
+            section = header and header:gsub("%s", "")
+        elseif section ~= nil then
+            local key, value = line:match('^%s*([%w_-]+)%s*=%s*(.*)$')
+            if section:match("dependencies$") and key and names[key] then
+                if value:sub(1, 1) == '"' then
+                    line = line:gsub(
+                               '=(%s*"[=^~<>%s]*)[^"]*"', function(prefix)
+                            return "=" .. prefix .. version .. '"'
+                        end, 1
+                           )
+                else
+                    line = replace(line)
+                end
+            elseif key == "version" then
+                local name = section:match("dependencies%.([%w_-]+)$")
+                if name and names[name] then
+                    line = replace(line)
+                end
+            end
+        end
+
+        lines[#lines + 1] = line
+    end
+
+    return table.concat(lines)
+end
+
+local workspace = function(options, find_members, manifest, mapping)
+    -- map the manifest in the root of a workspace and those of all members,
+    -- where the mapping is built from the member and the names of all members
+
+    local dir = options.dir
+    local root = ctx and ctx.worktree or "."
+    if dir ~= nil then
+        root = root .. "/" .. dir
+    end
+
+    local members = find_members(root)
+    local names = {}
+    for _, member in ipairs(members) do
+        names[member.name] = true
+    end
+
+    local prefix = dir and dir .. "/" or ""
+    local mappings = {[prefix .. manifest] = mapping({path = "."}, names)}
+    for _, member in ipairs(members) do
+        if member.path == "." then
+            mappings[prefix .. manifest] = mapping(member, names)
+        else
+            mappings[prefix .. member.path .. "/" .. manifest] =
+                mapping(member, names)
+        end
+    end
+
+    return mappings
+end
+
+helpers.cargo_workspace = function(options)
+    -- map the manifests of the Cargo workspace and all of its members,
+    -- optionally updating the dependencies between them as well
+
+    options = options or {}
+
+    return workspace(
+               options, helpers.cargo_workspace_members, "Cargo.toml",
+               function(member, names)
+            return function(version, content)
+                content = helpers.cargo_toml(version, content)
+                content = replace_in_section(
+                              "workspace.package", content, toml_version,
+                              '"' .. version .. '"'
+                          )
+                if options.dependencies then
+                    content = replace_dependencies(names, version, content)
+                end
+
+                -- only packages have an entry in Cargo.lock
+                if options.lockfile and member.name ~= nil then
+                    return content, {lockfile = "cargo"}
+                end
+                return content
+            end
+        end
+           )
+end
+
+local replace_npm_dependencies = function(names, version, content)
+    -- replace the ranges of the named packages in all dependency objects,
+    -- keeping any range operator or protocol in front of the version, and
+    -- leaving ranges without a version like "workspace:*" untouched
+
+    for _, key in ipairs(
+                      {
+            "dependencies", "devDependencies", "peerDependencies",
+            "optionalDependencies"
+        }
+                  ) do
+        local _, start = content:find('"' .. key .. '"%s*:%s*{')
+        if start ~= nil then
+            local stop = content:find("}", start, true) or #content
+            local object = content:sub(start, stop):gsub(
+                               '("([^"]+)"%s*:%s*"[^"%d]*)%d[^"]*"',
+                               function(prefix, name)
+                    if names[name] then
+                        return prefix .. version .. '"'
+                    end
+                end
+                           )
+            content = content:sub(1, start - 1) .. object ..
+                          content:sub(stop + 1)
+        end
+    end
+
+    return content
+end
+
+helpers.npm_workspace = function(options)
+    -- map the package.json files of the npm, Yarn, or pnpm workspace and all
+    -- of its members, optionally updating the dependencies between them
+
+    options = options or {}
+
+    return workspace(
+               options, helpers.npm_workspace_members, "package.json",
+               function(member, names)
+            return function(version, content)
+                content = helpers.package_json(version, content)
+                if options.dependencies then
+                    content = replace_npm_dependencies(
+                                  names, version, content
+                              )
+                end
+
+                -- the workspace shares a single lockfile in its root
+                if options.lockfile and member.path == "." then
+                    return content, {lockfile = "npm"}
+                end
+                return content
+            end
+        end
+           )
+end
+
+helpers.helm_chart = function(options)
+    -- build a bumping function that replaces the top-level "version" and
+    -- "appVersion" keys of a Helm chart, keeping the quoting style and
+    -- trailing comments; either of them can be switched off
+
+    options = options or {}
+
+    local bump_key = function(key, version, content)
+        -- only match keys at the start of a line, so nested keys are not
+        -- touched
+
+        local pattern = ("\n(%s:[ \t]*)([\"']?)[^\"'\n#]-%%2([ \t]*[#\n])")
+                            :format(key)
+
+        return (("\n" .. content .. "\n"):gsub(
+                   pattern, function(prefix, quote, suffix)
+                return "\n" .. prefix .. quote .. version .. quote .. suffix
+            end, 1
+               ):sub(2, -2))
+    end
+
+    return function(version, content)
+        if options.version ~= false then
+            content = bump_key("version", version, content)
+        end
+        if options.app_version ~= false then
+            content = bump_key("appVersion", version, content)
+        end
+        return content
+    end
+end
+
+local git_config = function(key)
+    -- read a value from the Git config, or nil if it is not set
+
+    local process = io.popen(("git config %s"):format(key))
+    local value = process:read("*a"):gsub("%s+$", "")
+    process:close()
+    if value == "" then
+        return nil
+    end
+    return value
+end
+
+local identity = function(name, email)
+    -- format a name and email as maintainer, falling back to the identity in
+    -- the Git config for missing parts
+
+    name = name or git_config("user.name") or ""
+    email = email or git_config("user.email") or ""
+    return ("%s <%s>"):format(name, email)
+end
+
+helpers.debian_changelog = function(options)
+    -- build a bumping function that prepends an entry in the format of `dch`
+    -- to a debian/changelog, with the package name taken from the latest
+    -- entry and the maintainer from DEBFULLNAME and DEBEMAIL, or else from the
+    -- Git config; snapshots go to the UNRELEASED distribution
+
+    options = options or {}
+
+    return function(version, content)
+        -- pre-releases have to sort before the final release, which is
+        -- achieved with a tilde in Debian versions
+        local debian_version = ("%s-%s"):format(
+                                   (version:gsub("-", "~")),
+                                   options.revision or "1"
+                               )
+
+        local package, current_version = content:match("^(%S+) %(([^)]+)%)")
+        if current_version == debian_version then
+            return content
+        end
+
+        local distribution = options.distribution or "unstable"
+        if version:find("%-SNAPSHOT$") then
+            distribution = "UNRELEASED"
+        end
+
+        local header = ("%s (%s) %s; urgency=%s"):format(
+                           options.package or package or "unknown",
+                           debian_version, distribution,
+                           options.urgency or "medium"
+                       )
+        local trailer = (" -- %s  %s"):format(
+                            options.maintainer or
+                                identity(
+                                    os.getenv("DEBFULLNAME"),
+                                    os.getenv("DEBEMAIL")
+                                ),
+                            os.date("%a, %d %b %Y %H:%M:%S %z")
+                        )
+
+        return ("%s\n\n  * New upstream release %s.\n\n%s\n\n%s"):format(
+                   header, version, trailer, content
+               )
+    end
+end
+
+helpers.rpm_spec = function(options)
+    -- build a bumping function that updates the "Version" tag of an RPM spec
+    -- file, resets its "Release" tag, and prepends an entry to its
+    -- changelog, so the version only has to be maintained in one place; the
+    -- packager is taken from the "Packager" tag, RPM_PACKAGER, or else from
+    -- the Git config
+
+    options = options or {}
+
+    return function(version, content)
+        -- pre-releases have to sort before the final release, which is
+        -- achieved with a tilde in RPM versions
+        local rpm_version = version:gsub("-", "~")
+
+        content = "\n" .. content
+
+        local current_version = content:match(
+                                    "\nVersion:[ \t]*([^\n]-)[ \t]*\n"
+                                )
+        if current_version == rpm_version then
+            return content:sub(2)
+        end
+
+        content = content:gsub(
+                      "(\nVersion:[ \t]*)[^\n]*", function(prefix)
+                return prefix .. rpm_version
+            end, 1
+                  )
+
+        -- a new version always starts with the first release
+        content = content:gsub(
+                      "(\nRelease:[ \t]*)%d+", function(prefix)
+                return prefix .. "1"
+            end, 1
+                  )
+
+        local packager = options.packager or
+                             content:match(
+                                 "\nPackager:[ \t]*([^\n]-)[ \t]*\n"
+                             ) or os.getenv("RPM_PACKAGER") or identity()
+
+        local entry = ("* %s %s - %s-1\n- Update to %s\n"):format(
+                          os.date("%a %b %d %Y"), packager, rpm_version,
+                          version
+                      )
+
+        local count
+        content, count = content:gsub(
+                             "(\n%%changelog[ \t]*\n)", function(header)
+                return header .. entry .. "\n"
+            end, 1
+                         )
+        if count == 0 then
+            content = ("%s\n%%changelog\n%s"):format(content, entry)
+        end
+
+        return content:sub(2)
+    end
+end
+
+local merge_hooks = function(hooks, more)
+    -- merge the hooks returned by a further bumping function into the
+    -- previous ones, where pre_func and post_func functions are run one after
+    -- another, pre_write functions each on the result of the previous one,
+    -- and the further hooks take precedence for all other members
+
+    if hooks == nil or more == nil then
+        return hooks or more
+    end
+
+    local merged = {}
+    for name, value in pairs(hooks) do
+        merged[name] = value
+    end
+    for name, value in pairs(more) do
+        local previous = merged[name]
+        if (name == "pre_func" or name == "post_func") and previous ~= nil then
+            merged[name] = function()
+                previous()
+                value()
+            end
+        elseif name == "pre_write" and previous ~= nil then
+            merged[name] = function(content, ctx)
+                return value(previous(content, ctx), ctx)
+            end
+        else
+            merged[name] = value
+        end
+    end
+
+    return merged
+end
+
+helpers.chain = function(...)
+    -- build a bumping function that runs the given bumping functions one
+    -- after another, each on the contents returned by the previous one, and
+    -- returns the merged hooks of all of them
+
+    local funcs = {...}
+
+    return function(version, content)
+        local hooks
+        for _, func in ipairs(funcs) do
+            local more
+            content, more = func(version, content)
+            hooks = merge_hooks(hooks, more)
+        end
+        return content, hooks
+    end
+end
+
+helpers.when = function(predicate, func)
+    -- build a bumping function that runs the given one only if the predicate
+    -- holds for the version and the global ctx table, just like the enabled
+    -- option of a mapping, and leaves the contents untouched otherwise
+
+    return function(version, content)
+        if predicate(version, ctx) then
+            return func(version, content)
+        end
+        return content
+    end
+end
+
+return helpers
@@ -0,0 +1,291 @@
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{PoisonError, RwLock};
+use std::time::{Duration, Instant};
+
+use mlua::prelude::*;
+
+use crate::mapping::{escapes_worktree, is_outside_worktree};
+use crate::report::HookReport;
+use crate::retry::Backoff;
+use crate::{command, Error, Result};
+
+/// Get the directory hooks run in, as declared by the `dir` member of the hooks table.
+///
+/// Hooks run in the root of the worktree by default. With `"file"`, they run in the directory of
+/// the mapped file, which global hooks do not have, and any other value is a directory relative to
+/// the worktree. Just like keys of mappings, such directories must not lead outside of the
+/// worktree, neither by absolute paths, nor via `..` or symlinks.
+fn hook_dir(hooks: &LuaTable, workdir: &Path, file: Option<&Path>) -> Result<PathBuf> {
+    let dir = match hooks.get::<_, Option<String>>("dir")? {
+        None => return Ok(workdir.to_path_buf()),
+        Some(dir) => dir,
+    };
+
+    let path = match (dir.as_str(), file.and_then(Path::parent)) {
+        ("worktree", _) => workdir.to_path_buf(),
+        ("file", Some(parent)) => parent.to_path_buf(),
+        ("file", None) => return Err(Error::InvalidHookDir { dir }),
+        (relative, _) => {
+            let path = workdir.join(relative);
+            if is_outside_worktree(relative) || escapes_worktree(&workdir.canonicalize()?, &path) {
+                return Err(Error::InvalidHookDir { dir });
+            }
+            path
+        }
+    };
+    match path.is_dir() {
+        true => Ok(path),
+        false => Err(Error::InvalidHookDir { dir }),
+    }
+}
+
+/// Lock of the current directory of the process.
+///
+/// Hook functions change the current directory while they run, so Lua code that might rely on it
+/// has to hold a read lock when running on worker threads of parallel bumps.
+pub(crate) static CURRENT_DIR: RwLock<()> = RwLock::new(());
+
+/// Run a hook function in the given directory, restoring the current directory afterwards.
+///
+/// This way, relative paths in `os.execute` or `io.open` do not depend on where git-bump was
+/// invoked.
+fn in_dir<T>(dir: &Path, hook: impl FnOnce() -> Result<T>) -> Result<T> {
+    let _current_dir = CURRENT_DIR.write().unwrap_or_else(PoisonError::into_inner);
+    let previous = env::current_dir()?;
+    env::set_current_dir(dir)?;
+    let result = hook();
+    env::set_current_dir(previous)?;
+    result
+}
+
+/// Run hooks that have to be executed before writing.
+///
+/// The hooks run in the directory given by [`hook_dir`], with the mapped file, if any. Returns
+/// `None` if there are no such hooks.
+pub(crate) fn run_pre_hooks(
+    hooks: &LuaTable,
+    workdir: &Path,
+    file: Option<&Path>,
+) -> Result<Option<HookReport>> {
+    let start = Instant::now();
+    let mut report = None;
+    let dir = &hook_dir(hooks, workdir, file)?;
+
+    if let Some(pre_func) = hooks.get::<_, Option<LuaFunction>>("pre_func")? {
+        in_dir(dir, || {
+            pre_func
+                .call::<_, ()>(())
+                .map_err(|source| Error::LuaPreFuncFailed { source })
+        })?;
+        report = Some(1);
+    }
+
+    let mut output = String::new();
+    if let Some(pre_cmd) = hooks.get::<_, Option<Vec<String>>>("pre_cmd")? {
+        output = run_command(&pre_cmd, dir)?;
+        report = Some(1);
+    }
+
+    Ok(report.map(|attempts| HookReport {
+        phase: "pre",
+        attempts,
+        duration: start.elapsed(),
+        output,
+    }))
+}
+
+/// Run hooks that have to be executed after writing.
+///
+/// Since post hooks often talk to network services, they can be retried on failure. Just like
+/// pre hooks, they run in the directory given by [`hook_dir`]. Returns `None` if there are no such
+/// hooks.
+pub(crate) fn run_post_hooks(
+    hooks: &LuaTable,
+    workdir: &Path,
+    file: Option<&Path>,
+) -> Result<Option<HookReport>> {
+    let start = Instant::now();
+    let mut report = None;
+    let mut output = String::new();
+    let dir = &hook_dir(hooks, workdir, file)?;
+
+    if let Some(post_func) = hooks.get::<_, Option<LuaFunction>>("post_func")? {
+        let attempts = with_retries(hooks, || {
+            in_dir(dir, || {
+                post_func
+                    .call::<_, ()>(())
+                    .map_err(|source| Error::LuaPostFuncFailed { source })
+            })
+        })?;
+        report = Some(attempts);
+    }
+
+    if let Some(post_cmd) = hooks.get::<_, Option<Vec<String>>>("post_cmd")? {
+        let attempts = with_retries(hooks, || {
+            output = run_command(&post_cmd, dir)?;
+            Ok(())
+        })?;
+        report = Some(report.unwrap_or(0).max(attempts));
+    }
+
+    Ok(report.map(|attempts| HookReport {
+        phase: "post",
+        attempts,
+        duration: start.elapsed(),
+        output,
+    }))
+}
+
+/// Run the global hook `post_commit` or `post_tag` after the according Git object was created.
+///
+/// The hook gets the hash of the commit or the name of the tag, respectively. Just like other post
+/// hooks, it is retried as configured and runs in the directory given by [`hook_dir`]. Since the
+/// Git object exists already, a failing hook only results in a warning, so that tagging, pushing,
+/// and further commits still happen. Returns `None` if there is no such hook or it failed.
+pub(crate) fn run_git_hook(
+    hooks: &LuaTable,
+    workdir: &Path,
+    hook: &'static str,
+    argument: &str,
+) -> Option<HookReport> {
+    let start = Instant::now();
+    let result = hooks
+        .get::<_, Option<LuaFunction>>(hook)
+        .map_err(Error::from)
+        .and_then(|func| {
+            let func = match func {
+                Some(func) => func,
+                None => return Ok(None),
+            };
+            let dir = &hook_dir(hooks, workdir, None)?;
+
+            with_retries(hooks, || {
+                in_dir(dir, || {
+                    func.call::<_, ()>(argument)
+                        .map_err(|source| Error::LuaHookFailed { hook, source })
+                })
+            })
+            .map(Some)
+        });
+
+    match result {
+        Ok(attempts) => attempts.map(|attempts| HookReport {
+            phase: hook,
+            attempts,
+            duration: start.elapsed(),
+            output: String::new(),
+        }),
+        Err(err) => {
+            eprintln!("Warning: Hook {} failed for {}: {}", hook, argument, err);
+            None
+        }
+    }
+}
+
+/// Run a hook, retrying it as configured in the hooks table.
+///
+/// The number of retries is taken from `post_retries`, defaulting to none. The delay before the
+/// first retry is taken from `post_retry_delay` in seconds, defaulting to one second, and doubles
+/// with every further retry, see [`Backoff`]. Returns the number of attempts needed.
+fn with_retries(hooks: &LuaTable, hook: impl FnMut() -> Result<()>) -> Result<u32> {
+    let retries = hooks.get::<_, Option<u32>>("post_retries")?.unwrap_or(0);
+    let delay = hooks
+        .get::<_, Option<f64>>("post_retry_delay")?
+        .unwrap_or(1.0);
+    let delay = Duration::try_from_secs_f64(delay).map_err(|_| Error::InvalidOption {
+        key: String::from("hooks"),
+        option: String::from("post_retry_delay"),
+        expected: String::from("a non-negative number of seconds"),
+        type_name: delay.to_string(),
+    })?;
+
+    let backoff = Backoff { retries, delay };
+    let (_, attempts) = backoff.run(
+        hook,
+        |_| true,
+        |attempt, delay, err| {
+            eprintln!(
+                "Warning: Post hook failed on attempt {} of {}, retrying in {:?}: {}",
+                attempt,
+                retries + 1,
+                delay,
+                err
+            );
+        },
+    )?;
+    Ok(attempts)
+}
+
+/// Run `on_error` hook after a file could not be bumped.
+///
+/// The hook gets the file as shown in messages, the phase in which bumping failed, and the
+/// error message. Since the original error is reported anyway, a failing hook only results in a
+/// warning.
+pub(crate) fn run_error_hook(hooks: &LuaTable, file: &str, phase: &str, message: &str) {
+    let result =
+        hooks
+            .get::<_, Option<LuaFunction>>("on_error")
+            .and_then(|on_error| match on_error {
+                Some(on_error) => on_error.call::<_, ()>((file, phase, message)),
+                None => Ok(()),
+            });
+
+    if let Err(err) = result {
+        eprintln!("Warning: Hook on_error failed for {}: {}", file, err);
+    }
+}
+
+/// Run an external command given as list of program and arguments.
+///
+/// The command is executed directly, without involving a shell. Returns the captured output, with
+/// standard error following standard output.
+fn run_command(argv: &[String], dir: &Path) -> Result<String> {
+    let (program, args) = argv.split_first().ok_or(Error::EmptyCommand)?;
+
+    let mut command = Command::new(program);
+    command.args(args).current_dir(dir);
+
+    let display = command::display(&command);
+    let output = command::run(&mut command).map_err(|output| Error::CommandFailed {
+        command: display,
+        output,
+    })?;
+
+    Ok(format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    ))
+}
+
+#[test]
+fn resolve_hook_directories() {
+    let lua = Lua::new();
+    let tmpdir = crate::state::TempDir::create().unwrap();
+    let workdir = tmpdir.path().to_path_buf();
+    std::fs::create_dir(workdir.join("src")).unwrap();
+    let file = workdir.join("src").join("lib.rs");
+    let dir = |dir: Option<&str>, file: Option<&Path>| {
+        let hooks = lua.create_table().unwrap();
+        hooks.set("dir", dir).unwrap();
+        hook_dir(&hooks, &workdir, file).ok()
+    };
+
+    assert_eq!(dir(None, Some(&file)), Some(workdir.clone()));
+    assert_eq!(dir(Some("worktree"), Some(&file)), Some(workdir.clone()));
+    assert_eq!(dir(Some("file"), Some(&file)), Some(workdir.join("src")));
+    assert_eq!(dir(Some("file"), None), None);
+    assert_eq!(dir(Some("src"), None), Some(workdir.join("src")));
+    assert_eq!(dir(Some("missing"), None), None);
+    assert_eq!(dir(Some(".."), None), None);
+    assert_eq!(dir(Some("src/../.."), None), None);
+    assert_eq!(dir(Some(&env::temp_dir().to_string_lossy()), None), None);
+
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(env::temp_dir(), workdir.join("link")).unwrap();
+        assert_eq!(dir(Some("link"), None), None);
+    }
+}
@@ -0,0 +1,1663 @@
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use crate::project::{cargo_workspace_members, toml_section};
+use crate::{Error, Result};
+
+/// Line of an imported config that holds the current version, which the config bumps itself.
+const CURRENT_VERSION: &str = "local current_version = \"{current_version}\"";
+
+/// Tools whose configs can be imported.
+#[derive(Clone, Copy, clap::ValueEnum)]
+pub(crate) enum Tool {
+    /// bumpversion and bump2version, from .bumpversion.cfg or setup.cfg
+    Bumpversion,
+    /// Pre-release replacements of cargo-release, from Cargo.toml or release.toml
+    CargoRelease,
+    /// File updates of semantic-release plugins, from package.json or .releaserc
+    SemanticRelease,
+}
+
+/// Config generated from the config of another tool.
+pub(crate) struct Import {
+    /// File the config was imported from, relative to the worktree.
+    pub(crate) source: String,
+    /// Generated Lua config.
+    pub(crate) config: String,
+    /// Settings that could not be converted.
+    pub(crate) warnings: Vec<String>,
+}
+
+/// Generate a config from the config of another tool in the worktree.
+pub(crate) fn import(tool: Tool, workdir: &Path) -> Result<Import> {
+    match tool {
+        Tool::Bumpversion => import_bumpversion(workdir),
+        Tool::CargoRelease => import_cargo_release(workdir),
+        Tool::SemanticRelease => import_semantic_release(workdir),
+    }
+}
+
+/// Search and replace templates for a file, as understood by `helpers.replace`.
+#[derive(Clone)]
+struct Rule {
+    search: String,
+    replace: String,
+    /// Further options of the rule, as Lua expressions.
+    options: Vec<(&'static str, String)>,
+}
+
+impl Rule {
+    /// Rule with plain templates.
+    fn plain(search: String, replace: String) -> Self {
+        Rule {
+            search,
+            replace,
+            options: Vec::new(),
+        }
+    }
+
+    /// Render as Lua table.
+    fn to_lua(&self) -> String {
+        let mut fields = vec![
+            format!("        search = {}", lua_string(&self.search)),
+            format!("        replace = {}", lua_string(&self.replace)),
+        ];
+        for (name, value) in &self.options {
+            fields.push(format!("        {} = {}", name, value));
+        }
+
+        format!("    {{\n{}\n    }}", fields.join(",\n"))
+    }
+}
+
+/// Generate a config from the given rules, which maintains the current version itself.
+///
+/// The mappings start out as the given Lua expression, to which each file is added, mapped to
+/// `helpers.replace` with its rules. The config file itself is mapped as well, so the current
+/// version in it is bumped along with the files.
+fn render_config(
+    header: &str,
+    current_version: &str,
+    base: &str,
+    files: &[(String, Vec<Rule>)],
+    warnings: &[String],
+) -> String {
+    let mut config = format!(
+        "-- {}\n-- Just like before, the current version is maintained in this config, which\n-- is bumped as well.\n",
+        header
+    );
+    for warning in warnings {
+        let _ = writeln!(config, "-- Not converted: {}", warning);
+    }
+
+    let _ = write!(
+        config,
+        "\n{}\n\nlocal mappings = {}\n",
+        CURRENT_VERSION.replace("{current_version}", current_version),
+        base
+    );
+
+    let self_rules = [Rule::plain(
+        CURRENT_VERSION.to_string(),
+        CURRENT_VERSION.replace("current_version}", "new_version}"),
+    )];
+    let mappings = std::iter::once((".git-bump.lua", &self_rules[..])).chain(
+        files
+            .iter()
+            .map(|(file, rules)| (file.as_str(), &rules[..])),
+    );
+    for (file, rules) in mappings {
+        let rules = rules
+            .iter()
+            .map(Rule::to_lua)
+            .collect::<Vec<_>>()
+            .join(",\n");
+        let _ = write!(
+            config,
+            "\nmappings[{}] = helpers.replace(current_version, {{\n{}\n}})\n",
+            lua_string(file),
+            rules
+        );
+    }
+
+    config.push_str("\nreturn mappings\n");
+    config
+}
+
+/// Quote a string as Lua string literal.
+fn lua_string(s: &str) -> String {
+    let mut lua = String::with_capacity(s.len() + 2);
+    lua.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => lua.push_str("\\\""),
+            '\\' => lua.push_str("\\\\"),
+            '\n' => lua.push_str("\\n"),
+            '\r' => lua.push_str("\\r"),
+            '\t' => lua.push_str("\\t"),
+            c if c.is_ascii_control() => {
+                let _ = write!(lua, "\\{:03}", c as u32);
+            }
+            c => lua.push(c),
+        }
+    }
+    lua.push('"');
+    lua
+}
+
+/// Parse an INI file as read by Python's `configparser`, into sections with their values.
+///
+/// Indented lines continue the value of the previous line, joined by a newline, including empty
+/// lines in between.
+fn parse_ini(contents: &str) -> Vec<(String, Vec<(String, String)>)> {
+    let mut sections: Vec<(String, Vec<(String, String)>)> = Vec::new();
+    let mut empty_lines = 0;
+
+    for line in contents.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            empty_lines += 1;
+            continue;
+        }
+        let empty_lines = std::mem::take(&mut empty_lines);
+        if trimmed.starts_with(['#', ';']) {
+            continue;
+        }
+
+        if let Some(name) = trimmed.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+            sections.push((name.trim().to_string(), Vec::new()));
+            continue;
+        }
+
+        let values = match sections.last_mut() {
+            Some((_, values)) => values,
+            None => continue,
+        };
+
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = values.last_mut() {
+                if !value.is_empty() {
+                    value.push_str(&"\n".repeat(empty_lines + 1));
+                }
+                value.push_str(trimmed);
+            }
+        } else if let Some((key, value)) = trimmed.split_once(['=', ':']) {
+            values.push((key.trim().to_string(), value.trim().to_string()));
+        }
+    }
+
+    sections
+}
+
+/// Get a value of an INI section.
+fn value<'a>(values: &'a [(String, String)], key: &str) -> Option<&'a str> {
+    values
+        .iter()
+        .find(|(name, _)| name == key)
+        .map(|(_, value)| value.as_str())
+}
+
+/// Convert a template of bumpversion, which uses Python's format syntax.
+///
+/// Escaped braces are unescaped, and unsupported placeholders like the parts of the version are
+/// reported.
+fn bumpversion_template(template: &str, warnings: &mut Vec<String>) -> String {
+    let mut converted = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find(['{', '}']) {
+        converted.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        if rest.starts_with("{{") || rest.starts_with("}}") {
+            converted.push_str(&rest[..1]);
+            rest = &rest[2..];
+            continue;
+        }
+
+        let end = match rest.find('}') {
+            Some(end) if rest.starts_with('{') => end + 1,
+            _ => 1,
+        };
+        let placeholder = &rest[..end];
+        let name = placeholder
+            .trim_matches(['{', '}'])
+            .split(':')
+            .next()
+            .unwrap_or_default();
+        if !["current_version", "new_version", "now", "utcnow"].contains(&name) {
+            warnings.push(format!(
+                "placeholder {} in template {:?}",
+                placeholder, template
+            ));
+        }
+        converted.push_str(placeholder);
+        rest = &rest[end..];
+    }
+    converted.push_str(rest);
+
+    converted
+}
+
+/// Import the config of bumpversion or bump2version.
+fn import_bumpversion(workdir: &Path) -> Result<Import> {
+    let (source, sections) = [".bumpversion.cfg", "setup.cfg"]
+        .iter()
+        .find_map(|file| {
+            let sections = parse_ini(&fs::read_to_string(workdir.join(file)).ok()?);
+            sections
+                .iter()
+                .any(|(name, _)| name == "bumpversion")
+                .then(|| (file.to_string(), sections))
+        })
+        .ok_or_else(|| Error::NothingToImport {
+            tool: String::from("bumpversion"),
+        })?;
+
+    let mut warnings = Vec::new();
+    let empty = Vec::new();
+    let global = sections
+        .iter()
+        .find(|(name, _)| name == "bumpversion")
+        .map_or(&empty, |(_, values)| values);
+
+    let current_version = value(global, "current_version").unwrap_or_default();
+    let default_search = value(global, "search").unwrap_or("{current_version}");
+    let default_replace = value(global, "replace").unwrap_or("{new_version}");
+    for (key, _) in global {
+        if !["current_version", "search", "replace"].contains(&key.as_str()) {
+            warnings.push(format!("option {}", key));
+        }
+    }
+
+    let mut files: Vec<(String, Vec<Rule>)> = Vec::new();
+    for (name, values) in &sections {
+        let (kind, target) = match name
+            .strip_prefix("bumpversion:")
+            .and_then(|rest| rest.split_once(':'))
+        {
+            Some(section) => section,
+            None => continue,
+        };
+
+        // Sections for the same file can be distinguished by a suffix, like `file(2)`
+        if kind != "file" && !kind.starts_with("file(") {
+            warnings.push(format!("section [{}]", name));
+            continue;
+        }
+
+        let rule = Rule::plain(
+            bumpversion_template(
+                value(values, "search").unwrap_or(default_search),
+                &mut warnings,
+            ),
+            bumpversion_template(
+                value(values, "replace").unwrap_or(default_replace),
+                &mut warnings,
+            ),
+        );
+        for (key, _) in values {
+            if !["search", "replace"].contains(&key.as_str()) {
+                warnings.push(format!("option {} of section [{}]", key, name));
+            }
+        }
+
+        match files.iter_mut().find(|(file, _)| file == target) {
+            Some((_, rules)) => rules.push(rule),
+            None => files.push((target.to_string(), vec![rule])),
+        }
+    }
+
+    let header = format!("Imported from {} by `git bump import bumpversion`.", source);
+    let config = render_config(&header, current_version, "{}", &files, &warnings);
+    Ok(Import {
+        source,
+        config,
+        warnings,
+    })
+}
+
+/// Value of a TOML or JSON document, as far as needed to import configs.
+#[derive(Clone, Debug, PartialEq)]
+enum Value {
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    Null,
+    Array(Vec<Value>),
+    Table(Vec<(String, Value)>),
+}
+
+/// Skip whitespace, newlines, and comments.
+fn skip_blank(input: &str) -> &str {
+    let mut input = input.trim_start();
+    while input.starts_with('#') {
+        input = input
+            .split_once('\n')
+            .map_or("", |(_, rest)| rest)
+            .trim_start();
+    }
+    input
+}
+
+/// Parse a bare or quoted key.
+fn parse_key(input: &str) -> Option<(String, &str)> {
+    let input = input.trim_start();
+    if input.starts_with(['"', '\'']) {
+        return match parse_value(input)? {
+            (Value::String(key), rest) => Some((key, rest)),
+            _ => None,
+        };
+    }
+
+    let end = input
+        .find(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_'))
+        .unwrap_or(input.len());
+    (end > 0).then(|| (input[..end].to_string(), &input[end..]))
+}
+
+/// Parse a TOML or JSON value, returning it along with the remaining input.
+///
+/// Strings, numbers, booleans, arrays, and inline tables or objects are supported.
+fn parse_value(input: &str) -> Option<(Value, &str)> {
+    let input = input.trim_start();
+
+    for (quotes, literal) in [("\"\"\"", false), ("'''", true)] {
+        if let Some(rest) = input.strip_prefix(quotes) {
+            let (string, rest) = rest.split_once(quotes)?;
+            // A newline directly after the opening quotes is not part of the string
+            let string = string.strip_prefix('\n').unwrap_or(string);
+            let string = match literal {
+                true => string.to_string(),
+                false => unescape(string)?,
+            };
+            return Some((Value::String(string), rest));
+        }
+    }
+
+    if let Some(rest) = input.strip_prefix('\'') {
+        let (string, rest) = rest.split_once('\'')?;
+        return Some((Value::String(string.to_string()), rest));
+    }
+
+    if let Some(rest) = input.strip_prefix('"') {
+        let mut escaped = false;
+        let end = rest.find(|c| {
+            let end = c == '"' && !escaped;
+            escaped = c == '\\' && !escaped;
+            end
+        })?;
+        return Some((Value::String(unescape(&rest[..end])?), &rest[end + 1..]));
+    }
+
+    if let Some(mut rest) = input.strip_prefix('[') {
+        let mut values = Vec::new();
+        loop {
+            rest = skip_blank(rest);
+            if let Some(rest) = rest.strip_prefix(']') {
+                return Some((Value::Array(values), rest));
+            }
+            let (value, after) = parse_value(rest)?;
+            values.push(value);
+            rest = skip_blank(after);
+            rest = rest.strip_prefix(',').unwrap_or(rest);
+        }
+    }
+
+    if let Some(mut rest) = input.strip_prefix('{') {
+        let mut values = Vec::new();
+        loop {
+            rest = rest.trim_start();
+            if let Some(rest) = rest.strip_prefix('}') {
+                return Some((Value::Table(values), rest));
+            }
+            let (key, after) = parse_key(rest)?;
+            let (value, after) = parse_value(after.trim_start().strip_prefix(['=', ':'])?)?;
+            values.push((key, value));
+            rest = after.trim_start();
+            rest = rest.strip_prefix(',').unwrap_or(rest);
+        }
+    }
+
+    let end = input
+        .find(|c: char| !(c.is_alphanumeric() || "+-_.".contains(c)))
+        .unwrap_or(input.len());
+    let value = match &input[..end] {
+        "true" => Value::Boolean(true),
+        "false" => Value::Boolean(false),
+        "null" => Value::Null,
+        number => {
+            let number = number.replace('_', "");
+            match number.parse() {
+                Ok(integer) => Value::Integer(integer),
+                Err(_) => Value::Float(number.parse().ok()?),
+            }
+        }
+    };
+    Some((value, &input[end..]))
+}
+
+/// Resolve the escape sequences of a basic TOML string or a JSON string.
+fn unescape(string: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(string.len());
+    let mut chars = string.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next()? {
+            'b' => unescaped.push('\u{8}'),
+            'f' => unescaped.push('\u{c}'),
+            'n' => unescaped.push('\n'),
+            'r' => unescaped.push('\r'),
+            't' => unescaped.push('\t'),
+            'u' => {
+                let code = chars.by_ref().take(4).collect::<String>();
+                unescaped.push(char::from_u32(u32::from_str_radix(&code, 16).ok()?)?);
+            }
+            c => unescaped.push(c),
+        }
+    }
+    Some(unescaped)
+}
+
+/// Get the pre-release replacements of a cargo-release config in the given table, where an empty
+/// name stands for the top level of a `release.toml`.
+///
+/// The replacements can either be given as array in the table itself, or as array of tables.
+fn release_replacements(toml: &str, table: &str) -> Option<Vec<Vec<(String, Value)>>> {
+    let key = "pre-release-replacements";
+    let (table_header, array_header) = match table {
+        "" => (String::new(), format!("[[{}]]", key)),
+        table => (format!("[{}]", table), format!("[[{}.{}]]", table, key)),
+    };
+
+    let mut replacements = None::<Vec<Vec<(String, Value)>>>;
+    let mut header = String::new();
+    let mut rest = toml;
+    while !rest.is_empty() {
+        let line = rest.split('\n').next().unwrap_or_default();
+        let trimmed = line.trim_start();
+        let next_line = rest.split_once('\n').map_or("", |(_, rest)| rest);
+
+        if trimmed.starts_with('[') {
+            header = trimmed
+                .split('#')
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_string();
+            if header == array_header {
+                replacements.get_or_insert_with(Vec::new).push(Vec::new());
+            }
+            rest = next_line;
+            continue;
+        }
+
+        let (name, value) = match parse_key(trimmed)
+            .and_then(|(name, after)| Some((name, after.trim_start().strip_prefix('=')?)))
+        {
+            Some(pair) => pair,
+            None => {
+                rest = next_line;
+                continue;
+            }
+        };
+
+        // Values can span multiple lines, so they are parsed from the remaining document, of which
+        // the value on this line is the start
+        let value = &rest[line.len() - value.len()..];
+        if header == array_header {
+            let (value, after) = parse_value(value)?;
+            if let Some(table) = replacements.as_mut().and_then(|tables| tables.last_mut()) {
+                table.push((name, value));
+            }
+            rest = after;
+        } else if header == table_header && name == key {
+            let (value, after) = parse_value(value)?;
+            if let Value::Array(values) = value {
+                let tables = values.into_iter().filter_map(|value| match value {
+                    Value::Table(table) => Some(table),
+                    _ => None,
+                });
+                replacements.get_or_insert_with(Vec::new).extend(tables);
+            }
+            rest = after;
+        } else {
+            rest = next_line;
+        }
+    }
+
+    replacements
+}
+
+/// Translate a regular expression into an equivalent Lua pattern.
+///
+/// Only the common subset is supported: literals, escapes, character classes, groups, anchors, and
+/// quantifiers of single characters or classes. Returns `None` for anything else, like
+/// alternations or quantified groups.
+fn lua_pattern(regex: &str) -> Option<String> {
+    let chars = regex.chars().collect::<Vec<_>>();
+    let mut pattern = String::new();
+    // Last item that can take a quantifier
+    let mut last = None::<String>;
+
+    let mut index = 0;
+    while index < chars.len() {
+        let c = chars[index];
+        index += 1;
+
+        let item = match c {
+            '\\' => {
+                let escaped = *chars.get(index)?;
+                index += 1;
+                match escaped {
+                    'd' | 'D' | 's' | 'S' => format!("%{}", escaped),
+                    'w' => String::from("[%w_]"),
+                    'W' => String::from("[^%w_]"),
+                    'n' => String::from("\n"),
+                    't' => String::from("\t"),
+                    c if c.is_ascii_punctuation() => format!("%{}", c),
+                    _ => return None,
+                }
+            }
+            '[' => {
+                let mut class = String::from("[");
+                if chars.get(index) == Some(&'^') {
+                    class.push('^');
+                    index += 1;
+                }
+                let mut first = true;
+                loop {
+                    let c = *chars.get(index)?;
+                    index += 1;
+                    match c {
+                        ']' if !first => break,
+                        '[' => return None,
+                        '\\' => {
+                            let escaped = *chars.get(index)?;
+                            index += 1;
+                            match escaped {
+                                'd' | 'D' | 's' | 'S' => class.push_str(&format!("%{}", escaped)),
+                                'w' => class.push_str("%w_"),
+                                c if c.is_ascii_punctuation() => class.push_str(&format!("%{}", c)),
+                                _ => return None,
+                            }
+                        }
+                        '%' | ']' => class.push_str(&format!("%{}", c)),
+                        c => class.push(c),
+                    }
+                    first = false;
+                }
+                class.push(']');
+                class
+            }
+            // Just like in regular expressions, the dot does not match newlines
+            '.' => String::from("[^\n]"),
+            '*' | '+' | '?' => {
+                last.take()?;
+                let lazy = chars.get(index) == Some(&'?');
+                if lazy {
+                    index += 1;
+                }
+                match (c, lazy) {
+                    ('*', true) => pattern.push('-'),
+                    (c, false) => pattern.push(c),
+                    _ => return None,
+                }
+                continue;
+            }
+            '{' => {
+                let item = last.take()?;
+                let end = chars[index..].iter().position(|&c| c == '}')? + index;
+                let count = chars[index..end]
+                    .iter()
+                    .collect::<String>()
+                    .parse::<usize>()
+                    .ok()?;
+                index = end + 1;
+                pattern.push_str(&item.repeat(count.checked_sub(1)?));
+                continue;
+            }
+            '(' if chars.get(index) != Some(&'?') => {
+                pattern.push('(');
+                last = None;
+                continue;
+            }
+            ')' => {
+                pattern.push(')');
+                last = None;
+                continue;
+            }
+            '^' if index == 1 => {
+                pattern.push('^');
+                continue;
+            }
+            '$' if index == chars.len() => {
+                pattern.push('$');
+                continue;
+            }
+            '(' | '|' | '^' | '$' => return None,
+            '%' | '-' | ']' => format!("%{}", c),
+            c => c.to_string(),
+        };
+
+        pattern.push_str(&item);
+        last = Some(item);
+    }
+
+    Some(pattern)
+}
+
+/// Translate a replacement template of cargo-release into a replace template of
+/// `helpers.replace` with a Lua pattern.
+///
+/// References to groups like `$1` become `%1`, and the template variables of cargo-release are
+/// replaced by their equivalents, while unsupported ones are reported.
+fn cargo_release_template(template: &str, crate_name: &str, warnings: &mut Vec<String>) -> String {
+    let mut converted = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find(['$', '%', '{']) {
+        converted.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let digits = rest[1..]
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len() - 1);
+        let group = match rest.strip_prefix("${") {
+            Some(braced) => braced.split_once('}'),
+            None if rest.starts_with('$') && digits > 0 => {
+                Some((&rest[1..1 + digits], &rest[1 + digits..]))
+            }
+            None => None,
+        };
+
+        if let Some((group, after)) =
+            group.filter(|(group, _)| group.len() == 1 && group.chars().all(|c| c.is_ascii_digit()))
+        {
+            converted.push('%');
+            converted.push_str(group);
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix("$$") {
+            converted.push('$');
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix('%') {
+            converted.push_str("%%");
+            rest = after;
+        } else if let Some((variable, after)) = rest
+            .strip_prefix("{{")
+            .and_then(|rest| rest.split_once("}}"))
+        {
+            match variable.trim() {
+                "version" => converted.push_str("{new_version}"),
+                "prev_version" => converted.push_str("{current_version}"),
+                "date" => converted.push_str("{utcnow:%Y-%m-%d}"),
+                "crate_name" => converted.push_str(&crate_name.replace('%', "%%")),
+                _ => {
+                    warnings.push(format!(
+                        "variable {{{{{}}}}} in template {:?}",
+                        variable.trim(),
+                        template
+                    ));
+                    converted.push_str(&rest[..variable.len() + 4]);
+                }
+            }
+            rest = after;
+        } else {
+            converted.push_str(&rest[..1]);
+            rest = &rest[1..];
+        }
+    }
+    converted.push_str(rest);
+
+    converted
+}
+
+/// Get the version in the `[package]` or `[workspace.package]` section of a manifest.
+fn manifest_version(manifest: &str) -> Option<String> {
+    ["package", "workspace.package"].iter().find_map(|section| {
+        toml_section(manifest, section).lines().find_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            match (key.trim(), parse_value(value)?) {
+                ("version", (Value::String(version), _)) => Some(version),
+                _ => None,
+            }
+        })
+    })
+}
+
+/// Import the pre-release replacements of cargo-release.
+///
+/// Just like in cargo-release, the replacements of a package are taken from its `Cargo.toml` or
+/// `release.toml`, or else from the workspace, with file names relative to the package.
+fn import_cargo_release(workdir: &Path) -> Result<Import> {
+    let nothing_to_import = || Error::NothingToImport {
+        tool: String::from("cargo-release"),
+    };
+    let read = |file: String| {
+        fs::read_to_string(workdir.join(&file))
+            .ok()
+            .map(|toml| (file, toml))
+    };
+    // Find the first of the given configs that has replacements
+    let find_replacements = |configs: [Option<(String, String, &str)>; 2]| {
+        configs
+            .into_iter()
+            .flatten()
+            .find_map(|(file, toml, table)| Some((file, release_replacements(&toml, table)?)))
+    };
+
+    let (_, root_manifest) = read(String::from("Cargo.toml")).ok_or_else(nothing_to_import)?;
+    let workspace = find_replacements([
+        read(String::from("release.toml")).map(|(file, toml)| (file, toml, "")),
+        Some((
+            String::from("Cargo.toml"),
+            root_manifest.clone(),
+            "workspace.metadata.release",
+        )),
+    ]);
+
+    let mut sources = Vec::new();
+    let mut warnings = Vec::new();
+    let mut files: Vec<(String, Vec<Rule>)> = Vec::new();
+    let mut current_version = manifest_version(&root_manifest);
+
+    for (name, dir) in cargo_workspace_members(workdir) {
+        let prefix = match dir.as_str() {
+            "." => String::new(),
+            dir => format!("{}/", dir),
+        };
+        let manifest = read(format!("{}Cargo.toml", prefix));
+        if current_version.is_none() {
+            current_version = manifest
+                .as_ref()
+                .and_then(|(_, toml)| manifest_version(toml));
+        }
+
+        let package = find_replacements([
+            manifest.map(|(file, toml)| (file, toml, "package.metadata.release")),
+            read(format!("{}release.toml", prefix)).map(|(file, toml)| (file, toml, "")),
+        ]);
+        let (source, replacements) = match package.as_ref().or(workspace.as_ref()) {
+            Some(config) => config,
+            None => continue,
+        };
+        if !sources.contains(source) {
+            sources.push(source.clone());
+        }
+
+        for replacement in replacements {
+            let get = |key: &str| {
+                replacement
+                    .iter()
+                    .find(|(name, _)| name == key)
+                    .map(|(_, value)| value)
+            };
+            let (file, search, replace) = match (get("file"), get("search"), get("replace")) {
+                (
+                    Some(Value::String(file)),
+                    Some(Value::String(search)),
+                    Some(Value::String(replace)),
+                ) => (format!("{}{}", prefix, file), search, replace),
+                _ => {
+                    warnings.push(format!("incomplete replacement in {}", source));
+                    continue;
+                }
+            };
+
+            let search = match lua_pattern(search) {
+                Some(pattern) => pattern,
+                None => {
+                    warnings.push(format!(
+                        "replacement of {:?} in {}, which has no equivalent Lua pattern",
+                        search, file
+                    ));
+                    continue;
+                }
+            };
+
+            let mut options = vec![("pattern", String::from("true"))];
+            for option in ["exactly", "min", "max"] {
+                if let Some(Value::Integer(count)) = get(option) {
+                    options.push((option, count.to_string()));
+                }
+            }
+            // Unlike in git-bump, replacements are skipped for pre-releases by default
+            if get("prerelease") != Some(&Value::Boolean(true)) {
+                options.push(("prerelease", String::from("false")));
+            }
+
+            let rule = Rule {
+                search,
+                replace: cargo_release_template(replace, &name, &mut warnings),
+                options,
+            };
+            match files.iter_mut().find(|(name, _)| *name == file) {
+                Some((_, rules)) => rules.push(rule),
+                None => files.push((file, vec![rule])),
+            }
+        }
+    }
+
+    if sources.is_empty() {
+        return Err(nothing_to_import());
+    }
+
+    let source = sources.join(", ");
+    let header = format!(
+        "Imported from {} by `git bump import cargo-release`.",
+        source
+    );
+    let base = match workdir.join("Cargo.lock").is_file() {
+        true => "helpers.cargo_workspace {dependencies = true, lockfile = true}",
+        false => "helpers.cargo_workspace {dependencies = true}",
+    };
+    let config = render_config(
+        &header,
+        current_version.as_deref().unwrap_or_default(),
+        base,
+        &files,
+        &warnings,
+    );
+
+    Ok(Import {
+        source,
+        config,
+        warnings,
+    })
+}
+
+/// Config files of semantic-release besides `package.json`, in the order they are searched.
+const RELEASE_CONFIGS: &[&str] = &[
+    ".releaserc",
+    ".releaserc.json",
+    ".releaserc.yaml",
+    ".releaserc.yml",
+    ".releaserc.js",
+    ".releaserc.cjs",
+    ".releaserc.mjs",
+    "release.config.js",
+    "release.config.cjs",
+    "release.config.mjs",
+];
+
+impl Value {
+    /// Get a member of a table.
+    fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Table(table) => table
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    /// Get the value as string, if it is one.
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    /// Get a single value or an array of values as list.
+    fn to_list(&self) -> Vec<&Value> {
+        match self {
+            Value::Array(values) => values.iter().collect(),
+            value => vec![value],
+        }
+    }
+}
+
+/// Translate a template of semantic-release into a template of `helpers.replace`.
+///
+/// The versions of the next and last release become `{new_version}` and `{current_version}`, while
+/// other variables are reported. In replace templates of regular expressions, the given `group`
+/// function translates references to groups.
+fn release_template(
+    template: &str,
+    group: fn(&str) -> Option<(String, &str)>,
+    warnings: &mut Vec<String>,
+) -> String {
+    let mut converted = String::with_capacity(template.len());
+    let mut rest = template;
+    while !rest.is_empty() {
+        if let Some((variable, after)) = rest
+            .strip_prefix("${")
+            .and_then(|rest| rest.split_once('}'))
+        {
+            match variable.trim() {
+                "nextRelease.version" => converted.push_str("{new_version}"),
+                "lastRelease.version" => converted.push_str("{current_version}"),
+                variable => {
+                    warnings.push(format!(
+                        "variable ${{{}}} in template {:?}",
+                        variable, template
+                    ));
+                    converted.push_str(&rest[..variable.len() + 3]);
+                }
+            }
+            rest = after;
+        } else if let Some((reference, after)) = group(rest) {
+            converted.push_str(&reference);
+            rest = after;
+        } else {
+            let c = rest.chars().next().unwrap_or_default();
+            converted.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+
+    converted
+}
+
+/// Translate the special replacement patterns of JavaScript's `String.replace`.
+fn js_group(template: &str) -> Option<(String, &str)> {
+    let mut chars = template.chars();
+    match (chars.next()?, chars.next()) {
+        ('%', _) => Some((String::from("%%"), &template[1..])),
+        ('$', Some('$')) => Some((String::from("$"), &template[2..])),
+        ('$', Some('&')) => Some((String::from("%0"), &template[2..])),
+        ('$', Some(digit @ '1'..='9')) => Some((format!("%{}", digit), &template[2..])),
+        _ => None,
+    }
+}
+
+/// Translate the references of sed's `s` command.
+fn sed_group(template: &str) -> Option<(String, &str)> {
+    let mut chars = template.chars();
+    match (chars.next()?, chars.next()) {
+        ('%', _) => Some((String::from("%%"), &template[1..])),
+        ('&', _) => Some((String::from("%0"), &template[1..])),
+        ('\\', Some(digit @ '1'..='9')) => Some((format!("%{}", digit), &template[2..])),
+        ('\\', Some('n')) => Some((String::from("\n"), &template[2..])),
+        ('\\', Some(c)) => Some((c.to_string(), &template[1 + c.len_utf8()..])),
+        _ => None,
+    }
+}
+
+/// Translate a basic regular expression, as used by sed by default, into an extended one.
+fn bre_to_ere(bre: &str) -> String {
+    let mut ere = String::with_capacity(bre.len());
+    let mut chars = bre.chars().peekable();
+    let mut in_class = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '[' if !in_class => {
+                in_class = true;
+                ere.push(c);
+                // A closing bracket directly after the opening one is part of the class
+                for prefix in ['^', ']'] {
+                    if chars.peek() == Some(&prefix) {
+                        ere.push(prefix);
+                        chars.next();
+                    }
+                }
+            }
+            ']' if in_class => {
+                in_class = false;
+                ere.push(c);
+            }
+            _ if in_class => ere.push(c),
+            '\\' => match chars.next() {
+                Some(c @ ('(' | ')' | '{' | '}' | '+' | '?' | '|')) => ere.push(c),
+                Some(c) => {
+                    ere.push('\\');
+                    ere.push(c);
+                }
+                None => ere.push('\\'),
+            },
+            '(' | ')' | '{' | '}' | '+' | '?' | '|' => {
+                ere.push('\\');
+                ere.push(c);
+            }
+            c => ere.push(c),
+        }
+    }
+    ere
+}
+
+/// Split a shell script into simple commands, as lists of words with quotes and escapes resolved.
+///
+/// Commands are separated by newlines, `;`, `&&`, `||`, and `|`. Returns `None` for scripts that
+/// cannot be split this way, like unterminated quotes.
+fn shell_commands(script: &str) -> Option<Vec<Vec<String>>> {
+    let mut commands = vec![Vec::new()];
+    let mut word = None::<String>;
+    let mut chars = script.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next()? {
+                        '\'' => break,
+                        c => word.push(c),
+                    }
+                }
+            }
+            '"' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next()? {
+                        '"' => break,
+                        '\\' if matches!(chars.peek(), Some('"' | '\\' | '$' | '`')) => {
+                            word.push(chars.next()?)
+                        }
+                        c => word.push(c),
+                    }
+                }
+            }
+            '\\' => match chars.next() {
+                Some('\n') | None => {}
+                Some(c) => word.get_or_insert_with(String::new).push(c),
+            },
+            '\n' | ';' | '&' | '|' => {
+                commands.last_mut()?.extend(word.take());
+                if c != '&' || chars.peek() == Some(&'&') {
+                    if matches!(c, '&' | '|') && chars.peek() == Some(&c) {
+                        chars.next();
+                    }
+                    commands.push(Vec::new());
+                } else {
+                    // Background jobs cannot be split reliably
+                    return None;
+                }
+            }
+            c if c.is_whitespace() => commands.last_mut()?.extend(word.take()),
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    commands.last_mut()?.extend(word);
+    commands.retain(|command| !command.is_empty());
+
+    Some(commands)
+}
+
+/// Parse a sed `s` command into its search and replace parts and its flags.
+fn sed_substitution(script: &str) -> Option<(String, String, String)> {
+    let script = script.trim();
+    let mut chars = script.strip_prefix('s')?.chars();
+    let delimiter = chars
+        .next()
+        .filter(|c| !c.is_alphanumeric() && *c != '\\')?;
+
+    let mut parts = vec![String::new()];
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next()? {
+                // Escaped delimiters stand for themselves
+                c if c == delimiter => parts.last_mut()?.push(c),
+                c => {
+                    parts.last_mut()?.push('\\');
+                    parts.last_mut()?.push(c);
+                }
+            },
+            c if c == delimiter && parts.len() < 3 => parts.push(String::new()),
+            c => parts.last_mut()?.push(c),
+        }
+    }
+
+    let flags = parts.pop().filter(|_| parts.len() == 2)?;
+    let replace = parts.pop()?;
+    let search = parts.pop()?;
+    Some((search, replace, flags.trim().to_string()))
+}
+
+/// Get the file updates of a sed command that edits files in place.
+///
+/// Returns the scripts of the command, whether they are extended regular expressions, and the
+/// edited files, or `None` if the command is no in-place sed command.
+fn sed_command(words: &[String]) -> Option<(Vec<String>, bool, Vec<String>)> {
+    let (command, args) = words.split_first()?;
+    if command != "sed" && command != "gsed" {
+        return None;
+    }
+
+    let mut scripts = Vec::new();
+    let mut files = Vec::new();
+    let mut in_place = false;
+    let mut extended = false;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--regexp-extended" => extended = true,
+            "--expression" => scripts.push(args.next()?.clone()),
+            arg if arg.starts_with("--in-place") => in_place = true,
+            arg if arg.starts_with("--expression=") => {
+                scripts.push(arg["--expression=".len()..].to_string())
+            }
+            arg if arg.starts_with('-') && arg.len() > 1 => {
+                for (index, flag) in arg.char_indices().skip(1) {
+                    match flag {
+                        'E' | 'r' => extended = true,
+                        // The rest of the argument is the suffix of backup files
+                        'i' => {
+                            in_place = true;
+                            break;
+                        }
+                        'e' if index == arg.len() - 1 => scripts.push(args.next()?.clone()),
+                        's' | 'u' | 'z' => {}
+                        _ => return None,
+                    }
+                }
+            }
+            // The empty suffix of BSD sed
+            "" => {}
+            arg if scripts.is_empty() && files.is_empty() => {
+                scripts.extend(arg.split(['\n', ';']).map(str::to_string))
+            }
+            arg => files.push(arg.to_string()),
+        }
+    }
+
+    (in_place && !files.is_empty()).then_some((scripts, extended, files))
+}
+
+/// Add the rules of a sed command to the files it edits.
+fn import_sed(
+    command: &str,
+    scripts: &[String],
+    extended: bool,
+    edited: &[String],
+    files: &mut Vec<(String, Vec<Rule>)>,
+    warnings: &mut Vec<String>,
+) {
+    let mut rules = Vec::new();
+    for script in scripts.iter().filter(|script| !script.trim().is_empty()) {
+        let (search, replace, flags) = match sed_substitution(script) {
+            Some(substitution) => substitution,
+            None => {
+                warnings.push(format!("sed script {:?} in {:?}", script, command));
+                continue;
+            }
+        };
+        if !flags.chars().all(|flag| flag == 'g') {
+            warnings.push(format!("sed flags {:?} in {:?}", flags, command));
+            continue;
+        }
+
+        let regex = match extended {
+            true => search.clone(),
+            false => bre_to_ere(&search),
+        };
+        match lua_pattern(&regex) {
+            Some(pattern) => rules.push(Rule {
+                search: pattern,
+                replace: release_template(&replace, sed_group, warnings),
+                options: vec![
+                    ("pattern", String::from("true")),
+                    ("lines", String::from("true")),
+                ],
+            }),
+            None => warnings.push(format!(
+                "sed expression {:?}, which has no equivalent Lua pattern",
+                search
+            )),
+        }
+    }
+    if rules.is_empty() {
+        return;
+    }
+
+    for file in edited {
+        match files.iter_mut().find(|(name, _)| name == file) {
+            Some((_, existing)) => existing.extend(rules.iter().cloned()),
+            None => files.push((file.clone(), rules.clone())),
+        }
+    }
+}
+
+/// Add the rules of the replacements of semantic-release-replace-plugin.
+///
+/// The plugin compiles `from` to a regular expression with the flags `g` and `m`, so patterns with
+/// anchors are matched against each line.
+fn import_replace_plugin(
+    options: &Value,
+    files: &mut Vec<(String, Vec<Rule>)>,
+    warnings: &mut Vec<String>,
+) {
+    let replacements = options
+        .get("replacements")
+        .map(Value::to_list)
+        .unwrap_or_default();
+    for replacement in replacements {
+        let (from, to) = match (
+            replacement.get("from").and_then(Value::as_str),
+            replacement.get("to").and_then(Value::as_str),
+        ) {
+            (Some(from), Some(to)) => (from, to),
+            _ => {
+                warnings.push(String::from(
+                    "replacement without plain `from` and `to` in semantic-release-replace-plugin",
+                ));
+                continue;
+            }
+        };
+
+        let search = match lua_pattern(from) {
+            Some(pattern) => pattern,
+            None => {
+                warnings.push(format!(
+                    "replacement of {:?}, which has no equivalent Lua pattern",
+                    from
+                ));
+                continue;
+            }
+        };
+        let replace = release_template(to, js_group, warnings);
+
+        let results = replacement
+            .get("results")
+            .map(Value::to_list)
+            .unwrap_or_default();
+        for file in replacement
+            .get("files")
+            .map(Value::to_list)
+            .unwrap_or_default()
+        {
+            let file = match file.as_str() {
+                Some(file) if !file.contains(['*', '?', '{', '[']) => file.to_string(),
+                Some(file) => {
+                    warnings.push(format!(
+                        "glob {:?} in semantic-release-replace-plugin",
+                        file
+                    ));
+                    continue;
+                }
+                None => continue,
+            };
+
+            let mut options = vec![("pattern", String::from("true"))];
+            if from.starts_with('^') || from.ends_with('$') {
+                options.push(("lines", String::from("true")));
+            }
+            let matches = results.iter().find_map(|result| {
+                match (result.get("file")?.as_str()?, result.get("numMatches")?) {
+                    (name, Value::Integer(count)) if name == file => Some(*count),
+                    _ => None,
+                }
+            });
+            if let Some(count) = matches {
+                options.push(("exactly", count.to_string()));
+            }
+
+            let rule = Rule {
+                search: search.clone(),
+                replace: replace.clone(),
+                options,
+            };
+            match files.iter_mut().find(|(name, _)| *name == file) {
+                Some((_, rules)) => rules.push(rule),
+                None => files.push((file, vec![rule])),
+            }
+        }
+    }
+}
+
+/// Find the config of semantic-release, along with the name of its file.
+fn release_config(workdir: &Path) -> Result<Option<(String, Value)>> {
+    if let Ok(json) = fs::read_to_string(workdir.join("package.json")) {
+        if let Some((package, _)) = parse_value(&json) {
+            if let Some(release @ Value::Table(_)) = package.get("release") {
+                return Ok(Some((String::from("package.json"), release.clone())));
+            }
+        }
+    }
+
+    for file in RELEASE_CONFIGS {
+        let contents = match fs::read_to_string(workdir.join(file)) {
+            Ok(contents) => contents,
+            Err(_) => continue,
+        };
+        // The config without extension can be written in JSON or YAML
+        return match parse_value(&contents) {
+            Some((config @ Value::Table(_), _))
+                if *file == ".releaserc" || file.ends_with(".json") =>
+            {
+                Ok(Some((file.to_string(), config)))
+            }
+            _ => Err(Error::ImportUnsupported {
+                file: file.to_string(),
+            }),
+        };
+    }
+
+    Ok(None)
+}
+
+/// Import the file updates of semantic-release plugins.
+///
+/// The replacements of semantic-release-replace-plugin and the in-place sed commands in the
+/// `prepareCmd` of `@semantic-release/exec` are converted, and `@semantic-release/npm` maps
+/// `package.json`.
+fn import_semantic_release(workdir: &Path) -> Result<Import> {
+    let nothing_to_import = || Error::NothingToImport {
+        tool: String::from("semantic-release"),
+    };
+    let (source, config) = release_config(workdir)?.ok_or_else(nothing_to_import)?;
+
+    let mut warnings = Vec::new();
+    let mut files = Vec::new();
+    let mut base = String::from("{}");
+
+    let plugins = config
+        .get("plugins")
+        .map(Value::to_list)
+        .unwrap_or_default();
+    for plugin in plugins {
+        let (name, options) = match plugin {
+            Value::String(name) => (name.as_str(), None),
+            Value::Array(plugin) => match plugin.first() {
+                Some(Value::String(name)) => (name.as_str(), plugin.get(1)),
+                _ => continue,
+            },
+            _ => continue,
+        };
+
+        match (name, options) {
+            (
+                "semantic-release-replace-plugin" | "@google/semantic-release-replace-plugin",
+                Some(options),
+            ) => import_replace_plugin(options, &mut files, &mut warnings),
+            ("@semantic-release/exec", Some(options)) => {
+                let script = match options.get("prepareCmd").and_then(Value::as_str) {
+                    Some(script) => script,
+                    None => continue,
+                };
+                let commands = match shell_commands(script) {
+                    Some(commands) => commands,
+                    None => {
+                        warnings.push(format!("prepareCmd {:?}", script));
+                        continue;
+                    }
+                };
+                for words in commands {
+                    let command = words.join(" ");
+                    match sed_command(&words) {
+                        Some((scripts, extended, edited)) => import_sed(
+                            &command,
+                            &scripts,
+                            extended,
+                            &edited,
+                            &mut files,
+                            &mut warnings,
+                        ),
+                        None => warnings.push(format!("command {:?} in prepareCmd", command)),
+                    }
+                }
+            }
+            ("@semantic-release/npm", options) => {
+                let root = options
+                    .and_then(|options| options.get("pkgRoot"))
+                    .and_then(Value::as_str)
+                    .map(|root| root.trim_start_matches("./").trim_end_matches('/'))
+                    .filter(|root| !root.is_empty() && *root != ".");
+                let file = match root {
+                    Some(root) => format!("{}/package.json", root),
+                    None => String::from("package.json"),
+                };
+                base = format!("{{[{}] = helpers.package_json}}", lua_string(&file));
+            }
+            ("@semantic-release/changelog", _) => warnings.push(String::from(
+                "release notes of @semantic-release/changelog, consider helpers.keep_a_changelog",
+            )),
+            _ => {}
+        }
+    }
+
+    if files.is_empty() && base == "{}" {
+        return Err(nothing_to_import());
+    }
+
+    let current_version = fs::read_to_string(workdir.join("package.json"))
+        .ok()
+        .and_then(|json| {
+            let (package, _) = parse_value(&json)?;
+            package.get("version")?.as_str().map(str::to_string)
+        })
+        .unwrap_or_default();
+    let header = format!(
+        "Imported from {} by `git bump import semantic-release`.",
+        source
+    );
+    let config = render_config(&header, &current_version, &base, &files, &warnings);
+
+    Ok(Import {
+        source,
+        config,
+        warnings,
+    })
+}
+
+#[test]
+fn import_bumpversion_config() {
+    let tmpdir = crate::state::TempDir::create().unwrap();
+    let workdir = tmpdir.path();
+    fs::write(
+        workdir.join(".bumpversion.cfg"),
+        concat!(
+            "[bumpversion]\ncurrent_version = 1.2.3\ncommit = True\n\n",
+            "[bumpversion:file:setup.py]\nsearch = version=\"{current_version}\"\n",
+            "replace = version=\"{new_version}\"\n\n",
+            "[bumpversion:file(date):setup.py]\nsearch =\n\t**Unreleased**\nreplace =\n\t**Unreleased**\n\t\n\t**{new_version}** ({now:%Y-%m-%d})\n\n",
+            "[bumpversion:file:VERSION]\n\n",
+            "[bumpversion:part:release]\nvalues = dev\n",
+        ),
+    )
+    .unwrap();
+
+    let import = import(Tool::Bumpversion, workdir).unwrap();
+    assert_eq!(import.source, ".bumpversion.cfg");
+    assert_eq!(
+        import.warnings,
+        ["option commit", "section [bumpversion:part:release]"]
+    );
+
+    let lua = mlua::Lua::new();
+    crate::engine::load_helpers(&lua).unwrap();
+    let mappings = lua.load(&import.config).eval::<mlua::Table>().unwrap();
+    let bump = |file: &str, content: &str| {
+        mappings
+            .get::<_, mlua::Function>(file)
+            .unwrap()
+            .call::<_, String>(("2.0.0", content))
+    };
+
+    assert_eq!(
+        bump(".git-bump.lua", &import.config).unwrap(),
+        import
+            .config
+            .replace("current_version = \"1.2.3\"", "current_version = \"2.0.0\"")
+    );
+    assert_eq!(
+        bump("setup.py", "**Unreleased**\n\nsetup(version=\"1.2.3\")\n").unwrap(),
+        format!(
+            "**Unreleased**\n\n**2.0.0** ({})\n\nsetup(version=\"2.0.0\")\n",
+            lua.load("os.date('%Y-%m-%d')").eval::<String>().unwrap()
+        )
+    );
+    assert_eq!(bump("VERSION", "1.2.3\n").unwrap(), "2.0.0\n");
+    assert!(bump("VERSION", "1.0.0\n").is_err());
+}
+
+#[test]
+fn import_cargo_release_config() {
+    assert_eq!(
+        lua_pattern(r"^version = \d+\.\d{2}(-[a-z0-9.]+)$").as_deref(),
+        Some("^version = %d+%.%d%d(%-[a-z0-9.]+)$")
+    );
+    assert_eq!(lua_pattern("a.*?b").as_deref(), Some("a[^\n]-b"));
+    assert_eq!(lua_pattern("(a|b)"), None);
+    assert_eq!(lua_pattern("(ab)+"), None);
+
+    let tmpdir = crate::state::TempDir::create().unwrap();
+    let workdir = tmpdir.path();
+    for (dir, name) in [("crates/a", "a"), ("crates/b", "b")] {
+        fs::create_dir_all(workdir.join(dir)).unwrap();
+        let manifest = format!("[package]\nname = \"{}\"\nversion = \"1.0.0\"\n", name);
+        fs::write(workdir.join(dir).join("Cargo.toml"), manifest).unwrap();
+    }
+    fs::write(
+        workdir.join("Cargo.toml"),
+        "[workspace]\nmembers = [\"crates/*\"]\n",
+    )
+    .unwrap();
+    fs::write(
+        workdir.join("release.toml"),
+        "[[pre-release-replacements]]\nfile = \"README.md\"\nsearch = '''\n\\{\\{crate\\}\\} [0-9.]+'''\nreplace = \"{{crate_name}} {{version}}\"\nexactly = 1\n",
+    )
+    .unwrap();
+    let manifest = concat!(
+        "[package]\nname = \"a\"\nversion = \"1.0.0\"\n\n",
+        "[package.metadata.release]\npre-release-replacements = [\n",
+        "  # changelog\n",
+        "  {file=\"CHANGELOG.md\", search=\"Unreleased\", replace=\"{{version}} - {{date}}\", prerelease = true},\n",
+        "  {file=\"src/lib.rs\", search=\"(v)\\\\d+(\\\\.\\\\d+)*|x\", replace=\"$1{{version}}\"},\n",
+        "]\n",
+    );
+    fs::write(workdir.join("crates/a/Cargo.toml"), manifest).unwrap();
+
+    let import = import(Tool::CargoRelease, workdir).unwrap();
+    assert_eq!(import.source, "crates/a/Cargo.toml, release.toml");
+    assert_eq!(import.warnings.len(), 1);
+    assert!(import.warnings[0].contains("crates/a/src/lib.rs"));
+
+    let lua = mlua::Lua::new();
+    crate::engine::load_helpers(&lua).unwrap();
+    let ctx = lua.create_table().unwrap();
+    ctx.set("worktree", workdir.to_string_lossy().as_ref())
+        .unwrap();
+    lua.globals().set("ctx", ctx).unwrap();
+    let mappings = lua.load(&import.config).eval::<mlua::Table>().unwrap();
+    let bump = |file: &str, version: &str, content: &str| {
+        mappings
+            .get::<_, mlua::Function>(file)
+            .unwrap()
+            .call::<_, String>((version, content))
+            .unwrap()
+    };
+
+    let date = lua.load("os.date('!%Y-%m-%d')").eval::<String>().unwrap();
+    assert_eq!(
+        bump("crates/a/CHANGELOG.md", "2.0.0-rc.1", "## Unreleased\n"),
+        format!("## 2.0.0-rc.1 - {}\n", date)
+    );
+    assert_eq!(
+        bump("crates/b/README.md", "2.0.0", "{{crate}} 1.0.0\n"),
+        "b 2.0.0\n"
+    );
+    assert_eq!(
+        bump("crates/b/README.md", "2.0.0-rc.1", "{{crate}} 1.0.0\n"),
+        "{{crate}} 1.0.0\n"
+    );
+    assert_eq!(
+        bump(
+            "crates/b/Cargo.toml",
+            "2.0.0",
+            "[package]\nversion = \"1.0.0\"\n"
+        ),
+        "[package]\nversion = \"2.0.0\"\n"
+    );
+}
+
+#[test]
+fn import_semantic_release_config() {
+    assert_eq!(bre_to_ere(r"\(v\)[0-9]\{1,\}+(x)"), r"(v)[0-9]{1,}\+\(x\)");
+    assert_eq!(
+        shell_commands("sed -i 's/a b/c/' \"x y\" && echo \\; done\nls | wc -l"),
+        Some(vec![
+            vec![
+                String::from("sed"),
+                String::from("-i"),
+                String::from("s/a b/c/"),
+                String::from("x y")
+            ],
+            vec![
+                String::from("echo"),
+                String::from(";"),
+                String::from("done")
+            ],
+            vec![String::from("ls")],
+            vec![String::from("wc"), String::from("-l")],
+        ])
+    );
+    assert_eq!(
+        sed_substitution(r"s|a\|b|&\1|g"),
+        Some((String::from("a|b"), String::from(r"&\1"), String::from("g")))
+    );
+
+    let tmpdir = crate::state::TempDir::create().unwrap();
+    let workdir = tmpdir.path();
+    fs::write(
+        workdir.join("package.json"),
+        "{\"name\": \"x\", \"version\": \"1.0.0\"}\n",
+    )
+    .unwrap();
+    fs::write(
+        workdir.join(".releaserc.json"),
+        r#"{
+  "branches": ["main"],
+  "plugins": [
+    "@semantic-release/commit-analyzer",
+    "@semantic-release/changelog",
+    ["@semantic-release/npm", {"npmPublish": false}],
+    ["@semantic-release/exec", {
+      "prepareCmd": "sed -i -e 's/^VERSION = .*/VERSION = \"${nextRelease.version}\"/' Makefile && make dist"
+    }],
+    ["@google/semantic-release-replace-plugin", {
+      "replacements": [{
+        "files": ["src/version.py", "docs/*.md"],
+        "from": "__version__ = \".*?\"",
+        "to": "__version__ = \"${nextRelease.version}\" # $& ${nextRelease.gitTag}",
+        "results": [{"file": "src/version.py", "hasChanged": true, "numMatches": 1, "numReplacements": 1}]
+      }]
+    }]
+  ]
+}
+"#,
+    )
+    .unwrap();
+
+    let imported = import(Tool::SemanticRelease, workdir).unwrap();
+    assert_eq!(imported.source, ".releaserc.json");
+    assert_eq!(imported.warnings.len(), 4);
+    assert!(imported
+        .config
+        .contains("local current_version = \"1.0.0\""));
+    assert!(imported.config.contains("exactly = 1"));
+
+    let lua = mlua::Lua::new();
+    crate::engine::load_helpers(&lua).unwrap();
+    let mappings = lua.load(&imported.config).eval::<mlua::Table>().unwrap();
+    let bump = |file: &str, content: &str| {
+        mappings
+            .get::<_, mlua::Function>(file)
+            .unwrap()
+            .call::<_, String>(("2.0.0", content))
+            .unwrap()
+    };
+
+    assert_eq!(
+        bump("Makefile", "NAME = x\nVERSION = 1.0.0\n"),
+        "NAME = x\nVERSION = \"2.0.0\"\n"
+    );
+    assert_eq!(
+        bump("src/version.py", "__version__ = \"1.0.0\"\n"),
+        "__version__ = \"2.0.0\" # __version__ = \"1.0.0\" ${nextRelease.gitTag}\n"
+    );
+    assert_eq!(
+        bump("package.json", "{\"version\": \"1.0.0\"}"),
+        "{\"version\": \"2.0.0\"}"
+    );
+
+    fs::remove_file(workdir.join(".releaserc.json")).unwrap();
+    fs::write(workdir.join(".releaserc.yml"), "plugins: []\n").unwrap();
+    assert!(matches!(
+        import(Tool::SemanticRelease, workdir),
+        Err(Error::ImportUnsupported { .. })
+    ));
+}
@@ -0,0 +1,366 @@
+use std::fs;
+use std::path::Path;
+
+use crate::project::{cargo_workspace_members, npm_workspace_members, toml_section};
+
+/// Directories that are not searched for Go files.
+const SKIPPED_DIRS: &[&str] = &["node_modules", "target", "testdata", "vendor"];
+
+/// Maximum depth of directories that are searched for Go files.
+const MAX_DEPTH: usize = 4;
+
+/// Recipe for a detected file, as part of a generated config.
+struct Recipe {
+    /// Explanation of the recipe, written as comment above it.
+    comment: String,
+    /// Lua statements adding the recipe to the `mappings` table.
+    code: String,
+}
+
+impl Recipe {
+    /// Recipe mapping a single file to the given function.
+    fn mapping(comment: &str, file: &str, function: &str) -> Self {
+        Recipe {
+            comment: comment.to_string(),
+            code: format!("mappings[\"{}\"] = {}", file, function),
+        }
+    }
+
+    /// Recipe adding all mappings of a workspace helper.
+    fn workspace(comment: String, helper: &str, lockfile: bool) -> Self {
+        let options = match lockfile {
+            true => "{dependencies = true, lockfile = true}",
+            false => "{dependencies = true}",
+        };
+
+        Recipe {
+            comment,
+            code: format!(
+                "for file, mapping in pairs(helpers.{} {}) do\n    mappings[file] = mapping\nend",
+                helper, options
+            ),
+        }
+    }
+}
+
+/// Get the names of the files a config was generated for, along with the config itself.
+///
+/// The worktree is searched for known manifests, changelogs, and Go files with version constants,
+/// and each detected file is mapped to a suitable bundled helper. Returns `None` if no such files
+/// were found.
+pub(crate) fn detect_config(workdir: &Path) -> Option<(Vec<String>, String)> {
+    let mut files = Vec::new();
+    let mut recipes = Vec::new();
+    let mut add = |file: &str, recipe| {
+        files.push(file.to_string());
+        recipes.push(recipe);
+    };
+
+    if let Some(recipe) = detect_cargo(workdir) {
+        add("Cargo.toml", recipe);
+    }
+    if let Some(recipe) = detect_npm(workdir) {
+        add("package.json", recipe);
+    }
+
+    if let Some(pyproject) = read(workdir, "pyproject.toml") {
+        if has_section_version(&pyproject, "project")
+            || has_section_version(&pyproject, "tool.poetry")
+        {
+            let function = match workdir.join("poetry.lock").is_file() {
+                true => "function(version, content)\n    return helpers.pyproject_toml(version, content), {lockfile = \"poetry\"}\nend",
+                false => "helpers.pyproject_toml",
+            };
+            add(
+                "pyproject.toml",
+                Recipe::mapping(
+                    "Python project: version in the [project] or [tool.poetry] section",
+                    "pyproject.toml",
+                    function,
+                ),
+            );
+        }
+    }
+
+    if let Some(setup_cfg) = read(workdir, "setup.cfg") {
+        // Versions read from attributes or files are not maintained in setup.cfg itself
+        let literal = toml_section(&setup_cfg, "metadata").lines().any(|line| {
+            matches!(line.split_once('='), Some((key, value))
+                if key.trim() == "version" && !value.trim().starts_with("attr:")
+                    && !value.trim().starts_with("file:"))
+        });
+        if literal {
+            add(
+                "setup.cfg",
+                Recipe::mapping(
+                    "Python package: version in the [metadata] section",
+                    "setup.cfg",
+                    "helpers.setup_cfg",
+                ),
+            );
+        }
+    }
+
+    let mut go_files = Vec::new();
+    find_go_versions(workdir, "", 0, &mut go_files);
+    for file in go_files {
+        add(
+            &file,
+            Recipe::mapping(
+                "Go version constant: first constant or variable ending in \"Version\"",
+                &file,
+                "helpers.go_version",
+            ),
+        );
+    }
+
+    if workdir.join("Chart.yaml").is_file() {
+        add(
+            "Chart.yaml",
+            Recipe::mapping(
+                "Helm chart: \"version\" and \"appVersion\" keys, either can be switched off with false",
+                "Chart.yaml",
+                "helpers.helm_chart {version = true, app_version = true}",
+            ),
+        );
+    }
+
+    if workdir.join("debian/changelog").is_file() {
+        add(
+            "debian/changelog",
+            Recipe::mapping(
+                "Debian package: new changelog entry, with the maintainer from DEBFULLNAME and DEBEMAIL",
+                "debian/changelog",
+                "helpers.debian_changelog()",
+            ),
+        );
+    }
+
+    let mut spec_files = fs::read_dir(workdir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .filter(|name| name.ends_with(".spec"))
+        .collect::<Vec<_>>();
+    spec_files.sort();
+    for file in spec_files {
+        add(
+            &file,
+            Recipe::mapping(
+                "RPM spec file: \"Version\" tag and a new changelog entry",
+                &file,
+                "helpers.rpm_spec()",
+            ),
+        );
+    }
+
+    if workdir.join("CHANGELOG.md").is_file() {
+        add(
+            "CHANGELOG.md",
+            Recipe::mapping(
+                "Changelog: new heading for the version, in the Keep a Changelog format",
+                "CHANGELOG.md",
+                "helpers.keep_a_changelog",
+            ),
+        );
+    }
+    if workdir.join("VERSION").is_file() {
+        add(
+            "VERSION",
+            Recipe::mapping(
+                "Plain version file",
+                "VERSION",
+                "helpers.plain_version_file",
+            ),
+        );
+    }
+
+    if recipes.is_empty() {
+        return None;
+    }
+
+    let recipes = recipes
+        .iter()
+        .map(|recipe| format!("-- {}\n{}\n", recipe.comment, recipe.code))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let config = format!(
+        "-- Generated by `git bump init --detect`, adjust to your needs. See the\n-- bundled helpers in the documentation for the details of each recipe.\n\nlocal mappings = {{}}\n\n{}\nreturn mappings\n",
+        recipes
+    );
+
+    Some((files, config))
+}
+
+/// Detect a Rust package or Cargo workspace.
+fn detect_cargo(workdir: &Path) -> Option<Recipe> {
+    let manifest = read(workdir, "Cargo.toml")?;
+    let lockfile = workdir.join("Cargo.lock").is_file();
+
+    if manifest.lines().any(|line| line.trim() == "[workspace]") {
+        let members = cargo_workspace_members(workdir)
+            .into_iter()
+            .map(|(_, path)| path)
+            .filter(|path| path != ".")
+            .collect::<Vec<_>>();
+        let comment = format!(
+            "Cargo workspace with the members {}:\n-- versions of all manifests and of the dependencies between them",
+            members.join(", ")
+        );
+        return Some(Recipe::workspace(comment, "cargo_workspace", lockfile));
+    }
+
+    let function = match lockfile {
+        true => "function(version, content)\n    return helpers.cargo_toml(version, content), {lockfile = \"cargo\"}\nend",
+        false => "helpers.cargo_toml",
+    };
+    Some(Recipe::mapping(
+        "Rust package: version in the [package] section",
+        "Cargo.toml",
+        function,
+    ))
+}
+
+/// Detect a Node.js package or npm, Yarn, or pnpm workspace.
+fn detect_npm(workdir: &Path) -> Option<Recipe> {
+    read(workdir, "package.json")?;
+    let lockfile = workdir.join("package-lock.json").is_file();
+
+    let members = npm_workspace_members(workdir)
+        .into_iter()
+        .map(|(_, path)| path)
+        .filter(|path| path != ".")
+        .collect::<Vec<_>>();
+    if !members.is_empty() {
+        let comment = format!(
+            "Node.js workspace with the members {}:\n-- versions of all package.json files and of the dependencies between them",
+            members.join(", ")
+        );
+        return Some(Recipe::workspace(comment, "npm_workspace", lockfile));
+    }
+
+    let function = match lockfile {
+        true => "function(version, content)\n    return helpers.package_json(version, content), {lockfile = \"npm\"}\nend",
+        false => "helpers.package_json",
+    };
+    Some(Recipe::mapping(
+        "Node.js package: top-level \"version\" member",
+        "package.json",
+        function,
+    ))
+}
+
+/// Read a file in the worktree, if it exists.
+fn read(workdir: &Path, file: &str) -> Option<String> {
+    fs::read_to_string(workdir.join(file)).ok()
+}
+
+/// Check if a section of a TOML file has a version.
+fn has_section_version(contents: &str, name: &str) -> bool {
+    toml_section(contents, name)
+        .lines()
+        .any(|line| matches!(line.split_once('='), Some((key, _)) if key.trim() == "version"))
+}
+
+/// Check if Go source defines a version constant or variable, as updated by `helpers.go_version`.
+fn has_go_version(source: &str) -> bool {
+    source.lines().any(|line| {
+        let line = line.trim();
+        let line = ["const ", "var "]
+            .iter()
+            .find_map(|keyword| line.strip_prefix(keyword))
+            .unwrap_or(line);
+
+        match line.split_once('=') {
+            Some((name, value)) => {
+                let name = name.trim();
+                (name.ends_with("Version") || name.ends_with("version"))
+                    && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+                    && value.trim().starts_with('"')
+            }
+            None => false,
+        }
+    })
+}
+
+/// Find Go files with version constants, relative to the worktree.
+///
+/// Hidden directories, dependencies, and build outputs are skipped, as well as test files.
+fn find_go_versions(workdir: &Path, dir: &str, depth: usize, files: &mut Vec<String>) {
+    let mut entries = match fs::read_dir(workdir.join(dir)) {
+        Ok(entries) => entries
+            .flatten()
+            .filter_map(|entry| Some((entry.file_name().into_string().ok()?, entry.path())))
+            .collect::<Vec<_>>(),
+        Err(_) => return,
+    };
+    entries.sort();
+
+    for (name, path) in entries {
+        let relative = match dir {
+            "" => name.clone(),
+            dir => format!("{}/{}", dir, name),
+        };
+
+        if path.is_dir() {
+            if depth < MAX_DEPTH && !name.starts_with('.') && !SKIPPED_DIRS.contains(&name.as_str())
+            {
+                find_go_versions(workdir, &relative, depth + 1, files);
+            }
+        } else if name.ends_with(".go") && !name.ends_with("_test.go") {
+            if let Ok(source) = fs::read_to_string(&path) {
+                if has_go_version(&source) {
+                    files.push(relative);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn detect_project_files() {
+    let tmpdir = crate::state::TempDir::create().unwrap();
+    let workdir = tmpdir.path();
+    fs::create_dir_all(workdir.join("cmd/tool")).unwrap();
+    fs::create_dir_all(workdir.join("vendor/lib")).unwrap();
+    fs::write(
+        workdir.join("Cargo.toml"),
+        "[package]\nname = \"x\"\nversion = \"1.0.0\"\n",
+    )
+    .unwrap();
+    fs::write(workdir.join("Cargo.lock"), "").unwrap();
+    fs::write(
+        workdir.join("setup.cfg"),
+        "[metadata]\nversion = attr: x.__version__\n",
+    )
+    .unwrap();
+    fs::write(
+        workdir.join("cmd/tool/main.go"),
+        "package main\n\nconst AppVersion = \"1.0.0\"\n",
+    )
+    .unwrap();
+    fs::write(
+        workdir.join("vendor/lib/lib.go"),
+        "package lib\n\nvar Version = \"2.0.0\"\n",
+    )
+    .unwrap();
+    fs::write(workdir.join("Chart.yaml"), "version: 1.0.0\n").unwrap();
+    fs::create_dir(workdir.join("debian")).unwrap();
+    fs::write(workdir.join("debian/changelog"), "").unwrap();
+    fs::write(workdir.join("x.spec"), "Version: 1.0.0\n").unwrap();
+
+    let (files, config) = detect_config(workdir).unwrap();
+    assert_eq!(
+        files,
+        [
+            "Cargo.toml",
+            "cmd/tool/main.go",
+            "Chart.yaml",
+            "debian/changelog",
+            "x.spec"
+        ]
+    );
+    assert!(config.contains("{lockfile = \"cargo\"}"));
+    assert!(config.contains("mappings[\"cmd/tool/main.go\"] = helpers.go_version\n"));
+    mlua::Lua::new().load(&config).into_function().unwrap();
+}
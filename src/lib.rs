@@ -0,0 +1,3649 @@
+//! # git-bump
+//!
+//! [![badge github]][url github]
+//! [![badge crates.io]][url crates.io]
+//! [![badge docs.rs]][url docs.rs]
+//! [![badge license]][url license]
+//!
+//! [badge github]: https://img.shields.io/badge/github-FloGa%2Fgit--bump-green
+//! [badge crates.io]: https://img.shields.io/crates/v/git-bump
+//! [badge docs.rs]: https://img.shields.io/docsrs/git-bump
+//! [badge license]: https://img.shields.io/crates/l/git-bump
+//!
+//! [url github]: https://github.com/FloGa/git-bump
+//! [url crates.io]: https://crates.io/crates/git-bump
+//! [url docs.rs]: https://docs.rs/git-bump
+//! [url license]: https://github.com/FloGa/git-bump/blob/develop/LICENSE
+//!
+//! Consistently bump your version numbers with Lua scripts.
+//!
+//! ## Motivation
+//!
+//! When publishing a new software release, there are usually a couple of places
+//! where you want to update the current version number:
+//!
+//! -   configuration files like `Cargo.toml` and `package.json`
+//! -   source files with defined constants for your application
+//! -   a conventional `VERSION` file in your repository root
+//! -   your changelog
+//! -   maybe a lot of other places, depending on you specific needs and workflow
+//!
+//! Also, depending on your workflow, you might want to first bump your version to
+//! something like `1.2.3-RC`, then after some final testing `1.2.3` and
+//! eventually to a development version `1.3.0-SNAPSHOT`.
+//!
+//! Since these tasks can be nicely automated, you might want to have a small
+//! script that does the bumping for you. I even ended up with a `bump.sh` in each
+//! of my projects, which are all quite similar, especially the ones for the same
+//! programming language. To avoid this kind of boilerplate code in every single
+//! repository, I came up with `git-bump` which is configurable via Lua scripts.
+//!
+//! `git-bump` searches for configuration files in certain
+//! [locations](#configuration-file-locations), aggregates them, and calls a
+//! custom Lua function for every defined file. This way it is possible to define
+//! global version bump functions that can be used in each repository.
+//!
+//! ## Installation
+//!
+//! `git-bump` can be installed easily through Cargo via `crates.io`:
+//!
+//! ```shell script
+//! cargo install --locked git-bump
+//! ```
+//!
+//! Please note that the `--locked` flag is necessary here to have the exact same
+//! dependencies as when the application was tagged and tested. Without it, you
+//! might get more up-to-date versions of dependencies, but you have the risk of
+//! undefined and unexpected behavior if the dependencies changed some
+//! functionalities. The application might even fail to build if the public API of
+//! a dependency changed too much.
+//!
+//! By default, the config files are run by a bundled Lua 5.4. If your helper
+//! libraries target another Lua version, build `git-bump` with a matching runtime
+//! instead, by replacing the default feature with one of `lua53`, `lua52`,
+//! `lua51`, or `luajit`:
+//!
+//! ```shell script
+//! cargo install --locked git-bump --no-default-features --features luajit
+//! ```
+//!
+//! Config files that need to work with several runtimes can check the global
+//! `_VERSION`, like `Lua 5.1`, and the global `jit` table, which only exists with
+//! LuaJIT.
+//!
+//! Alternatively, pre-built binaries can be downloaded from the [GitHub
+//! releases][gh-releases] page.
+//!
+//! [gh-releases]: https://github.com/FloGa/git-bump/releases
+//!
+//! ## Usage
+//!
+//! ```text
+//! Usage: git-bump [OPTIONS] <NEW_VERSION|--from-describe|--major|--minor|--patch|--list-files|--print-sample-config [<ECOSYSTEM>]> [NEXT_VERSION]...
+//!        git-bump <COMMAND>
+//!
+//! Commands:
+//!   hook-check   Check that files match a version, for use with the pre-commit framework
+//!   init         Create .git-bump.lua in the root of the worktree, with the sample config by default
+//!   import       Create .git-bump.lua in the root of the worktree from the config of another tool
+//!   audit        Show what the config files would execute or access outside of the worktree
+//!   lint         Check config files for mistakes and questionable constructs
+//!   status       Show the last bump and whether its files have drifted from its version since
+//!   doctor       Check the repository, config files, and commit setup for problems
+//!   verify       Check that all mapped files already match a version byte for byte
+//!   replace      Replace the previous version in all tracked text files, after confirmation
+//!   notes        Print release notes of a version, extracted from the changelog
+//!   completions  Print shell completions
+//!   help         Print this message or the help of the given subcommand(s)
+//!
+//! Arguments:
+//!   [NEW_VERSION]
+//!           Version to set
+//!
+//!   [NEXT_VERSION]...
+//!           Further versions to set afterwards, each in a bump of its own, like `1.3.0-SNAPSHOT`
+//!
+//! Options:
+//!       --from-describe
+//!           Use version derived from the nearest tag, as given by `git describe --tags`
+//!
+//!       --major
+//!           Increment major version of the nearest tag
+//!
+//!       --minor
+//!           Increment minor version of the nearest tag
+//!
+//!       --patch
+//!           Increment patch version of the nearest tag
+//!
+//!       --pre <IDENTIFIERS>
+//!           Add pre-release identifiers to the incremented version, like `rc.1`
+//!
+//!       --build <METADATA>
+//!           Add build metadata to the incremented version, like a commit hash
+//!
+//!       --list-files
+//!           List files that would be updated
+//!
+//!       --long
+//!           Also show descriptions and config files of listed files
+//!
+//!       --print-sample-config [<ECOSYSTEM>]
+//!           Print sample config file, only with the recipes of an ecosystem if given
+//!
+//!           Possible values:
+//!           - rust:   Cargo packages and workspaces
+//!           - node:   npm, Yarn, and pnpm packages and workspaces
+//!           - python: Python packages, built with setuptools, Poetry, or any other PEP 621 backend
+//!           - java:   Maven and Gradle projects
+//!
+//!       --diff
+//!           Show changes instead of writing files, without running any hooks
+//!
+//!       --stdout
+//!           Print the new contents of the files with a header each instead of writing them, without running any hooks
+//!
+//!       --diff-mode <MODE>
+//!           How to show changes
+//!
+//!           Possible values:
+//!           - unified:      Classic unified diff
+//!           - side-by-side: Old and new lines in two columns
+//!           - word:         Changed words inline, like `git diff --word-diff`
+//!
+//!       --no-pager
+//!           Do not pipe shown changes through a pager
+//!
+//!       --edit[=<FILE>]
+//!           Open written files in an editor before running post hooks, optionally only the given one
+//!
+//!       --notes
+//!           Ask for release notes in an editor and pass them to the config files as `ctx.notes`
+//!
+//!       --branch <TEMPLATE>
+//!           Create and switch to a branch before bumping, like `release/{version}`
+//!
+//!       --no-switch
+//!           Do not switch to the created branch, so the bump stays on the current branch
+//!
+//!       --require-branch <BRANCH>
+//!           Refuse to bump unless the branch is checked out [default: bump.requireBranch from Git config]
+//!
+//!       --commit
+//!           Commit the bumped files
+//!
+//!       --commit-each
+//!           Bump to every given version in turn, committing after each bump; only the first is tagged
+//!
+//!       --amend
+//!           Amend the previous commit with the bumped files instead of creating a new one
+//!
+//!       --identity <NAME <EMAIL>>
+//!           Commit and tag as this identity [default: bump.identity or user.name and user.email from Git config]
+//!
+//!       --commit-template <FILE>
+//!           Build the commit message from a template with {version}, {notes}, and {files} placeholders
+//!
+//!       --signoff
+//!           Add a Signed-off-by trailer of the committer to the commit message
+//!
+//!       --trailer <KEY=VALUE>
+//!           Add a trailer to the commit message, can be given multiple times
+//!
+//!       --tag
+//!           Tag the bump commit, refusing to bump if the tag already exists
+//!
+//!       --force-tag
+//!           Move the tag if it already exists
+//!
+//!       --check-remote-tags[=<REMOTE>]
+//!           Refuse to bump if the tag already exists on the remote, `origin` by default
+//!
+//!       --push
+//!           Push the bump commit and the tag to `origin`
+//!
+//!       --json
+//!           Print a report of the bump as JSON
+//!
+//!       --timings
+//!           Print durations and written bytes of all files, to find slow mappings
+//!
+//!       --allow-empty
+//!           Succeed without doing anything if no config file or no file to bump is found
+//!
+//!       --group <GROUP>
+//!           Only process files of the given group, can be given multiple times
+//!
+//!       --since[=<REF>]
+//!           Only bump files changed since the given revision, or since the nearest tag
+//!
+//!       --tracked-only
+//!           Skip files that are not tracked by Git, like build artifacts
+//!
+//!       --materialize
+//!           Check out mapped files that are excluded by sparse-checkout, instead of skipping them
+//!
+//!       --force-writable
+//!           Write read-only files anyway, making them read-only again afterwards
+//!
+//!   -j, --jobs <N>
+//!           Bump files on N threads in parallel, each evaluating the config files on its own
+//!
+//!       --index-only
+//!           Write bumped files only into the index, leaving the worktree untouched
+//!
+//!       --no-override
+//!           Fail if a config file overrides a mapping of a previous config file
+//!
+//!       --no-user-config
+//!           Ignore the per-user config file [default on CI, see GIT_BUMP_NO_GLOBAL]
+//!
+//!       --no-local-config
+//!           Ignore the per-repository config file in $GIT_DIR
+//!
+//!       --no-shared-config
+//!           Ignore the per-repository config file in the worktree
+//!
+//!       --outer-config
+//!           Also use the config files of all repositories the current one is nested in
+//!
+//!       --no-canonicalize
+//!           Keep the paths of config files as found instead of resolving symlinks, like for dotfiles
+//!
+//!       --absolute-paths
+//!           Show full paths in messages instead of relative ones
+//!
+//!       --allow-outside-worktree
+//!           Allow mappings for files outside of the worktree, like absolute paths
+//!
+//!       --trace
+//!           Show the Lua stack traceback of errors in config files
+//!
+//!   -h, --help
+//!           Print help (see a summary with '-h')
+//!
+//!   -V, --version
+//!           Print version
+//! ```
+//!
+//! The following examples require that `git-bump` is accessible from your current
+//! shell, so ensure that the executable lies in a directory that is part of your
+//! `$PATH` (Linux and MacOS) or `%PATH%` (Windows) variable.
+//!
+//! To bump your versions to `1.2.3`, it is as simple as:
+//!
+//! ```shell script
+//! git-bump 1.2.3
+//! ```
+//!
+//! Or, with Git subcommand syntax:
+//!
+//! ```shell script
+//! git bump 1.2.3
+//! ```
+//!
+//! Well, maybe not quite that easy. If you do not have any configuration files
+//! yet, nothing will happen.
+//!
+//! For a first success, let's start with a very simple configuration file in the
+//! root of your Git repository. Name it `.git-bump.lua` (the leading `.` denotes
+//! a hidden file in Linux and is quite usual for such configuration files) with
+//! the following contents:
+//!
+//! ```lua
+//! return {
+//!     VERSION = function(version)
+//!         return version
+//!     end,
+//! }
+//! ```
+//!
+//! The configuration files are expected to return a Lua table. The keys are the
+//! file names you want to run the bumper on, relative to the Git repository root,
+//! with either forward slashes or backslashes as separators. The value is a Lua
+//! function, taking two parameters: The version that was given as argument to
+//! `git-bump` and the contents of the file for conveniently altering. If you do
+//! not need the current file content, you can ignore the second parameter, Lua
+//! does not care about extraneous parameters. The functions need to return the
+//! new contents of the file, which will then be written into the according files.
+//!
+//! In this example, the file `VERSION` will only contain the given version string.
+//!
+//! More complex examples can be found in the section [Sample
+//! Functions](#sample-functions).
+//!
+//! Before any bumping function runs, `git-bump` checks that all files to be
+//! bumped can actually be written. If any of them cannot, for example because of
+//! missing permissions or a read-only file system, all of them are reported at
+//! once and nothing is changed, so that the repository is never left half-bumped.
+//! Optional files (see [Mapping Options](#mapping-options)) are not checked,
+//! since they are skipped on failure anyway.
+//!
+//! Each blocked file is reported along with the reason, like a read-only file or,
+//! on Windows, a file locked by another program that has to be closed first.
+//! Read-only files, as checked out by some version control bridges or made so by
+//! hand, can be bumped anyway with `--force-writable`, which makes them writable
+//! for their owner while bumping them and read-only again afterwards:
+//!
+//! ```text
+//! $ git bump 1.2.3
+//! VERSION: Not writable: the file is read-only, make it writable or use --force-writable
+//! Error: 1 file(s) cannot be written, nothing was changed
+//! $ git bump --force-writable 1.2.3
+//! ```
+//!
+//! IDEs and virus scanners on Windows often lock files for a moment while they
+//! look at them. Instead of failing right away, checking and writing a locked
+//! file is retried up to 5 times, with a delay of 100 milliseconds before the
+//! first retry that doubles with every further one. Each retry is reported as a
+//! warning. Before writing anything, all locked files are retried together, so
+//! they do not add up their delays. Set `bump.writeRetries` in the Git config to
+//! change the number of retries, up to 10, or to `0` to fail right away:
+//!
+//! ```shell script
+//! git config bump.writeRetries 8
+//! ```
+//!
+//! Right before writing a file, `git-bump` also checks that it still has the
+//! contents the bumping function was given. If a pre hook, an editor, or any
+//! other process changed the file in the meantime, bumping the file fails instead
+//! of silently discarding these changes.
+//!
+//! Since such configurations could be shared across multiple, different
+//! repositories, `git-bump` will not create new files, but only operate on
+//! existing files. So, for this example, create `VERSION` and run the bumper
+//! again:
+//!
+//! ```text
+//! $ touch VERSION
+//! $ git bump 1.2.3
+//! $ cat VERSION
+//! 1.2.3
+//! ```
+//!
+//! For nightly or development builds, the version can also be derived from the
+//! nearest tag, just like `git describe --tags` does it:
+//!
+//! ```shell script
+//! git bump --from-describe
+//! ```
+//!
+//! A leading `v` of the tag name is removed, so with a tag `v1.2.3`, the version is
+//! `1.2.3` on the tagged commit itself and something like `1.2.3-5-gabcdef1` five
+//! commits later.
+//!
+//! Versions are normalized the same way everywhere, so `git bump v1.2.3` and `git
+//! bump 1.2.3` are equivalent, and the config files always get the version in the
+//! same canonical form. The prefixes are configured via Git config:
+//! `bump.tagPrefix` is the prefix of release tags, `v` by default, and
+//! `bump.versionPrefix` is the prefix of the version given to the config files,
+//! empty by default. A leading `v` is always accepted, and prefixes are only
+//! removed if they are followed by a digit. For example, if tags are named like
+//! `release-1.2.3`, but the config files should get `v1.2.3`:
+//!
+//! ```shell script
+//! git config bump.tagPrefix release-
+//! git config bump.versionPrefix v
+//! ```
+//!
+//! To compute the next version entirely via flags, for example on CI, increment
+//! the version of the nearest tag with `--major`, `--minor`, or `--patch`, and
+//! optionally add pre-release identifiers and build metadata:
+//!
+//! ```shell script
+//! git bump --minor --pre rc.1
+//! git bump --patch --build "$(git rev-parse --short HEAD)"
+//! ```
+//!
+//! With a tag `v1.2.3`, this results in `1.3.0-rc.1` and `1.2.4+abcdef1`,
+//! respectively. If the nearest tag is a pre-release, it is simply released if the
+//! increment allows it, so `--minor` turns `1.3.0-rc.1` into `1.3.0` and
+//! `--patch` turns `1.2.4-rc.1` into `1.2.4`.
+//!
+//! To commit the bumped files along with any refreshed lockfiles, use `--commit`.
+//! Only these files end up in the bump commit, so any other changes that are
+//! already staged stay staged for a later commit. For git-flow style workflows,
+//! `--branch` creates a release branch from the current commit and switches to it
+//! before bumping, replacing `{version}` in the given template:
+//!
+//! ```shell script
+//! git bump --minor --branch 'release/{version}' --commit
+//! ```
+//!
+//! With `--no-switch`, the branch is only created, so the bumped files and the
+//! bump commit stay on the current branch instead.
+//!
+//! To make sure releases are only bumped on the right branch, set
+//! `bump.requireBranch` in the Git config, or pass `--require-branch`, which takes
+//! precedence. Bumping on any other branch or on a detached `HEAD` is refused,
+//! while previews with `--diff` are always allowed. An empty name, like
+//! `--require-branch=`, allows any branch again:
+//!
+//! ```shell script
+//! git config bump.requireBranch main
+//! ```
+//!
+//! If the version bump was forgotten in the release preparation commit,
+//! `--commit --amend` folds the bumped files into the previous commit instead,
+//! keeping its author and message. Just like with Git itself, commits are signed if
+//! `commit.gpgSign` is set, using `gpg.format`, `gpg.program`, and
+//! `user.signingKey`. To sign only bump commits, or to not sign them even though
+//! other commits are, set `bump.sign`, which takes precedence over
+//! `commit.gpgSign`. With `gpg.format = ssh`, commits are signed with
+//! `ssh-keygen -Y sign`, and `user.signingKey` must be set, either to the path of a
+//! key, or to a public key prefixed with `key::`, whose private key is taken from
+//! the SSH agent. A signing config that cannot work fails before any file is
+//! written.
+//!
+//! The bump commit has the message `Bump version to 1.2.3` by default. To follow
+//! the Conventional Commits format, as enforced by tools like commitlint,
+//! configure a type and optionally a scope, which are validated before anything
+//! is bumped:
+//!
+//! ```shell script
+//! git config bump.commitType chore
+//! git config bump.commitScope release
+//! ```
+//!
+//! This results in messages like `chore(release): bump version to 1.2.3`.
+//!
+//! For longer, structured messages, `--commit-template` takes a file whose
+//! contents become the commit message instead. In there, `{version}` is replaced
+//! with the new version, `{notes}` with the release notes entered with `--notes`,
+//! and `{files}` with the committed files, one per line. Consecutive empty lines,
+//! as left behind by empty notes, are collapsed into one:
+//!
+//! ```text
+//! Release {version}
+//!
+//! {notes}
+//!
+//! Updated files:
+//! {files}
+//! ```
+//!
+//! Projects that require a Developer Certificate of Origin can add a
+//! `Signed-off-by` trailer of the committer with `--signoff`, just like with
+//! `git commit`. Further trailers are added with `--trailer`, given as
+//! `key=value` or `key: value`, which can be repeated. When amending, the
+//! trailers are added to the message of the previous commit, unless they are
+//! already there:
+//!
+//! ```shell script
+//! git bump --patch --commit --signoff --trailer "Ticket=REL-42"
+//! ```
+//!
+//! Bump commits and tags are created with the same identity Git would use, so
+//! `GIT_AUTHOR_NAME`, `GIT_AUTHOR_EMAIL`, `GIT_COMMITTER_NAME`, and
+//! `GIT_COMMITTER_EMAIL` take precedence over `user.name` and `user.email` from
+//! the Git config. To commit as a dedicated identity, like a bot on CI, without
+//! touching the identity used for other commits, set `bump.identity` in the Git
+//! config, or pass `--identity`, which also overrides the environment:
+//!
+//! ```shell script
+//! git bump --patch --commit --tag --identity "Release Bot <bot@example.com>"
+//! ```
+//!
+//! On CI, where the checkout may be shared with later steps, `--index-only` reads
+//! the files from the index and writes the bumped contents only back into the
+//! index, leaving the worktree untouched. Together with `--commit`, this creates
+//! the bump commit without ever dirtying the checkout. Since there is nothing on
+//! disk to work with, lockfiles are not refreshed and `--edit` is not available
+//! in this mode.
+//!
+//! ```shell script
+//! git bump --patch --index-only --commit
+//! ```
+//!
+//! To tag the bump commit as well, add `--tag`. The tag is named after the
+//! version with the configured tag prefix, so `v1.2.3` by default. To prevent
+//! accidental double releases, nothing is bumped if the tag already exists. If
+//! this is really intended, for example to redo a botched release, `--force-tag`
+//! moves the existing tag to the new bump commit instead.
+//!
+//! ```shell script
+//! git bump --minor --commit --tag
+//! ```
+//!
+//! Many projects reopen development right after a release, like Maven projects
+//! with their `-SNAPSHOT` versions. With `--commit-each`, several versions can be
+//! given, and the files are bumped to each of them in turn, with a commit after
+//! every bump. Only the first version is tagged and released, while all other
+//! options apply to every bump:
+//!
+//! ```shell script
+//! git bump 1.2.3 1.3.0-SNAPSHOT --commit-each --tag
+//! ```
+//!
+//! The local tags may be outdated, though, if someone else already released the
+//! same version. To catch this, `--check-remote-tags` asks the remote, `origin`
+//! unless given like `--check-remote-tags=upstream`, and refuses to bump if the
+//! tag already exists there. This works independently of `--tag` and uses `git
+//! ls-remote`, so the remote is reached just like with any other Git command, and
+//! nothing is fetched.
+//!
+//! With `--push`, the bump commit and the tag, if any, are pushed to `origin`
+//! afterwards, again via `git push`. Going one step further, `--create-release`
+//! then creates a release for the tag on GitHub or GitLab, as detected from the
+//! URL of `origin`, with the section of the version in the changelog as release
+//! notes (see [Release Notes](#release-notes)). The API token is taken from the
+//! environment variable `GITHUB_TOKEN` or `GH_TOKEN` for GitHub, and
+//! `GITLAB_TOKEN` for GitLab. Both the forge and the token are checked before
+//! bumping anything.
+//!
+//! ```shell script
+//! GITHUB_TOKEN=... git bump --minor --commit --tag --push --create-release
+//! ```
+//!
+//! Since this pulls in an HTTP client, it is only available with the cargo
+//! feature `release`:
+//!
+//! ```shell script
+//! cargo install --locked git-bump --features release
+//! ```
+//!
+//! For further processing, for example in release automation, `--json` prints a
+//! report of the bump to standard output. It lists every processed file with its
+//! outcome (`written`, `unchanged`, `disabled` by its `enabled` guard or its
+//! function, `missing` if its function did not create it, `not-materialized` if it
+//! is excluded by sparse-checkout, `untracked` with `--tracked-only`, or `skipped`
+//! along with the failed phase and the error message), the hooks that ran including
+//! the number of attempts and the captured output of `pre_cmd` and `post_cmd`, and
+//! the durations in milliseconds.
+//!
+//! To find slow recipes, for example in large monorepos, `--timings` prints a
+//! table to standard error with the total duration of each file, the time taken
+//! by its Lua functions, and the number of bytes written, slowest files first.
+//! The time taken by evaluating the config files is shown separately. The JSON
+//! report contains the same figures as `lua_ms`, `bytes_written`, and
+//! `config_ms`:
+//!
+//! ```text
+//! $ git bump --timings 1.2.3
+//! File                   Total        Lua    Written
+//! CHANGELOG.md           12 ms      11 ms     4812 B
+//! Cargo.toml              3 ms       1 ms      412 B
+//! Config evaluation       5 ms
+//! Total                  21 ms                5224 B
+//! ```
+//!
+//! After every bump, its version, time, and bumped files are stored in
+//! `.git/git-bump/state.toml`. For a quick health check of the release state,
+//! run:
+//!
+//! ```text
+//! $ git bump status
+//! Last bump to 1.2.3 on 2024-05-01 12:00:00 UTC
+//!
+//!     ok        Cargo.toml
+//!     drifted   VERSION
+//! ```
+//!
+//! Just like the [pre-commit hook](#pre-commit-integration), this bumps every
+//! file of the last bump in memory and reports those whose contents would change
+//! as drifted, which makes the command fail. Files that no longer exist or no
+//! longer have a mapping are reported as well.
+//!
+//! To check that the files match what the mappings would generate for a version,
+//! for example to catch manual edits on CI, run `git bump verify`. Every mapping
+//! is evaluated for the given version, and each file is compared byte for byte
+//! with its expected contents. Files that differ, or that would be created by
+//! their mapping but do not exist yet, make the command fail. Files with a
+//! [formatter](#mapping-options) are only listed as `formatted`, since the
+//! formatter only runs when writing them:
+//!
+//! ```text
+//! $ git bump verify 1.2.3
+//!     ok        Cargo.toml
+//!     formatted src/version.rs
+//!     differs   VERSION
+//! ```
+//!
+//! Versions also hide in places no mapping covers, like install instructions in the
+//! documentation. To catch those, `git bump replace` searches all tracked text
+//! files for the exact previous version, the version of the nearest tag unless
+//! given with `--previous`, and shows every line containing it. Only after
+//! confirming are the occurrences replaced, or right away with `--yes`. Versions
+//! that merely contain the previous one, like `11.2.3` or `1.2.3.4` for `1.2.3`,
+//! are left alone. Lockfiles, vendored code in `vendor/`, `node_modules/`, and
+//! `third_party/`, symbolic links, and submodules are never searched, and the
+//! search can be narrowed down further with Git pathspecs, each of which can be
+//! given multiple times:
+//!
+//! ```text
+//! $ git bump replace 1.3.0 --include 'docs/*' --exclude CHANGELOG.md
+//! docs/install.md:12: curl -LO https://example.com/tool-1.2.3.tar.gz
+//! Replace 1 occurrence(s) of 1.2.3 in 1 file(s) with 1.3.0? [y/N]
+//! ```
+//!
+//! If mappings might match files that are not part of the repository, like build
+//! artifacts in `target/` or `node_modules/`, `--tracked-only` skips all files
+//! that are not tracked by Git, with a warning.
+//!
+//! In monorepos with a [sparse
+//! checkout](https://git-scm.com/docs/git-sparse-checkout), mapped files might
+//! not be materialized in the worktree. Instead of treating them like keys with a
+//! typo, git-bump skips them with a warning that names them as not materialized.
+//! With `--materialize`, they are checked out before bumping by adding them to
+//! the sparse-checkout patterns, or their parent directories in cone mode:
+//!
+//! ```shell script
+//! git bump --materialize 1.3.0
+//! ```
+//!
+//! In repositories where only the components that were actually touched get
+//! their embedded versions refreshed, `--since` restricts the bump to files that
+//! changed since the nearest tag, including uncommitted changes:
+//!
+//! ```shell script
+//! git bump --since 1.3.0
+//! ```
+//!
+//! With a value, like `--since=HEAD~5` or `--since=v1.0.0`, the files are
+//! compared against the given revision instead.
+//!
+//! In large repositories with expensive bumping functions, `--jobs` bumps files
+//! on several threads in parallel:
+//!
+//! ```shell script
+//! git bump --jobs 8 1.3.0
+//! ```
+//!
+//! Since Lua states cannot be shared between threads, every thread evaluates the
+//! config files on its own, so configs must not rely on being evaluated only once.
+//! The results are merged in the usual order, so reports, previews, and the
+//! `on_error` hook look just like without parallel jobs, and global hooks still
+//! run only once. Hook functions and lockfile refreshes of different files run
+//! one at a time, since they change the current directory or share lockfiles.
+//! Opening files in an editor, reviewing changes, and `--index-only` are not
+//! available with parallel jobs.
+//!
+//! A bump that has nothing to do fails, so a typo in a file name or group does not
+//! go unnoticed. If no config file is found at all, the error says so. If config
+//! files are found, but none of their mappings matches an existing file of the
+//! selected groups, the error lists the keys whose files are missing, and
+//! `git-bump` exits with the dedicated code 3 instead of 1, so pipelines can tell
+//! this case from actual failures. Where an empty bump is acceptable,
+//! `--allow-empty` turns both cases into an informational message.
+//!
+//! To create a sample configuration file with several ready-to-use recipes, run:
+//!
+//! ```shell script
+//! git bump --print-sample-config >.git-bump.lua
+//! ```
+//!
+//! Or let `git-bump` write it to `.git-bump.lua` in the root of the worktree, which
+//! it refuses if the file already exists:
+//!
+//! ```shell script
+//! git bump init
+//! ```
+//!
+//! With `--detect`, the worktree is searched for known files instead, and the
+//! config is generated with a commented recipe for each of them, using the
+//! [bundled helpers](#bundled-helpers): `Cargo.toml` and `package.json`,
+//! including their workspaces, `pyproject.toml`, `setup.cfg`, Go files that define
+//! a version constant, `Chart.yaml`, `debian/changelog`, RPM spec files,
+//! `CHANGELOG.md`, and `VERSION`. Lockfiles are refreshed if they exist.
+//!
+//! Since the full sample config covers many kinds of projects, both
+//! `--print-sample-config` and `git bump init --ecosystem` can restrict it to the
+//! recipes of a single ecosystem, which are `rust`, `node`, `python`, and `java`.
+//! Each of these configs also maps `CHANGELOG.md`:
+//!
+//! ```shell script
+//! git bump --print-sample-config python >.git-bump.lua
+//! git bump init --ecosystem java
+//! ```
+//!
+//! When migrating from another tool, its config can be converted instead:
+//!
+//! ```shell script
+//! git bump import bumpversion
+//! ```
+//!
+//! This reads the `[bumpversion]` sections of `.bumpversion.cfg` or `setup.cfg`,
+//! as used by bumpversion and bump2version, and maps each configured file to
+//! [`helpers.replace`](#search-and-replace) with its `search` and `replace`
+//! templates. Since these templates need the current version, it is kept in the
+//! generated config, which maps itself to bump it along with the other files.
+//! Settings without an equivalent, like custom version parts or `commit` and
+//! `tag`, are reported and listed as comments in the generated config.
+//!
+//! Rust projects using cargo-release can import its pre-release replacements:
+//!
+//! ```shell script
+//! git bump import cargo-release
+//! ```
+//!
+//! The replacements are read from `[package.metadata.release]` in `Cargo.toml` or
+//! from `release.toml` of each package, or else from the workspace, with file
+//! names relative to the package. Their regular expressions are translated to Lua
+//! patterns, and the template variables `{{version}}`, `{{prev_version}}`,
+//! `{{date}}`, and `{{crate_name}}` to their equivalents. Regular expressions
+//! without an equivalent Lua pattern, for example alternations, are reported and
+//! have to be converted by hand. The manifests of all packages are mapped with
+//! [`helpers.cargo_workspace`](#cargo-workspaces), including the dependencies
+//! between them.
+//!
+//! File updates done by semantic-release plugins can be imported as well:
+//!
+//! ```shell script
+//! git bump import semantic-release
+//! ```
+//!
+//! The config is read from the `release` member of `package.json`, from
+//! `.releaserc`, or from `.releaserc.json`; configs in YAML or JavaScript have to
+//! be converted to JSON first. The replacements of
+//! `semantic-release-replace-plugin` and the `sed -i` commands in the
+//! `prepareCmd` of `@semantic-release/exec` become rules of `helpers.replace`,
+//! with `${nextRelease.version}` and `${lastRelease.version}` translated to their
+//! equivalents, and `@semantic-release/npm` maps `package.json` with
+//! `helpers.package_json`. Other commands, globs, and further template variables
+//! are reported. Just like the other imports, the generated config keeps the
+//! current version, taken from `package.json`.
+//!
+//! To print out a list of existing files that are configured in the config files
+//! and would be processed during bumping, run:
+//!
+//! ```shell script
+//! git bump --list-files
+//! ```
+//!
+//! With `--long`, the description of each file and the config file that defined
+//! its mapping are printed alongside its path.
+//!
+//! To preview the changes of a bump without writing any files or running any
+//! hooks, run:
+//!
+//! ```shell script
+//! git bump --diff 1.2.3
+//! ```
+//!
+//! The changes are shown as unified diff by default. With `--diff-mode
+//! side-by-side`, the old and new lines are shown in two columns, and with
+//! `--diff-mode word`, only the changed words are marked inline, which makes
+//! reviewing large changelog edits much easier. On a terminal, the version
+//! strings that actually changed are highlighted.
+//!
+//! Just like with Git, previews that do not fit on the screen are shown through
+//! the pager configured in `GIT_PAGER`, `core.pager`, or `PAGER`, falling back to
+//! `less`. Use `--no-pager` to print them directly.
+//!
+//! To use `git-bump` as a text transformer in other pipelines, `--stdout` prints
+//! the new contents of the files instead of writing them, again without running
+//! any hooks. Each file is preceded by a header line like `==> VERSION <==`, so
+//! the output can be split up again, and combined with `--group`, it can be
+//! narrowed down to a single file:
+//!
+//! ```shell script
+//! git bump --stdout --group docs 1.2.3 | tail -n +2 > /tmp/VERSION
+//! ```
+//!
+//! `git-bump` can be run from any subdirectory of the worktree. Like `git status`,
+//! file lists, diffs, and messages then show paths relative to the current
+//! directory, for example `../VERSION`, while the mappings themselves are always
+//! relative to the root of the worktree. Set `status.relativePaths` to `false` to
+//! show all paths relative to the root instead. This goes for every path in
+//! messages, including errors and warnings about config files in the worktree,
+//! while paths outside of it, like the one of the per-user config, are always
+//! shown in full. To get full paths everywhere, for example for tools that parse
+//! the output, pass `--absolute-paths`.
+//!
+//! For big bumps, like in a monorepo, the changes can also be reviewed file by
+//! file before applying them:
+//!
+//! ```shell script
+//! git bump --review 1.2.3
+//! ```
+//!
+//! This opens a full-screen view with the list of files on the left and the
+//! changes of the selected file on the right. Use the arrow keys to select a
+//! file, space to exclude or include it, `a` to toggle all of them, and Page
+//! Up/Down to scroll through the changes. Enter bumps only the included files,
+//! `q` aborts without changing anything. This is only available with the cargo
+//! feature `tui`:
+//!
+//! ```shell script
+//! cargo install --locked git-bump --features tui
+//! ```
+//!
+//! To hand-polish the generated content before committing, for example the
+//! release notes in a changelog, run:
+//!
+//! ```shell script
+//! git bump --edit=CHANGES.md 1.2.3
+//! ```
+//!
+//! After a file has been written, it is opened in the editor configured in
+//! `GIT_EDITOR`, `core.editor`, `VISUAL`, or `EDITOR`, falling back to `vi`. The
+//! post hooks of the file only run after the editor has been closed. Without a
+//! value, `--edit` opens every bumped file. The file paths are relative to the
+//! root of the repository, and `--edit` can be given multiple times.
+//!
+//! To write the release notes by hand while still bumping everything else
+//! automatically, run:
+//!
+//! ```shell script
+//! git bump --notes 1.2.3
+//! ```
+//!
+//! This opens the editor with an empty buffer before any file is bumped. Just
+//! like with commit messages, lines starting with `#` are ignored. The notes are
+//! passed to the bumping functions as `ctx.notes` (see [Repository
+//! Context](#repository-context)), and the sample `CHANGES.md` mapping adds them
+//! below the new version header.
+//!
+//! If a bumping function fails, the error names the mapping and the config file
+//! it comes from, and the Lua message points to the line in the config file:
+//!
+//! ```text
+//! $ git bump 1.2.3
+//! Error: Failed to bump VERSION from /path/to/repo/.git-bump.lua: Failed to execute Lua code: runtime error: /path/to/repo/.git-bump.lua:2: attempt to index a nil value (field 'x')
+//! ```
+//!
+//! To find out how the failing line was reached, for example through helper
+//! functions, `--trace` additionally shows the Lua stack traceback.
+//!
+//! To find mistakes in the config files before they break a release, run:
+//!
+//! ```shell script
+//! git bump lint
+//! ```
+//!
+//! This reports all findings at once, each with its severity, config file, and
+//! mapping:
+//!
+//! -   **error**: config files that fail to load, mappings that are neither a
+//!     function nor a table with a `func` member, and functions that fail on
+//!     their file.
+//! -   **warning**: unknown options in mapping tables, mappings whose result does
+//!     not change with the version, mappings that are not idempotent, two
+//!     mappings in one config file referring to the same file, and calls to
+//!     `os.execute`, which are better declared as [external
+//!     commands](#external-commands).
+//! -   **info**: mappings that override a mapping of a previous config file.
+//!
+//! The mappings are run on their files with placeholder versions, without writing
+//! anything. To find mappings that are not idempotent, like changelog functions
+//! that add a heading without checking whether it is already there, each mapping is
+//! run a second time on its own result with the same version, which must not change
+//! anything. Otherwise, bumping twice to the same version would add duplicate
+//! entries. Errors make the command fail, and with `--deny-warnings`, so do
+//! warnings, for example on CI.
+//!
+//! When something does not work as expected, for example on a fresh CI runner,
+//! `git bump doctor` checks the environment without changing anything. It reports
+//! whether the repository is found, which config files exist and whether they
+//! parse, which Lua runtime is used, whether the mapped files can be written, the
+//! identity bump commits would be created with, and whether signing is set up:
+//!
+//! ```text
+//! $ git bump doctor
+//! pass  repository: Found worktree /path/to/repo/
+//! pass  lua: Running Lua 5.4
+//! pass  config: Parsed .git-bump.lua
+//! pass  files: 3 mapped file(s) can be written
+//! warn  identity: No identity found, set user.name and user.email, or bump.identity, to use --commit
+//! pass  signing: Commits are not signed
+//! ```
+//!
+//! Failed checks make the command fail, while warnings are only reported.
+//!
+//! Since config files are Lua code, a shared config can run any command on your
+//! machine. To vet such a config before trusting it, run:
+//!
+//! ```shell script
+//! git bump audit
+//! ```
+//!
+//! This runs all config files, mappings, and hook functions in a sandbox, where
+//! anything that reaches outside of the worktree is only recorded instead of
+//! executed, and prints what would have happened along with the location in the
+//! config file:
+//!
+//! ```text
+//! /path/to/repo/.git-bump.lua:4: io.popen git config user.name
+//! /path/to/repo/.git-bump.lua: Cargo.toml: lockfile cargo
+//! ```
+//!
+//! Recorded are shell commands via `os.execute` and `io.popen`, removing or
+//! renaming files, opening files outside of the worktree or for writing, any other
+//! functions of the `io` and `os` libraries except for reading the time, dates, and
+//! environment variables, loading Lua modules, especially network ones like
+//! `socket.http`, and C libraries, as well as declared [external
+//! commands](#external-commands) and [lockfile refreshes](#lockfile-refresh).
+//! Reading files inside of the worktree is still allowed, so the mappings run on
+//! the real contents of their files.
+//!
+//! ## Pre-Commit Integration
+//!
+//! To prevent versions in files from drifting, for example after resolving merge
+//! conflicts by hand, `git bump hook-check` checks the given files against a
+//! version. Every file that has a mapping is bumped in memory, without running any
+//! hooks, and if its contents would change, the file is reported and the command
+//! fails. The expected version is the version of the nearest tag, or the one
+//! given with `--expect`:
+//!
+//! ```shell script
+//! git bump hook-check --expect 1.2.3 --files Cargo.toml CHANGES.md
+//! ```
+//!
+//! This is designed to run under the [pre-commit](https://pre-commit.com)
+//! framework, which passes the staged files to the hook:
+//!
+//! ```yaml
+//! repos:
+//!   - repo: https://github.com/FloGa/git-bump
+//!     rev: v0.4.0
+//!     hooks:
+//!       - id: git-bump
+//! ```
+//!
+//! ## Shell Completions
+//!
+//! Completions for Bash, Zsh, Fish, PowerShell, and Elvish are printed by
+//! `git bump completions`, for example:
+//!
+//! ```shell script
+//! git bump completions bash > ~/.local/share/bash-completion/completions/git-bump
+//! ```
+//!
+//! With Bash and Fish, the completions also suggest versions to bump to, namely
+//! the next patch, minor, and major version after the nearest tag, as well as the
+//! versions of all existing tags. In Bash, this also works for `git bump` if
+//! Git's own completions are loaded.
+//!
+//! ## Release Notes
+//!
+//! To use the changelog as body of a release on GitHub or GitLab, `git bump
+//! notes` prints the section of a version:
+//!
+//! ```shell script
+//! git bump notes 1.2.3 > notes.md
+//! ```
+//!
+//! The section starts with a second-level heading containing the version, like
+//! `## [1.2.3] - 2024-01-31` or `## v1.2.3`, and ends before the next such
+//! heading or the link reference definitions at the end of the file. The heading
+//! itself is left out. The changelog is `CHANGELOG.md` in the root of the
+//! worktree, unless configured otherwise with `git config bump.changelog
+//! CHANGES.md` or given explicitly with `--changelog`.
+//!
+//! ## Metrics
+//!
+//! To track bump activity across many repositories, for example in release
+//! engineering, set `bump.metricsCommand` in the Git config, like in the system
+//! or global config of your CI runners. After each bump, the command is run
+//! through the shell and gets a summary of the bump as JSON on its standard
+//! input. Nothing is sent unless the command is configured:
+//!
+//! ```shell script
+//! git config --global bump.metricsCommand \
+//!     'curl -fsS -H "Content-Type: application/json" -d @- https://metrics.example.com/bumps'
+//! ```
+//!
+//! ```json
+//! {"repository":"https://example.com/group/repo.git","version":"1.2.3","outcome":"success","error":null,"preview":false,"files_written":3,"duration_ms":120}
+//! ```
+//!
+//! The repository is the URL of `origin`, without any credentials, or the path
+//! of the worktree if there is no such remote. Failed bumps are sent as well,
+//! with `"outcome":"failure"` and the error message. If the command fails, a
+//! warning is shown, but the bump itself is not affected.
+//!
+//! ## Library Usage
+//!
+//! `git-bump` can also be embedded as a library. The function `git_bump::bump`
+//! takes the same options as the command line and returns a `BumpReport`, which is
+//! the same data model that `--json` renders:
+//!
+//! ```rust,no_run
+//! use git_bump::{bump, BumpOptions, FileOutcome, Settings};
+//!
+//! fn main() -> git_bump::Result<()> {
+//!     let report = bump(String::from("1.2.3"), &BumpOptions::default(), Settings::default())?;
+//!
+//!     for file in &report.files {
+//!         if file.outcome == FileOutcome::Written {
+//!             println!("Bumped {}", file.path.display());
+//!         }
+//!     }
+//!
+//!     Ok(())
+//! }
+//! ```
+//!
+//! ## Hook Functions
+//!
+//! Along with the new contents for a specified file, one can also define hook
+//! functions that should be run *before* or *after* the new content is written to
+//! the file.
+//!
+//! The `pre_func` could be used, for example, to create a backup of the file
+//! prior to updating it. The `post_func` might be used to do some house keeping
+//! with modified config files.
+//!
+//! The hooks must be returned as a Lua table with the members `pre_func` and
+//! `post_func`. Both members are optional. If a hook function does not exist, it
+//! will be silently ignored.
+//!
+//! The following is a simple, imaginary example to demonstrate the usage of hook
+//! functions. For a proper example, take a look at the section [Sample
+//! Functions](#sample-functions).
+//!
+//! ```lua
+//! return {
+//!     VERSION = function(version)
+//!         local os = require("os")
+//!
+//!         local pre_func = function()
+//!             os.execute("cp VERSION VERSION.old")
+//!         end
+//!
+//!         local post_func = function()
+//!             os.execute("git commit -m 'Update VERSION' VERSION")
+//!         end
+//!
+//!         return version, {pre_func = pre_func, post_func = post_func}
+//!     end
+//! }
+//! ```
+//!
+//! ### Transforming Contents
+//!
+//! While `pre_func` can only act on the side, the hook `pre_write` gets the new
+//! contents right before they are written and returns them, possibly modified,
+//! for example by running a formatter over a generated file. Besides the
+//! contents, it gets a table with the `key` of the mapping and the `version`.
+//! Since it decides what is written, it also runs for previews like `--diff` and
+//! for checks like `git bump verify`, so those show exactly what would be
+//! written:
+//!
+//! ```lua
+//! local indent_with_spaces = function(content, ctx)
+//!     return (content:gsub("\n\t+", function(tabs)
+//!         return "\n" .. ("    "):rep(#tabs - 1)
+//!     end))
+//! end
+//!
+//! return {
+//!     ["Makefile.inc"] = function(version, content)
+//!         return content:gsub("VERSION = [^\n]*", "VERSION = " .. version),
+//!             {pre_write = indent_with_spaces}
+//!     end
+//! }
+//! ```
+//!
+//! ### External Commands
+//!
+//! Instead of calling `os.execute` in a hook function, external commands can also
+//! be declared with the members `pre_cmd` and `post_cmd`. They are given as a
+//! list of the program and its arguments and are executed by `git-bump` itself,
+//! without involving a shell, in the root of the Git repository. Their output is
+//! captured and a command that exits unsuccessfully aborts the bump with an error
+//! containing that output.
+//!
+//! ```lua
+//! return {
+//!     ["Cargo.toml"] = function(version, content)
+//!         -- ...
+//!
+//!         return content, {post_cmd = {"cargo", "check"}}
+//!     end
+//! }
+//! ```
+//!
+//! ### Hook Directory
+//!
+//! Hook functions and external commands run in the root of the worktree, no
+//! matter from which directory `git-bump` is invoked, so relative paths in
+//! `os.execute`, `io.open`, or `pre_cmd` and `post_cmd` work the same everywhere.
+//! Set the member `dir` to `"file"` to run them in the directory of the mapped
+//! file instead, or to a directory relative to the worktree:
+//!
+//! ```lua
+//! return {
+//!     ["web/package.json"] = function(version, content)
+//!         -- ...
+//!
+//!         return content, {post_cmd = {"npm", "install"}, dir = "file"}
+//!     end
+//! }
+//! ```
+//!
+//! Global hooks do not belong to a file, so they only support directories
+//! relative to the worktree. Just like keys of mappings, directories outside of the
+//! worktree, like absolute paths, paths leaving it via `..`, or paths through
+//! symlinks to other places, are rejected with an error, even with
+//! `--allow-outside-worktree`.
+//!
+//! ### Retrying Post Hooks
+//!
+//! Post hooks that talk to network services may fail transiently. To retry them
+//! instead of failing the whole bump, set `post_retries` to the number of retries.
+//! The delay before the first retry is set with `post_retry_delay` in seconds,
+//! defaulting to one second, and doubles with every further retry up to one
+//! minute. Each failed attempt is reported as a warning:
+//!
+//! ```lua
+//! return {
+//!     VERSION = function(version)
+//!         return version, {
+//!             post_cmd = {"curl", "-fsS", "https://example.com/notify"},
+//!             post_retries = 3,
+//!             post_retry_delay = 2,
+//!         }
+//!     end
+//! }
+//! ```
+//!
+//! ### Global Hooks
+//!
+//! Hooks that should run only once per bump, *before* the first and *after* the
+//! last file, can be assigned to the global `hooks` table in any config file. It
+//! supports the same members `pre_func`, `post_func`, `pre_cmd`, and `post_cmd`:
+//!
+//! ```lua
+//! hooks.post_cmd = {"git", "add", "--update"}
+//!
+//! return {
+//!     -- ...
+//! }
+//! ```
+//!
+//! If bumping a file fails, the function `hooks.on_error` is called with the file
+//! name as shown in messages, the phase in which it failed, and the error message,
+//! for example to send notifications or write marker files. The phase is one of
+//! `enabled`, `read`, `func`, `pre`, `write`, `format`, `edit`, `lockfile`, or
+//! `post`. The hook is called for optional files as well, and errors in the hook
+//! itself are only reported as warnings:
+//!
+//! ```lua
+//! hooks.on_error = function(file, phase, message)
+//!     io.open(".release-failed", "w"):write(file, " (", phase, "): ", message)
+//! end
+//! ```
+//!
+//! With `--commit` or `--tag`, the functions `hooks.post_commit` and
+//! `hooks.post_tag` are called right after the commit or the tag was created,
+//! with the hash of the commit and the name of the tag, respectively, for example
+//! to trigger follow-up actions for exactly these objects. They run after the
+//! global `post_func` and `post_cmd`, but before `--push`, and can be retried
+//! just like other post hooks. Since the commit or the tag exists already, a
+//! failing hook is only reported as a warning and the bump carries on:
+//!
+//! ```lua
+//! hooks.post_tag = function(tag)
+//!     os.execute("./scripts/announce.sh " .. tag)
+//! end
+//! ```
+//!
+//! ### Lockfile Refresh
+//!
+//! Many package managers keep the version of the package in a lockfile, too.
+//! Instead of calling the according tool manually in a `post_func`, the hooks
+//! table may contain a `lockfile` member to let `git-bump` refresh the lockfile
+//! right after the manifest has been written:
+//!
+//! | Value      | Command                                               |
+//! |------------|-------------------------------------------------------|
+//! | `"cargo"`  | `cargo update -p <package> --precise <version>`       |
+//! | `"npm"`    | `npm install --package-lock-only`                     |
+//! | `"poetry"` | `poetry lock --no-update`                             |
+//!
+//! If the value is `true`, the updater is derived from the file name of the
+//! manifest (`Cargo.toml`, `package.json`, or `pyproject.toml`). The command is run
+//! in the directory of the manifest, its output is only shown if it fails, in which
+//! case `git-bump` aborts with an error. Since `cargo update` needs the package
+//! name, refreshing fails with a dedicated error for manifests without one, like
+//! the virtual manifest at the root of a workspace.
+//!
+//! ```lua
+//! return {
+//!     ["Cargo.toml"] = function(version, content)
+//!         content = content:gsub(
+//!                       'version = %b""', ('version = "%s"'):format(version), 1
+//!                   )
+//!
+//!         return content, {lockfile = "cargo"}
+//!     end
+//! }
+//! ```
+//!
+//! ## Return Values
+//!
+//! To sum up, a bumping function returns the new contents of its file, optionally
+//! followed by a table of hooks and further options for the file:
+//!
+//! ```lua
+//! return content, {pre_cmd = {"make", "docs"}, mode = "755"}
+//! ```
+//!
+//! Besides the hooks described above, the table may contain:
+//!
+//! -   `skip`: If `true`, the file is left untouched and its hooks are not run,
+//!     just like for a mapping whose `enabled` guard returns `false`. This way, a
+//!     function can decide to skip its file after looking at the contents.
+//! -   `create`: If `true`, the file is created if it does not exist yet, along
+//!     with its parent directories. Since there are no contents to pass, only
+//!     mappings that do not need the contents of their file, like functions that
+//!     only take the version, are run for missing files. If they do not ask to
+//!     create the file, it is reported as `missing`. Files cannot be created with
+//!     `--index-only`.
+//! -   `mode`: Unix permissions of the written file as octal string, like `"755"`.
+//!     It is ignored on other platforms and with `--index-only`.
+//! -   `encoding`: Either `"utf-8"`, which removes a byte order mark at the start
+//!     of the new contents, or `"utf-8-bom"`, which adds one, as expected by some
+//!     Windows tools. Without it, the contents are written as returned.
+//!
+//! For example, a version-dependent config can add a file for every release:
+//!
+//! ```lua
+//! return function(version)
+//!     return {
+//!         ["docs/releases/" .. version .. ".md"] = function(version)
+//!             return "# Release " .. version, {create = true}
+//!         end
+//!     }
+//! end
+//! ```
+//!
+//! Any other values are rejected with an error that names the offending value,
+//! like a misspelled option, a function that forgot to `return` the contents, or
+//! more than two returned values. This also catches the common mistake of
+//! returning the result of `string.gsub` directly, which includes the number of
+//! replacements as second value, so wrap such calls in parentheses.
+//!
+//! The line endings of the written contents follow the `.gitattributes` of the
+//! repository, so a file does not show up as modified as a whole just because a
+//! function added lines with different line endings. Files with `eol=crlf`, or
+//! text files that Git checks out with CRLF due to `core.eol` or `core.autocrlf`,
+//! are written with CRLF throughout, and files with `eol=lf` with LF. Files
+//! without any conversion, like those marked as binary with `-text`, are written
+//! as returned. With `--index-only`, the contents are written to the index as
+//! returned as well, since Git keeps them normalized there.
+//!
+//! ## Mapping Options
+//!
+//! Instead of a plain function, a mapping can also be given as a table. In this
+//! case, the function has to be given in the `func` member, any additional
+//! members are options for this mapping:
+//!
+//! ```lua
+//! return {
+//!     ["docs/VERSION"] = {
+//!         func = function(version)
+//!             return version
+//!         end,
+//!         optional = true
+//!     }
+//! }
+//! ```
+//!
+//! The following options are supported. Before anything is bumped, each option
+//! is checked to have the expected type, so a typo like `optional = "yes"` fails
+//! with an error naming the file and the option, instead of being silently taken
+//! as `true`:
+//!
+//! ```text
+//! Error: Invalid option `optional` for docs/VERSION: expected a boolean, got string
+//! ```
+//!
+//! -   `optional`
+//!
+//!     If set to `true`, a failure while bumping this file (for example, a file
+//!     that cannot be read or a Lua error) is only reported as a warning and the
+//!     remaining files will still be bumped. By default, every failure aborts the
+//!     whole bump.
+//!
+//! -   `group`
+//!
+//!     A group name or a list of group names this file belongs to. If any groups
+//!     are selected with `--group`, only files belonging to at least one of them
+//!     are processed. This is useful if, for example, the documentation is
+//!     versioned on a different cadence than the rest of the project:
+//!
+//!     ```shell script
+//!     git bump 1.2.3 --group manifests
+//!     ```
+//!
+//! -   `description`
+//!
+//!     A short description of this file. It is shown by `--list-files --long`,
+//!     along with the config file that defined the mapping, which turns the
+//!     listing into a small documentation of your release process.
+//!
+//! -   `enabled`
+//!
+//!     A function that gets the version and the [repository
+//!     context](#repository-context) `ctx` and returns whether this file should be
+//!     bumped at all. It is called before the file is even read, so a disabled
+//!     file is left completely untouched, for example to skip the changelog for
+//!     pre-release versions:
+//!
+//!     ```lua
+//!     return {
+//!         ["CHANGES.md"] = {
+//!             func = function(version, content)
+//!                 -- ...
+//!             end,
+//!             enabled = function(version, ctx)
+//!                 return not version:find("-")
+//!             end
+//!         }
+//!     }
+//!     ```
+//!
+//! -   `needs_content`
+//!
+//!     Whether the function needs the current contents of the file. If set to
+//!     `false`, the file is not read at all and the function gets an empty string
+//!     instead, which saves time for big generated files that are replaced as a
+//!     whole, and avoids errors for files that are not valid UTF-8. Functions that
+//!     only take the version, like the one for `docs/VERSION` above, are detected
+//!     automatically. Since the old contents are not known, such files are always
+//!     reported as `written`, and they are not checked for concurrent
+//!     modifications.
+//!
+//! -   `ensure_trailing_newline`
+//!
+//!     Whether a newline is appended to the new contents if they do not end with
+//!     one, `true` by default. Set it to `false` for generated files that must be
+//!     written exactly as returned, for example JSON files consumed by tools that
+//!     reject a trailing newline.
+//!
+//! -   `raw`
+//!
+//!     Whether the contents are passed to the function as a byte string, `false`
+//!     by default. Raw contents are not checked to be valid UTF-8, and the
+//!     returned contents are written back verbatim, without a trailing newline
+//!     unless `ensure_trailing_newline` asks for one, and without converting the
+//!     line endings configured in `.gitattributes`. This allows bumping files with
+//!     mixed encodings or embedded binary sections, as long as the function only
+//!     uses byte-oriented string functions, which all of Lua's own are:
+//!
+//!     ```lua
+//!     return {
+//!         ["firmware.bin"] = {
+//!             func = function(version, content)
+//!                 return (content:gsub("VER=%d+%.%d+%.%d+", "VER=" .. version))
+//!             end,
+//!             raw = true
+//!         }
+//!     }
+//!     ```
+//!
+//!     Previews and diffs show bytes that are not valid UTF-8 as replacement
+//!     characters.
+//!
+//! -   `current_version`
+//!
+//!     A function that gets the contents of the file and returns the version it
+//!     currently contains, or `nil`. It is used by
+//!     [`ctx.current_version`](#repository-context), so that other mappings can
+//!     refer to the version that is replaced, like a changelog listing the
+//!     changes since the previous release.
+//!
+//! -   `source_of_truth`
+//!
+//!     Whether the version extracted by `current_version` is the version of the
+//!     whole project, `false` by default. At most one mapping can be marked this
+//!     way.
+//!
+//! -   `format`
+//!
+//!     An external formatter that is run on the file right after it was written,
+//!     so regenerated files always match the formatting of the project. It is
+//!     given as a list of the program and its arguments, or as a single program,
+//!     and gets the full path of the file as last argument. Just like [external
+//!     commands](#external-commands), it runs without involving a shell, in the
+//!     directory of the file, and its output is only shown if it fails. Previews
+//!     like `--diff` show the contents before formatting, and checks like `git
+//!     bump verify`, `git bump hook-check`, and `git bump status` leave out files
+//!     with a formatter, since they would always differ otherwise. So the
+//!     formatter should only touch the formatting, or use a [`pre_write`
+//!     hook](#transforming-contents) instead.
+//!
+//!     ```lua
+//!     return {
+//!         ["src/version.rs"] = {
+//!             func = function(version)
+//!                 return ('pub const VERSION: &str = "%s";'):format(version)
+//!             end,
+//!             format = {"rustfmt", "--edition", "2021"}
+//!         }
+//!     }
+//!     ```
+//!
+//! -   `format_failure`
+//!
+//!     What happens if the formatter fails: `"error"`, the default, aborts the
+//!     bump, while `"warn"` and `"ignore"` keep the file as bumped, with or
+//!     without a warning.
+//!
+//! -   `chain`
+//!
+//!     Whether the function is run on the result of the mapping of the same file
+//!     from a previous config file, instead of overriding it. See [Chained
+//!     Mappings](#chained-mappings).
+//!
+//! ## Chained Mappings
+//!
+//! Sometimes a file is best updated in several independent steps, for example the
+//! version line of a changelog by one function and its date line by another.
+//! Instead of a single mapping, a list of mappings can be given for a file. They
+//! are run in order, each getting the contents returned by the previous one, and
+//! the result of the last one is written:
+//!
+//! ```lua
+//! return {
+//!     ["CHANGES.md"] = {
+//!         helpers.keep_a_changelog,
+//!         function(version, content)
+//!             local date = os.date("%Y-%m-%d")
+//!             return (content:gsub("Last updated: %S+", "Last updated: " .. date))
+//!         end
+//!     }
+//! }
+//! ```
+//!
+//! The options of the chain as a whole, like `optional` or `group`, are taken from
+//! its first mapping. Each further mapping can still have an `enabled` guard to
+//! skip its step, and all hooks returned along the way are run.
+//!
+//! Similarly, a mapping with the `chain` option set to `true` is appended to the
+//! mapping of the same file from a previous config file, instead of overriding
+//! it. This way, for example, a per-user config can add a step to a mapping of the
+//! shared config. Without a previous mapping, it is used as a regular mapping.
+//!
+//! ## Version-Dependent Configs
+//!
+//! Instead of a table, a config file may also return a function that takes the
+//! version to bump to and returns the table of mappings. This way, the set of
+//! mappings itself can depend on the version, for example to skip the changelog
+//! for snapshot versions:
+//!
+//! ```lua
+//! return function(version)
+//!     local mappings = {
+//!         VERSION = function(version)
+//!             return version
+//!         end
+//!     }
+//!
+//!     if version == nil or not version:find("%-SNAPSHOT$") then
+//!         mappings["CHANGES.md"] = function(version, content)
+//!             -- ...
+//!         end
+//!     end
+//!
+//!     return mappings
+//! end
+//! ```
+//!
+//! Since there is no version when only listing the files with `--list-files`,
+//! the function is called with `nil` in this case.
+//!
+//! ## Repository Context
+//!
+//! While evaluating the config files, a global table `ctx` provides some
+//! information about the current repository, so that especially the per-user
+//! config can enable mappings depending on the repository it runs in:
+//!
+//! -   `ctx.worktree`: Path to the root of the worktree.
+//! -   `ctx.name`: Name of the repository, taken from the worktree directory.
+//! -   `ctx.branch`: Name of the current branch, or `nil` if `HEAD` is detached.
+//! -   `ctx.project_types`: Table of detected project types, with the type names
+//!     as keys and `true` as values. Detected types are `debian`, `go`, `helm`,
+//!     `java`, `node`, `python`, `rpm`, and `rust`.
+//! -   `ctx.notes`: Release notes entered with `--notes`, or `nil`. Only available
+//!     in the bumping functions, not while evaluating the config files.
+//! -   `ctx.current_version(file)`: Current version of a file, as extracted by the
+//!     `current_version` function of its mapping, with the file given relative to
+//!     the root of the worktree. Without a file, the mapping marked as
+//!     `source_of_truth` is used. The contents are taken from `HEAD`, or from the
+//!     worktree for files that are not committed yet, so the result is the same
+//!     no matter which files were bumped already. Only available in the bumping
+//!     functions.
+//! -   `ctx.tmpdir`: Path to a temporary directory of the current run, which is
+//!     removed afterwards, so hooks can stash backups or intermediate artifacts
+//!     without littering the repository. It is shared by all files and, with
+//!     `--jobs`, by all threads.
+//!
+//! ```lua
+//! local mappings = {}
+//!
+//! if ctx.project_types.rust then
+//!     mappings["Cargo.toml"] = function(version, content)
+//!         -- ...
+//!     end
+//! end
+//!
+//! return mappings
+//! ```
+//!
+//! With `ctx.current_version`, a changelog can name the previous release without
+//! parsing it from the changelog itself:
+//!
+//! ```lua
+//! return {
+//!     ["Cargo.toml"] = {
+//!         func = helpers.cargo_toml,
+//!         current_version = function(content)
+//!             return content:match('\nversion%s*=%s*"([^"]+)"')
+//!         end,
+//!         source_of_truth = true
+//!     },
+//!     ["CHANGES.md"] = function(version, content)
+//!         return content .. ("\n## %s\n\nChanges since %s.\n"):format(
+//!             version, ctx.current_version()
+//!         )
+//!     end
+//! }
+//! ```
+//!
+//! Additionally, a global table `links` provides helpers for links to the forge
+//! the repository is hosted on, as detected from the URL of the `origin` remote.
+//! `links.compare(prev, next)` returns the URL comparing the release tags of two
+//! versions, like `https://github.com/owner/repo/compare/v1.2.2...v1.2.3`, or
+//! `nil` if the remote is not hosted on the web. URLs are built GitHub style, or
+//! GitLab style if the host name contains `gitlab`. With this, the footer links
+//! of a Keep a Changelog style `CHANGELOG.md` can be maintained automatically:
+//!
+//! ```lua
+//! return {
+//!     ["CHANGELOG.md"] = function(version, content)
+//!         local previous = content:match("%[(%d[^%]]*)%]:")
+//!         local link = previous and links.compare(previous, version)
+//!         if link then
+//!             content = content:gsub(
+//!                 "%[" .. previous:gsub("%p", "%%%0") .. "%]:",
+//!                 "[" .. version .. "]: " .. link .. "\n%0",
+//!                 1
+//!             )
+//!         end
+//!         return content
+//!     end,
+//! }
+//! ```
+//!
+//! ## Configuration File Locations
+//!
+//! The bump config files will be searched in the following locations:
+//!
+//! -   `$HOME/.git-bump.lua` (Unix) or `%USERPROFILE%\.git-bump.lua` (Windows)
+//!
+//!     Per-user global config file. Such a file may define mappings for hundreds
+//!     of files across all projects. Mappings of files that do not exist in the
+//!     current repository and need their contents are only validated, but not
+//!     created, which keeps startup fast. Once all files are bumped, the functions
+//!     of the mappings are released before running global hooks.
+//!
+//! -   `$GIT_DIR/git-bump.lua`
+//!
+//!     Per-repository config file, not intended for sharing.
+//!
+//! -   `$GIT_WORK_TREE/.git-bump.lua`
+//!
+//!     Per-repository config file, may be checked into Git for sharing.
+//!
+//! -   Path from `bump.config` in the Git config
+//!
+//!     Further config file, for example in a dotfiles repository shared across
+//!     several projects. Just like for `core.hooksPath`, a leading `~/` stands for
+//!     the home directory, and relative paths are relative to the root of the
+//!     worktree. Unlike the other locations, a configured file that does not exist
+//!     is an error.
+//!
+//! -   `$GIT_WORK_TREE/**/.git-bump.lua`
+//!
+//!     Directory-scoped config files, so that packages in a monorepo can keep
+//!     their bump config next to their code. The keys of these files are relative
+//!     to the directory of the file, not to the root of the repository. Ignored
+//!     directories and nested repositories are skipped, and configs of parent
+//!     directories are evaluated before those of their subdirectories.
+//!
+//! For umbrella repositories that contain other repositories, `--outer-config`
+//! additionally evaluates the `.git-bump.lua` in the worktrees of all
+//! repositories the current one is nested in, right after the per-user config and
+//! from the outermost to the innermost one. This way, an umbrella repository can
+//! define shared bumpers for its inner projects. Just like in the other config
+//! files, the keys are relative to the root of the current repository.
+//!
+//! Those locations will be evaluated in order, a later file overrides mappings of
+//! the previous ones if they have matching keys, unless the mappings are
+//! [chained](#chained-mappings). Missing config files will be silently ignored.
+//!
+//! Whenever a mapping is overridden this way, an informational message names
+//! both config files, so surprising precedence effects do not go unnoticed. With
+//! `--no-override`, overriding a mapping is treated as an error instead.
+//!
+//! Config files are identified by their paths with all symlinks resolved, so a
+//! file that is found in several locations, like a per-user config that is a
+//! symlink to the shared config of a repository, is only evaluated once, at its
+//! first location. The resolved path is also what messages show and what decides
+//! whether a config is directory-scoped. Dotfile managers often symlink
+//! `~/.git-bump.lua` into a dotfiles repository, though, where the resolved path
+//! would change its meaning. With `--no-canonicalize`, config files keep the
+//! paths they were found at, while duplicates are still detected by their
+//! resolved paths.
+//!
+//! Keys are matched against the names of the files on disk, regardless of their
+//! Unicode normalization form, so a key `Café.txt` also matches a file whose name
+//! is stored decomposed, like macOS does it. On case-insensitive file systems,
+//! keys like `Version` and `VERSION` refer to the same file and override each
+//! other as well. If such keys come from the same config file, a warning is shown
+//! and the last key in sorted order wins.
+//!
+//! The same goes for keys of existing files that are reached via symlinks or hard
+//! links, which are recognized by their identity on disk, so a file is never
+//! written twice. A warning names both keys along with their config files and the
+//! mapping that wins, and the file keeps the path it was mapped with first. With
+//! `--no-override`, such a conflict between different config files is an error.
+//!
+//! Since config files may come from untrusted sources, for example a shared config
+//! in a cloned repository, keys that point outside of the worktree, like absolute
+//! paths, paths leaving it via `..`, or paths through symlinks to other places, are
+//! rejected with an error. If this is really intended, for example in a per-user
+//! config, allow it with `--allow-outside-worktree`.
+//!
+//! Each of these locations can be skipped with `--no-user-config`,
+//! `--no-local-config`, and `--no-shared-config`, respectively. This way, for
+//! example, a CI job can run with only the shared config of the repository and
+//! ignore whatever happens to be in the home directory of the runner.
+//!
+//! The per-user config file can also be ignored by setting the environment
+//! variable `GIT_BUMP_NO_GLOBAL=1`. Since CI images sometimes carry unexpected
+//! global configs, this is done automatically if `CI=true` is set, which is the
+//! case on most CI systems. To use the per-user config file on CI anyway, set
+//! `GIT_BUMP_NO_GLOBAL=0`.
+//!
+//! If you want to explicitly ignore a bumping function of a "higher"
+//! configuration, you must declare it in a "lower" config file like so:
+//!
+//! ```lua
+//! return {
+//!     -- ...
+//!
+//!     ["dummy.txt"] = function(_, content)
+//!         -- no bumping, just return unaltered content
+//!         return content
+//!     end
+//!
+//!     -- ...
+//! }
+//! ```
+//!
+//! ## Bundled Helpers
+//!
+//! For the most common files, `git-bump` comes with a library of ready-made
+//! bumping functions. It is available in every config file as the global
+//! `helpers` table, or via `require("git-bump.helpers")`:
+//!
+//! | Function                     | Updates                                       |
+//! |------------------------------|-----------------------------------------------|
+//! | `helpers.plain_version_file` | the whole file, like a `VERSION` file         |
+//! | `helpers.cargo_toml`         | the version in the `[package]` section        |
+//! | `helpers.package_json`       | the top-level `"version"` member              |
+//! | `helpers.pyproject_toml`     | the version in `[project]` or `[tool.poetry]` |
+//! | `helpers.setup_cfg`          | the version in the `[metadata]` section       |
+//! | `helpers.go_version`         | the first constant ending in `Version`        |
+//! | `helpers.keep_a_changelog`   | a new heading in the Keep a Changelog format  |
+//!
+//! Since they are bumping functions themselves, they can be used as mappings
+//! directly, or be called from custom functions, for example to add hooks:
+//!
+//! ```lua
+//! return {
+//!     VERSION = helpers.plain_version_file,
+//!
+//!     ["package.json"] = helpers.package_json,
+//!
+//!     ["Cargo.toml"] = function(version, content)
+//!         return helpers.cargo_toml(version, content), {lockfile = "cargo"}
+//!     end
+//! }
+//! ```
+//!
+//! ### Search and Replace
+//!
+//! For files without a dedicated helper, `helpers.replace(current_version, rules)`
+//! builds a bumping function from a list of rules with `search` and `replace`
+//! templates. Every occurrence of a search template is replaced, and a search
+//! template that is not found is an error. In the templates, `{current_version}`
+//! and `{new_version}` stand for the versions, while `{now:FORMAT}` and
+//! `{utcnow:FORMAT}` stand for the local and UTC date in the given `os.date`
+//! format. Missing templates default to the plain versions.
+//!
+//! Further options of a rule:
+//!
+//! -   `pattern`: If `true`, the search template is a Lua pattern, and the replace
+//!     template can refer to its captures like in `string.gsub`.
+//! -   `min`, `max`, `exactly`: Required number of occurrences, at least one by
+//!     default.
+//! -   `prerelease`: If `false`, the rule is skipped for pre-release versions,
+//!     which contain a `-`.
+//! -   `lines`: If `true`, the pattern is matched against each line, so that `^`
+//!     and `$` match at the start and end of lines, like in sed.
+//!
+//! ```lua
+//! local current_version = "1.2.3"
+//!
+//! return {
+//!     ["setup.py"] = helpers.replace(current_version, {
+//!         {search = 'version="{current_version}"', replace = 'version="{new_version}"'},
+//!     }),
+//! }
+//! ```
+//!
+//! ### Cargo Workspaces
+//!
+//! Instead of listing every manifest of a Cargo workspace, the mappings for all of
+//! them can be generated with `helpers.cargo_workspace`. It reads the `members`
+//! and `exclude` arrays of the `[workspace]` section in the root `Cargo.toml`,
+//! including wildcards like `crates/*`, and maps the root manifest as well as the
+//! manifest of every member. Besides the `[package]` version, the version in the
+//! `[workspace.package]` section is updated, for members that inherit it:
+//!
+//! ```lua
+//! return helpers.cargo_workspace {
+//!     -- also update the versions of dependencies on other members, in all
+//!     -- dependency sections and in [workspace.dependencies]
+//!     dependencies = true,
+//!
+//!     -- refresh Cargo.lock for every member package
+//!     lockfile = true,
+//!
+//!     -- directory of the workspace, relative to the worktree
+//!     -- dir = "rust",
+//! }
+//! ```
+//!
+//! Since the result is an ordinary table of mappings, it can be extended with
+//! further mappings before returning it. The discovered members themselves are
+//! available via `helpers.cargo_workspace_members(path)`, as a list of tables with
+//! the package `name` and its `path` relative to the workspace.
+//!
+//! ### npm Workspaces
+//!
+//! Likewise, `helpers.npm_workspace` maps the root `package.json` and those of all
+//! members of an npm, Yarn, or pnpm workspace. The members are taken from the
+//! `workspaces` of the root `package.json`, or else from the `packages` list in
+//! `pnpm-workspace.yaml`, where patterns starting with `!` exclude members:
+//!
+//! ```lua
+//! return helpers.npm_workspace {
+//!     -- also update the ranges of dependencies on other members, keeping the
+//!     -- range operator, while ranges like "workspace:*" are left untouched
+//!     dependencies = true,
+//!
+//!     -- refresh the shared package-lock.json
+//!     lockfile = true,
+//! }
+//! ```
+//!
+//! The `dir` option works just like for Cargo workspaces, and the members are
+//! available via `helpers.npm_workspace_members(path)`.
+//!
+//! ### Helm Charts
+//!
+//! `helpers.helm_chart` builds a bumping function for the `Chart.yaml` of a Helm
+//! chart, which replaces the top-level `version` and `appVersion` keys, keeping
+//! their quoting style and trailing comments. Nested keys of the same names are
+//! left untouched. Both keys are replaced by default, and either of them can be
+//! switched off, for example if the chart is versioned separately from the
+//! application it deploys:
+//!
+//! ```lua
+//! return {
+//!     ["Chart.yaml"] = helpers.helm_chart {version = false, app_version = true},
+//! }
+//! ```
+//!
+//! ### Debian Changelogs
+//!
+//! `helpers.debian_changelog` builds a bumping function that prepends an entry in
+//! the format of `dch` to `debian/changelog`, with the date in the RFC 2822
+//! format. The package name is taken from the latest entry, and the maintainer
+//! from the environment variables `DEBFULLNAME` and `DEBEMAIL`, or else from
+//! `user.name` and `user.email` of the Git config. Pre-release versions get a
+//! tilde instead of the hyphen, so they sort before the final release, and
+//! snapshots go to the `UNRELEASED` distribution. If the latest entry already has
+//! the version, the changelog is left untouched. All options are optional:
+//!
+//! ```lua
+//! return {
+//!     ["debian/changelog"] = helpers.debian_changelog {
+//!         distribution = "bookworm",  -- "unstable" by default
+//!         urgency = "low",            -- "medium" by default
+//!         revision = "1",             -- Debian revision, "1" by default
+//!         -- package = "git-bump",
+//!         -- maintainer = "Jane Doe <jane@example.com>",
+//!     },
+//! }
+//! ```
+//!
+//! ### RPM Spec Files
+//!
+//! `helpers.rpm_spec` builds a bumping function for RPM spec files, so the version
+//! only has to be maintained in one place. It updates the `Version` tag, resets
+//! the `Release` tag to `1`, and prepends an entry with the date in the format of
+//! RPM to the `%changelog` section, which is added if it does not exist yet. The
+//! packager is taken from the `packager` option, the `Packager` tag, the
+//! environment variable `RPM_PACKAGER`, or else from `user.name` and `user.email`
+//! of the Git config. Just like for Debian, pre-release versions get a tilde
+//! instead of the hyphen:
+//!
+//! ```lua
+//! return {
+//!     ["git-bump.spec"] = helpers.rpm_spec {
+//!         -- packager = "Jane Doe <jane@example.com>",
+//!     },
+//! }
+//! ```
+//!
+//! ### Composing Bumpers
+//!
+//! Complex recipes can be assembled from small, reusable bumping functions, like
+//! the bundled helpers or functions shared between projects:
+//!
+//! -   `bump.chain(func1, func2, ...)` builds a bumping function that runs the
+//!     given ones one after another, each on the contents returned by the
+//!     previous one. Their hooks are merged, where `pre_func` and `post_func`
+//!     functions are run one after another, `pre_write` functions each on the
+//!     result of the previous one, and later hooks take precedence for all other
+//!     members.
+//! -   `bump.when(predicate, func)` builds a bumping function that runs the
+//!     given one only if the predicate returns `true`. Just like the `enabled`
+//!     option of a mapping, the predicate gets the version and the [repository
+//!     context](#repository-context) `ctx`. Otherwise, the contents are left
+//!     untouched.
+//!
+//! Both are also part of the bundled helpers, as `helpers.chain` and
+//! `helpers.when`.
+//!
+//! ```lua
+//! local current_version = "1.2.3"
+//!
+//! local is_release = function(version)
+//!     return not version:find("-")
+//! end
+//!
+//! return {
+//!     ["README.md"] = bump.chain(
+//!         helpers.replace(current_version, {
+//!             {search = "v{current_version}", replace = "v{new_version}"},
+//!         }),
+//!         bump.when(is_release, helpers.replace(current_version, {
+//!             {search = "/{current_version}/", replace = "/{new_version}/"},
+//!         }))
+//!     ),
+//! }
+//! ```
+//!
+//! ## Sample Functions
+//!
+//! Find the latest sample config file here:
+//! <https://github.com/FloGa/git-bump/blob/develop/.git-bump.lua>
+//!
+//! This is a non-exhaustive list of possible functions that can be used in your
+//! config files. If you have ideas for more default functions, don't hesitate to
+//! open a PR!
+
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::io::IsTerminal;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use git2::{DescribeFormatOptions, DescribeOptions, Repository};
+use mlua::prelude::*;
+
+use crate::engine::{Disk, Workspace};
+use crate::formatter::FormatFailure;
+use crate::git::LineEnding;
+use crate::lint::Severity;
+use crate::lockfile::Lockfile;
+use crate::mapping::Mapping;
+use crate::paths::PathDisplay;
+use crate::prefix::Prefixes;
+use crate::retry::Backoff;
+use crate::sample::Ecosystem;
+use crate::semver::{Increment, Version as SemVer};
+use crate::state::State as BumpState;
+use crate::status::{format_timestamp, LastBump};
+
+#[doc(hidden)]
+pub use crate::cli::run;
+pub use crate::diff::DiffMode;
+pub use crate::error::{Error, Result};
+pub use crate::report::{BumpReport, FileOutcome, FileReport, HookReport};
+pub use crate::state::Settings;
+
+mod audit;
+mod bytecode;
+mod cli;
+mod command;
+mod diff;
+mod doctor;
+mod editor;
+mod engine;
+mod error;
+mod formatter;
+mod git;
+mod hooks;
+mod import;
+mod init;
+mod links;
+mod lint;
+mod lockfile;
+mod mapping;
+mod metrics;
+mod notes;
+mod pager;
+mod paths;
+mod pool;
+mod prefix;
+mod project;
+#[cfg(feature = "release")]
+mod release;
+mod replace;
+mod report;
+mod retry;
+mod sample;
+mod semver;
+mod state;
+mod status;
+#[cfg(feature = "tui")]
+mod tui;
+
+/// Options for bumping files.
+#[derive(Default)]
+pub struct BumpOptions {
+    /// Only bump files of these groups, or all files if empty.
+    pub groups: Vec<String>,
+    /// Only bump files that changed since this revision, or since the nearest tag if empty.
+    pub since: Option<String>,
+    /// Skip files that are not tracked by Git.
+    pub tracked_only: bool,
+    /// Check out mapped files that are excluded by sparse-checkout, instead of skipping them.
+    pub materialize: bool,
+    /// Write read-only files anyway, by clearing their read-only attribute while bumping them.
+    pub force_writable: bool,
+    /// Write bumped files only into the index, leaving the worktree untouched.
+    pub index_only: bool,
+    /// Show changes in the given mode instead of writing files.
+    pub diff: Option<DiffMode>,
+    /// Print the new contents of the files instead of writing them, each below a header line.
+    pub stdout: bool,
+    /// Succeed without doing anything if no config file is found or no mapping matches an
+    /// existing file of the selected groups, instead of failing.
+    pub allow_empty: bool,
+    /// Review changes in a terminal interface and only bump the chosen files.
+    #[cfg(feature = "tui")]
+    pub review: bool,
+    /// Never pipe shown changes through a pager.
+    pub no_pager: bool,
+    /// Open written files in an editor, either all of them if empty, or only the given ones.
+    pub edit: Option<Vec<PathBuf>>,
+    /// Ask for release notes in an editor and provide them as `ctx.notes`.
+    pub notes: bool,
+    /// Create a branch from this template before bumping, replacing `{version}`.
+    pub branch: Option<String>,
+    /// Do not switch to the created branch.
+    pub no_switch: bool,
+    /// Refuse to bump unless this branch is checked out, overriding `bump.requireBranch` from the
+    /// Git config. An empty name allows any branch.
+    pub require_branch: Option<String>,
+    /// Number of worker threads that bump files in parallel, each with its own Lua state. Zero and
+    /// one both bump all files on the calling thread. Cannot be combined with `index_only`,
+    /// `edit`, or `review`.
+    pub jobs: usize,
+    /// Commit the bumped files.
+    pub commit: bool,
+    /// Amend `HEAD` with the bumped files instead of creating a new commit.
+    pub amend: bool,
+    /// Commit and tag as this identity in the format `Name <email>`, instead of the one from the
+    /// environment or the Git config.
+    pub identity: Option<String>,
+    /// Build the commit message from this template file, replacing `{version}`, `{notes}`, and
+    /// `{files}`, instead of using the default message.
+    pub commit_template: Option<PathBuf>,
+    /// Add a `Signed-off-by` trailer of the committer to the commit message.
+    pub signoff: bool,
+    /// Add these trailers, given as `key=value` or `key: value`, to the commit message.
+    pub trailers: Vec<String>,
+    /// Tag the bump commit, refusing to bump if the tag already exists.
+    pub tag: bool,
+    /// Move an already existing tag instead of refusing to bump.
+    pub force_tag: bool,
+    /// Refuse to bump if the tag already exists on this remote.
+    pub check_remote_tags: Option<String>,
+    /// Push the bump commit and the tag, if any, to `origin`.
+    pub push: bool,
+    /// Create a release on GitHub or GitLab after pushing.
+    #[cfg(feature = "release")]
+    pub create_release: bool,
+}
+
+impl BumpOptions {
+    /// Check if files are only previewed, either as diff or as their new contents.
+    pub(crate) fn is_preview(&self) -> bool {
+        self.diff.is_some() || self.stdout
+    }
+}
+
+/// Bump files to a given version.
+///
+/// Only files of the selected groups are bumped, or all files if no group is selected. If a diff
+/// mode is given or the new contents are printed to stdout, the changes are only shown and no
+/// files or hooks are touched.
+///
+/// If bumping a required file fails, the whole process is aborted. Failures of optional files are
+/// only reported as warnings and listed as skipped in the returned report.
+pub fn bump(version: String, options: &BumpOptions, settings: Settings) -> Result<BumpReport> {
+    let start = Instant::now();
+    let mut report = BumpReport {
+        version: version.clone(),
+        files: Vec::new(),
+        hooks: Vec::new(),
+        config_duration: Duration::ZERO,
+        duration: Duration::ZERO,
+    };
+
+    let worker_settings = settings.clone();
+    let mut bump_state = BumpState::new(Some(version.clone()), settings);
+
+    // Previews are harmless, so they are allowed on any branch
+    if !options.is_preview() {
+        check_branch(&mut bump_state, options.require_branch.as_deref())?;
+    }
+
+    let config_start = Instant::now();
+    let mut map = bump_state.get_file_mapping()?;
+    let mut missing_map = bump_state.get_missing_file_mapping()?;
+    let workdir = bump_state.get_workdir()?;
+
+    // Files excluded by sparse-checkout would look like typos in their keys otherwise
+    let mut sparse = git::sparse_excluded(
+        bump_state.get_repository()?.deref(),
+        &workdir,
+        &bump_state.get_missing_keys()?,
+    )?;
+    if options.materialize && !sparse.is_empty() && !options.is_preview() {
+        git::materialize(&workdir, &sparse)?;
+        bump_state = BumpState::new(Some(version.clone()), worker_settings.clone());
+        map = bump_state.get_file_mapping()?;
+        missing_map = bump_state.get_missing_file_mapping()?;
+        sparse.clear();
+    }
+    report.config_duration = config_start.elapsed();
+
+    let repository = bump_state.get_repository()?;
+    let paths = bump_state.get_paths()?;
+    for file in &sparse {
+        eprintln!(
+            "Warning: Skipping file {}, since it is not materialized by sparse-checkout, use \
+             --materialize to check it out",
+            paths.show(file)
+        );
+        report.files.push(FileReport {
+            path: file.strip_prefix(&*workdir).unwrap_or(file).to_path_buf(),
+            outcome: FileOutcome::NotMaterialized,
+            hooks: Vec::new(),
+            duration: Duration::ZERO,
+            lua_duration: Duration::ZERO,
+            bytes_written: 0,
+        });
+    }
+
+    // Files cannot be created in the index alone
+    let mut files = map
+        .deref()
+        .iter()
+        .chain(missing_map.iter().filter(|_| !options.index_only))
+        .filter(|(file, _)| !sparse.contains(file))
+        .filter(|(_, mapping)| mapping.is_selected(&options.groups))
+        .collect::<Vec<_>>();
+    files.sort_by_key(|(file, _)| *file);
+
+    if files.is_empty() {
+        nothing_to_do(&mut bump_state, options.allow_empty)?;
+        report.duration = start.elapsed();
+        return Ok(report);
+    }
+
+    if let Some(since) = &options.since {
+        let changed = git::changed_since(&repository, since)?;
+        files.retain(|(file, _)| changed.contains(file.strip_prefix(&*workdir).unwrap_or(file)));
+    }
+    let tag = Prefixes::from_config(&repository.config()?).tag(&version);
+    // Resolve the identity and signing up front, so a broken config does not leave behind
+    // uncommitted files
+    let identity = options
+        .commit
+        .then(|| git::Identity::resolve(&repository, options.identity.as_deref()))
+        .transpose()?;
+    if options.commit {
+        git::signing_program(&repository.config()?)?;
+    }
+    let (message, trailers) = match &identity {
+        Some(identity) => (
+            match options.amend {
+                true => git::head_message(&repository)?,
+                false => git::commit_message(&repository.config()?, &version)?,
+            },
+            git::trailers(&options.trailers, options.signoff.then_some(identity))?,
+        ),
+        None => Default::default(),
+    };
+    let template = options
+        .commit_template
+        .as_deref()
+        .map(git::read_commit_template)
+        .transpose()?;
+    if options.tag && !options.force_tag && git::tag_exists(&repository, &tag) {
+        return Err(Error::TagExists { tag });
+    }
+    if let Some(remote) = &options.check_remote_tags {
+        if git::remote_tag_exists(&workdir, remote, &tag)? {
+            return Err(Error::RemoteTagExists {
+                tag,
+                remote: remote.clone(),
+            });
+        }
+    }
+
+    // Fail early if the release cannot be created, before anything is committed or pushed
+    #[cfg(feature = "release")]
+    let release = if options.create_release {
+        let links = links::Links::from_origin(bump_state.get_repository()?.deref())
+            .ok_or(Error::UnknownForge)?;
+        let token = release::token(&links)?;
+        Some((links, token))
+    } else {
+        None
+    };
+
+    let untracked = if options.tracked_only {
+        let index = bump_state.get_repository()?.index()?;
+        files
+            .iter()
+            .map(|(file, _)| *file)
+            .filter(|file| file.exists() && !git::is_tracked(&index, &workdir, file))
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    let write_retries = bump_state
+        .get_repository()?
+        .config()?
+        .get_i32("bump.writeRetries")
+        .map_or(WRITE_RETRIES, |retries| {
+            retries.clamp(0, MAX_WRITE_RETRIES as i32) as u32
+        });
+    if !options.is_preview() && !options.index_only {
+        let tracked = files
+            .iter()
+            .filter(|(file, _)| file.exists() && !untracked.contains(file))
+            .copied()
+            .collect::<Vec<_>>();
+        check_writable(
+            &Disk,
+            &paths,
+            &tracked,
+            options.force_writable,
+            write_retries,
+        )?;
+    }
+
+    let lua = bump_state.get_lua()?;
+    let global_hooks = match options.is_preview() {
+        true => None,
+        false => lua.globals().get::<_, Option<LuaTable>>("hooks")?,
+    };
+
+    let index_workspace;
+    let workspace: &dyn Workspace = if options.index_only {
+        index_workspace = git::IndexWorkspace::new(&repository, &workdir)?;
+        &index_workspace
+    } else {
+        &Disk
+    };
+
+    #[cfg(feature = "tui")]
+    let files = if options.review {
+        let reviewed = review_changes(&lua, workspace, &paths, &version, &files, &untracked)?;
+        let changes = reviewed
+            .iter()
+            .map(|(_, change)| change)
+            .collect::<Vec<_>>();
+        let included = tui::review(&changes)?.ok_or(Error::ReviewAborted)?;
+
+        let excluded = reviewed
+            .iter()
+            .zip(included)
+            .filter(|(_, included)| !included)
+            .map(|((file, _), _)| file.clone())
+            .collect::<Vec<_>>();
+        files
+            .into_iter()
+            .filter(|(file, _)| !excluded.contains(file))
+            .collect()
+    } else {
+        files
+    };
+
+    let editor = if options.edit.is_some() || options.notes {
+        Some(editor::resolve(
+            bump_state
+                .get_repository()?
+                .config()?
+                .get_string("core.editor")
+                .ok(),
+        ))
+    } else {
+        None
+    };
+
+    let notes = match (&editor, options.notes) {
+        (Some(editor), true) => editor::prompt(
+            editor,
+            &bump_state.get_repository()?.path().join("BUMP_NOTES"),
+            &format!(
+                "\n# Please enter the release notes for version {}. Lines starting\n\
+                 # with '#' will be ignored, and empty notes are not passed on.\n",
+                version
+            ),
+        )?,
+        _ => None,
+    };
+    if let Some(ctx) = lua.globals().get::<_, Option<LuaTable>>("ctx")? {
+        ctx.set("notes", notes.as_deref())?;
+    }
+
+    // The index holds normalized contents, so only the worktree has converted line endings
+    let line_endings = match options.index_only {
+        true => HashMap::new(),
+        false => git::line_endings(
+            bump_state.get_repository()?.deref(),
+            &workdir,
+            files.iter().map(|(file, _)| *file),
+        ),
+    };
+
+    let bumper = Bumper {
+        lua: &lua,
+        workspace,
+        workdir: &workdir,
+        paths: &paths,
+        line_endings: &line_endings,
+        version: &version,
+        options,
+        write_retries,
+        editor,
+        preview: Default::default(),
+        written: Default::default(),
+        phase: Cell::new(""),
+        lua_duration: Default::default(),
+        bytes_written: Default::default(),
+    };
+
+    if let Some(branch) = &options.branch {
+        git::create_branch(
+            bump_state.get_repository()?.deref(),
+            &branch.replace("{version}", &version),
+            !options.no_switch,
+        )?;
+    }
+
+    if let Some(hooks) = &global_hooks {
+        report
+            .hooks
+            .extend(hooks::run_pre_hooks(hooks, &workdir, None)?);
+    }
+
+    let mut bumped = if options.jobs > 1 {
+        let tracked = files
+            .iter()
+            .map(|(file, _)| *file)
+            .filter(|file| !untracked.contains(file))
+            .collect::<Vec<_>>();
+        let pool = pool::Pool {
+            jobs: options.jobs,
+            settings: &worker_settings,
+            version: &version,
+            options,
+            notes: notes.as_deref(),
+            workdir: &workdir,
+            paths: &paths,
+            line_endings: &line_endings,
+            write_retries,
+            tmpdir: &bump_state.get_tmpdir()?,
+        };
+        pool.bump(&tracked)?
+    } else {
+        HashMap::new()
+    };
+
+    for (file, mapping) in files {
+        let file_start = Instant::now();
+        let path = file.strip_prefix(&*workdir).unwrap_or(file);
+
+        if untracked.contains(&file) {
+            eprintln!(
+                "Warning: Skipping file {}, since it is not tracked by Git",
+                paths.show(file)
+            );
+            report.files.push(FileReport {
+                path: path.to_path_buf(),
+                outcome: FileOutcome::Untracked,
+                hooks: Vec::new(),
+                duration: file_start.elapsed(),
+                lua_duration: Duration::ZERO,
+                bytes_written: 0,
+            });
+            continue;
+        }
+
+        // Results of worker threads are merged in the same order as files are bumped otherwise
+        let mut duration = None;
+        let result = match bumped.remove(file) {
+            Some(bumped) => {
+                bumper.phase.set(bumped.phase);
+                bumper.preview.borrow_mut().push_str(&bumped.preview);
+                bumper.written.borrow_mut().extend(bumped.written);
+                bumper.lua_duration.set(bumped.lua_duration);
+                bumper.bytes_written.set(bumped.bytes_written);
+                duration = Some(bumped.duration);
+                bumped.result
+            }
+            None => bumper.bump_file(file, mapping),
+        };
+
+        let (outcome, hooks) = match result {
+            Ok(result) => result,
+            Err(err) => {
+                if let Some(hooks) = &global_hooks {
+                    hooks::run_error_hook(
+                        hooks,
+                        &paths.show(path),
+                        bumper.phase.get(),
+                        &err.to_string(),
+                    );
+                }
+
+                if !mapping.optional {
+                    return Err(Error::MappingFailed {
+                        key: mapping.key.clone(),
+                        config: paths.show(&mapping.source),
+                        source: Box::new(err),
+                    });
+                }
+
+                eprintln!(
+                    "Warning: Skipping optional file {}: {}",
+                    paths.show(file),
+                    err
+                );
+
+                let outcome = FileOutcome::Skipped {
+                    phase: bumper.phase.get().to_string(),
+                    message: err.to_string(),
+                };
+                (outcome, Vec::new())
+            }
+        };
+
+        report.files.push(FileReport {
+            path: path.to_path_buf(),
+            outcome,
+            hooks,
+            duration: duration.unwrap_or_else(|| file_start.elapsed()),
+            lua_duration: bumper.lua_duration.get(),
+            bytes_written: bumper.bytes_written.get(),
+        });
+    }
+
+    report.files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    // Mappings of missing files might not create them after all
+    if report.files.iter().all(|file| {
+        matches!(
+            file.outcome,
+            FileOutcome::Missing | FileOutcome::NotMaterialized
+        )
+    }) {
+        nothing_to_do(&mut bump_state, options.allow_empty)?;
+        report.duration = start.elapsed();
+        return Ok(report);
+    }
+
+    drop(untracked);
+    drop(map);
+    drop(missing_map);
+    bump_state.release_mappings()?;
+
+    if let Some(hooks) = &global_hooks {
+        report
+            .hooks
+            .extend(hooks::run_post_hooks(hooks, &workdir, None)?);
+    }
+
+    if let Some(identity) = &identity {
+        let written = bumper.written.take();
+        let message = match &template {
+            Some(template) => {
+                let files = written
+                    .iter()
+                    .map(|file| file.strip_prefix(&*workdir).unwrap_or(file))
+                    .map(|file| file.to_string_lossy().into_owned())
+                    .collect::<Vec<_>>();
+                git::render_commit_template(template, &version, notes.as_deref(), &files)
+            }
+            None => message,
+        };
+
+        let commit = git::commit(
+            bump_state.get_repository()?.deref(),
+            identity,
+            &workdir,
+            &written,
+            &git::add_trailers(&message, &trailers),
+            // In index-only mode, the bumped files are already staged
+            !options.index_only,
+            options.amend,
+        )?;
+
+        if let Some(hooks) = &global_hooks {
+            report.hooks.extend(hooks::run_git_hook(
+                hooks,
+                &workdir,
+                "post_commit",
+                &commit.to_string(),
+            ));
+        }
+    }
+
+    if let Some(identity) = identity.as_ref().filter(|_| options.tag) {
+        git::create_tag(
+            bump_state.get_repository()?.deref(),
+            identity,
+            &tag,
+            &format!("Version {}", version),
+            options.force_tag,
+        )?;
+
+        if let Some(hooks) = &global_hooks {
+            report
+                .hooks
+                .extend(hooks::run_git_hook(hooks, &workdir, "post_tag", &tag));
+        }
+    }
+
+    if options.push {
+        let mut refs = vec![String::from("HEAD")];
+        if options.tag {
+            refs.push(format!("refs/tags/{}", tag));
+        }
+        git::push(&workdir, "origin", &refs)?;
+    }
+
+    #[cfg(feature = "release")]
+    if let Some((links, token)) = release {
+        let notes = match release_notes(&mut bump_state, &version, None) {
+            Ok(notes) => notes,
+            Err(err) => {
+                eprintln!("Warning: Creating release without notes: {}", err);
+                String::new()
+            }
+        };
+        release::create(&links, token, &tag, &version, &notes)?;
+    }
+
+    if options.stdout {
+        print!("{}", bumper.preview.take());
+    } else if options.diff.is_some() {
+        let preview = bumper.preview.take();
+        if options.no_pager {
+            print!("{}", preview);
+        } else {
+            let core_pager = bump_state
+                .get_repository()?
+                .config()?
+                .get_string("core.pager")
+                .ok();
+            pager::page(&preview, core_pager)?;
+        }
+    }
+
+    if !options.is_preview() {
+        let files = report
+            .files
+            .iter()
+            .filter(|file| matches!(file.outcome, FileOutcome::Written | FileOutcome::Unchanged))
+            .map(|file| file.path.to_string_lossy().into_owned())
+            .collect();
+        let last_bump = LastBump::now(&version, files);
+        if let Err(err) = last_bump.save(bump_state.get_repository()?.path()) {
+            eprintln!("Warning: Failed to save state of the bump: {}", err);
+        }
+    }
+
+    if report.skipped() > 0 {
+        eprintln!(
+            "Warning: {} optional file(s) could not be bumped",
+            report.skipped()
+        );
+    }
+
+    report.duration = start.elapsed();
+
+    Ok(report)
+}
+
+/// Fail because there are no files to bump, unless empty bumps are allowed.
+///
+/// The error tells missing config files apart from mappings that do not match any files.
+fn nothing_to_do(bump_state: &mut BumpState, allow_empty: bool) -> Result<()> {
+    let err = match bump_state.get_config_files()?.len() {
+        0 => Error::NoConfig,
+        count => Error::NothingToDo {
+            count,
+            missing: bump_state.get_missing_keys()?,
+        },
+    };
+    if !allow_empty {
+        return Err(err);
+    }
+
+    eprintln!("Info: {}", err);
+    Ok(())
+}
+
+/// Compute changes of the given files for reviewing them.
+///
+/// Disabled and untracked files are left out, just like optional files that fail, since they are
+/// skipped anyway.
+#[cfg(feature = "tui")]
+fn review_changes(
+    lua: &Lua,
+    workspace: &dyn Workspace,
+    paths: &PathDisplay,
+    version: &str,
+    files: &[(&PathBuf, &Mapping)],
+    untracked: &[&PathBuf],
+) -> Result<Vec<(PathBuf, engine::Change)>> {
+    let mut changes = Vec::new();
+
+    for (file, mapping) in files.iter().filter(|(file, _)| !untracked.contains(file)) {
+        let change = engine::is_enabled(lua, mapping, version)
+            .and_then(|enabled| {
+                if !enabled {
+                    return Ok(None);
+                }
+                let old = engine::read(workspace, mapping, file)
+                    .map_err(|source| Error::ReadFailed { source })?;
+                let (new, returned) = engine::apply(lua, mapping, version, &old)?;
+                Ok((!returned.skip).then_some((old, new)))
+            })
+            .or_else(|err| match mapping.optional {
+                true => Ok(None),
+                false => Err(Error::MappingFailed {
+                    key: mapping.key.clone(),
+                    config: paths.show(&mapping.source),
+                    source: Box::new(err),
+                }),
+            })?;
+
+        if let Some((old, new)) = change {
+            changes.push((
+                file.to_path_buf(),
+                engine::Change {
+                    file: paths.show(file),
+                    old: String::from_utf8_lossy(&old).into_owned(),
+                    new: String::from_utf8_lossy(&new).into_owned(),
+                },
+            ));
+        }
+    }
+
+    Ok(changes)
+}
+
+/// Check that the required branch is checked out, as given or configured with `bump.requireBranch`.
+fn check_branch(bump_state: &mut BumpState, required: Option<&str>) -> Result<()> {
+    let required = match required {
+        Some(required) => required.to_string(),
+        None => match bump_state
+            .get_repository()?
+            .config()?
+            .get_string("bump.requireBranch")
+        {
+            Ok(required) => required,
+            Err(_) => return Ok(()),
+        },
+    };
+    if required.is_empty() {
+        return Ok(());
+    }
+
+    match bump_state.get_branch()? {
+        Some(branch) if branch == required => Ok(()),
+        branch => Err(Error::WrongBranch {
+            required,
+            branch: branch.unwrap_or_else(|| String::from("a detached HEAD")),
+        }),
+    }
+}
+
+/// Check that all required files can be written before touching any of them.
+///
+/// All problems are reported at once, so they can be fixed in one go instead of leaving the
+/// repository half-bumped. Optional files are left out, since they are skipped on failure anyway.
+/// Read-only files are fine if they are to be made writable anyway. Locked files are retried just
+/// like when writing them, but all together, so that they share a single backoff.
+fn check_writable(
+    workspace: &dyn Workspace,
+    paths: &PathDisplay,
+    files: &[(&PathBuf, &Mapping)],
+    force_writable: bool,
+    retries: u32,
+) -> Result<()> {
+    let mut locked = files
+        .iter()
+        .filter(|(_, mapping)| !mapping.optional)
+        .map(|(file, _)| *file)
+        .collect::<Vec<_>>();
+    let mut failed = Vec::new();
+
+    let backoff = Backoff {
+        retries,
+        delay: LOCK_RETRY_DELAY,
+    };
+    let _ = backoff.run(
+        || {
+            locked.retain(|file| match workspace.check_writable(file) {
+                Ok(()) => false,
+                Err(err) if is_locked(&err) => true,
+                Err(err) => {
+                    failed.push((*file, err));
+                    false
+                }
+            });
+            match locked.len() {
+                0 => Ok(()),
+                count => Err(count),
+            }
+        },
+        |_| true,
+        |attempt, delay, count| {
+            eprintln!(
+                "Warning: {} file(s) locked by another process on attempt {} of {}, retrying in {:?}",
+                count,
+                attempt,
+                retries + 1,
+                delay
+            );
+        },
+    );
+
+    let mut count = 0;
+    for (file, err) in failed {
+        let read_only = std::fs::metadata(file)
+            .map(|metadata| metadata.permissions().readonly())
+            .unwrap_or(false);
+
+        let reason = if read_only && force_writable {
+            continue;
+        } else if read_only {
+            String::from("the file is read-only, make it writable or use --force-writable")
+        } else {
+            err.to_string()
+        };
+        eprintln!("{}: Not writable: {}", paths.show(file), reason);
+        count += 1;
+    }
+    for file in locked {
+        eprintln!(
+            "{}: Not writable: locked by another process, close the programs using it and try again",
+            paths.show(file)
+        );
+        count += 1;
+    }
+
+    if count > 0 {
+        return Err(Error::NotWritable { count });
+    }
+
+    Ok(())
+}
+
+/// Run a file operation, retrying while another program has the file locked.
+///
+/// IDEs and virus scanners on Windows briefly lock files they look at, so operations failing that
+/// way are retried up to the given number of times, starting with a delay of
+/// [`LOCK_RETRY_DELAY`]. Other errors fail right away.
+fn retry_locked<T>(
+    file: &str,
+    retries: u32,
+    operation: impl FnMut() -> std::io::Result<T>,
+) -> std::io::Result<T> {
+    let backoff = Backoff {
+        retries,
+        delay: LOCK_RETRY_DELAY,
+    };
+    let (value, _) = backoff.run(operation, is_locked, |attempt, delay, _| {
+        eprintln!(
+            "Warning: {} is locked by another process on attempt {} of {}, retrying in {:?}",
+            file,
+            attempt,
+            retries + 1,
+            delay
+        );
+    })?;
+    Ok(value)
+}
+
+/// Check if a file operation failed since another program has the file locked.
+///
+/// This is the case for sharing and lock violations on Windows, where programs can open files
+/// exclusively.
+fn is_locked(err: &std::io::Error) -> bool {
+    cfg!(windows) && matches!(err.raw_os_error(), Some(32 | 33))
+}
+
+/// Read-only file that was made writable for bumping it, and is made read-only again when dropped.
+struct Writable {
+    file: PathBuf,
+    /// File as shown in messages.
+    shown: String,
+}
+
+impl Writable {
+    /// Make a file writable for its owner, if it is read-only.
+    ///
+    /// Returns `None` if the file is not read-only, so there is nothing to restore.
+    fn make(file: &Path, paths: &PathDisplay) -> std::io::Result<Option<Self>> {
+        let mut permissions = std::fs::metadata(file)?.permissions();
+        if !permissions.readonly() {
+            return Ok(None);
+        }
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            permissions.set_mode(permissions.mode() | 0o200);
+        }
+        #[cfg(not(unix))]
+        {
+            #[allow(clippy::permissions_set_readonly_false)]
+            permissions.set_readonly(false);
+        }
+        std::fs::set_permissions(file, permissions)?;
+
+        Ok(Some(Writable {
+            file: file.to_path_buf(),
+            shown: paths.show(file),
+        }))
+    }
+}
+
+impl Drop for Writable {
+    fn drop(&mut self) {
+        // Only the write permissions are taken away again, any mode set while bumping is kept
+        let result = std::fs::metadata(&self.file).and_then(|metadata| {
+            let mut permissions = metadata.permissions();
+            permissions.set_readonly(true);
+            std::fs::set_permissions(&self.file, permissions)
+        });
+        if let Err(err) = result {
+            eprintln!(
+                "Warning: Failed to make {} read-only again: {}",
+                self.shown, err
+            );
+        }
+    }
+}
+
+/// Set the Unix permissions of a written file.
+///
+/// Other platforms do not have such permissions, so the mode is ignored there.
+fn set_mode(file: &Path, mode: u32) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(file, std::fs::Permissions::from_mode(mode))
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = (file, mode);
+        Ok(())
+    }
+}
+
+/// Shared context for bumping single files.
+struct Bumper<'a> {
+    lua: &'a Lua,
+    workspace: &'a dyn Workspace,
+    workdir: &'a Path,
+    paths: &'a PathDisplay,
+    /// Line endings of files whose line endings are converted by Git.
+    line_endings: &'a HashMap<PathBuf, LineEnding>,
+    version: &'a str,
+    options: &'a BumpOptions,
+    /// Number of retries for writes that fail since another program has the file locked.
+    write_retries: u32,
+    editor: Option<String>,
+    preview: RefCell<String>,
+    written: RefCell<Vec<PathBuf>>,
+    /// Phase of the file that is currently bumped, reported to the `on_error` hook.
+    phase: Cell<&'static str>,
+    /// Time taken by the Lua functions of the file that is currently bumped.
+    lua_duration: Cell<Duration>,
+    /// Number of bytes written to the file that is currently bumped.
+    bytes_written: Cell<usize>,
+}
+
+impl Bumper<'_> {
+    /// Run Lua functions of a mapping, adding the time taken to that of the current file.
+    fn timed<T>(&self, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = f();
+        self.lua_duration
+            .set(self.lua_duration.get() + start.elapsed());
+        result
+    }
+
+    /// Write new contents of a file, retrying while another program has it locked.
+    fn write(&self, file: &Path, contents: &[u8]) -> Result<()> {
+        retry_locked(&self.paths.show(file), self.write_retries, || {
+            self.workspace.write_bytes(file, contents)
+        })
+        .map_err(|source| Error::WriteFailed { source })
+    }
+
+    /// Check if a written file should be opened in the editor.
+    fn should_edit(&self, file: &Path) -> bool {
+        match &self.options.edit {
+            Some(files) if !files.is_empty() => {
+                let path = file.strip_prefix(self.workdir).unwrap_or(file);
+                files.iter().any(|edit| edit == path)
+            }
+            Some(_) => true,
+            None => false,
+        }
+    }
+
+    /// Bump a single file.
+    ///
+    /// Returns the outcome along with reports of the hooks of the file.
+    fn bump_file(&self, file: &Path, mapping: &Mapping) -> Result<(FileOutcome, Vec<HookReport>)> {
+        self.lua_duration.set(Duration::ZERO);
+        self.bytes_written.set(0);
+
+        self.phase.set("enabled");
+        if !self.timed(|| engine::is_enabled(self.lua, mapping, self.version))? {
+            return Ok((FileOutcome::Disabled, Vec::new()));
+        }
+
+        self.phase.set("read");
+        // Previews always show the old contents, otherwise they are only read if needed. Missing
+        // files only have mappings that do not need their contents.
+        let exists = self.workspace.exists(file);
+        let old_contents = if exists && (mapping.needs_content || self.options.diff.is_some()) {
+            engine::read(self.workspace, mapping, file)
+                .map(Some)
+                .map_err(|source| Error::ReadFailed { source })?
+        } else {
+            None
+        };
+
+        self.phase.set("func");
+        let (contents, returned) = self.timed(|| {
+            engine::apply(
+                self.lua,
+                mapping,
+                self.version,
+                old_contents.as_deref().unwrap_or_default(),
+            )
+        })?;
+        if returned.skip {
+            return Ok((FileOutcome::Disabled, Vec::new()));
+        }
+        if !exists && !returned.create {
+            return Ok((FileOutcome::Missing, Vec::new()));
+        }
+        // Keep the line endings Git checks the file out with, so it is not modified as a whole,
+        // unless it is written verbatim
+        let contents = match self.line_endings.get(file).filter(|_| !mapping.raw) {
+            Some(line_ending) => line_ending.apply(&contents),
+            None => contents,
+        };
+
+        if let Some(mode) = self.options.diff {
+            let old_contents = old_contents.unwrap_or_default();
+            let color = std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+            self.preview.borrow_mut().push_str(&diff::render(
+                &self.paths.show(file),
+                &String::from_utf8_lossy(&old_contents),
+                &String::from_utf8_lossy(&contents),
+                mode,
+                color,
+            ));
+
+            let outcome = if contents == old_contents {
+                FileOutcome::Unchanged
+            } else {
+                FileOutcome::Previewed
+            };
+            return Ok((outcome, Vec::new()));
+        }
+
+        if self.options.stdout {
+            // Keep the next header on a line of its own
+            let newline = if contents.ends_with(b"\n") { "" } else { "\n" };
+            self.preview.borrow_mut().push_str(&format!(
+                "==> {} <==\n{}{}",
+                self.paths.show(file),
+                String::from_utf8_lossy(&contents),
+                newline
+            ));
+
+            let outcome = if old_contents.as_ref() == Some(&contents) {
+                FileOutcome::Unchanged
+            } else {
+                FileOutcome::Previewed
+            };
+            return Ok((outcome, Vec::new()));
+        }
+
+        let outcome = if old_contents.as_ref() == Some(&contents) {
+            FileOutcome::Unchanged
+        } else {
+            FileOutcome::Written
+        };
+        let mut hook_reports = Vec::new();
+
+        for hooks in &returned.hooks {
+            self.phase.set("pre");
+            hook_reports.extend(hooks::run_pre_hooks(hooks, self.workdir, Some(file))?);
+        }
+
+        self.phase.set("write");
+        // Pre hooks, editors, or other processes might have changed the file in the meantime, so
+        // only overwrite it if it still has the contents the new ones are based on
+        if let Some(old_contents) = &old_contents {
+            let current_contents = engine::read(self.workspace, mapping, file)
+                .map_err(|source| Error::ReadFailed { source })?;
+            if current_contents != *old_contents {
+                return Err(Error::ModifiedConcurrently {
+                    file: self.paths.show(file),
+                });
+            }
+        }
+        if !exists {
+            if let Some(dir) = file.parent() {
+                std::fs::create_dir_all(dir).map_err(|source| Error::WriteFailed { source })?;
+            }
+        }
+        let writable = match self.options.force_writable && exists && !self.options.index_only {
+            true => {
+                Writable::make(file, self.paths).map_err(|source| Error::WriteFailed { source })?
+            }
+            false => None,
+        };
+        self.write(file, &contents)?;
+        self.written.borrow_mut().push(file.to_path_buf());
+        self.bytes_written.set(contents.len());
+        if let Some(mode) = returned.mode.filter(|_| !self.options.index_only) {
+            set_mode(file, mode).map_err(|source| Error::WriteFailed { source })?;
+        }
+
+        if let Some(formatter) = mapping.format.as_ref().filter(|_| !self.options.index_only) {
+            self.phase.set("format");
+            if let Err(err) = formatter.run(file) {
+                if formatter.on_failure == FormatFailure::Error {
+                    return Err(err);
+                }
+                if formatter.on_failure == FormatFailure::Warn {
+                    eprintln!(
+                        "Warning: Keeping {} unformatted: {}",
+                        self.paths.show(file),
+                        err
+                    );
+                }
+                // The formatter might have left the file half done
+                self.write(file, &contents)?;
+            }
+        }
+
+        if let Some(editor) = &self.editor {
+            if self.should_edit(file) && !self.options.index_only {
+                self.phase.set("edit");
+                editor::edit(editor, file)?;
+            }
+        }
+        drop(writable);
+
+        for hooks in &returned.hooks {
+            self.phase.set("lockfile");
+            if let Some(lockfile) = Lockfile::from_lua(hooks.get("lockfile")?, file)?
+                .filter(|_| !self.options.index_only)
+            {
+                lockfile.refresh(file, self.version)?;
+                self.written
+                    .borrow_mut()
+                    .extend(lockfile.find(file, self.workdir));
+            }
+
+            self.phase.set("post");
+            hook_reports.extend(hooks::run_post_hooks(hooks, self.workdir, Some(file))?);
+        }
+
+        Ok((outcome, hook_reports))
+    }
+}
+
+/// Derive version from the nearest tag, as given by `git describe --tags`.
+///
+/// A leading `v` of the tag name is removed, so a tag `v1.2.3` results in versions like `1.2.3`
+/// on the tagged commit itself and `1.2.3-5-gabcdef1` five commits later.
+fn describe_version() -> Result<String> {
+    let prefixes = prefixes()?;
+    Ok(prefixes.canonical(&describe(&prefixes, None)?))
+}
+
+/// Compute next version by incrementing the version of the nearest tag.
+///
+/// The pre-release identifiers and build metadata are set afterwards, if given.
+fn next_version(increment: Increment, pre: Option<&str>, build: Option<&str>) -> Result<String> {
+    let prefixes = prefixes()?;
+    let current = describe(
+        &prefixes,
+        Some(DescribeFormatOptions::new().abbreviated_size(0)),
+    )?;
+    let invalid = |version: &str| Error::InvalidVersion {
+        version: version.to_string(),
+    };
+
+    let mut version = SemVer::parse(&current)
+        .ok_or_else(|| invalid(&current))?
+        .increment(increment);
+    if let Some(pre) = pre {
+        version = version.with_pre(pre).ok_or_else(|| invalid(pre))?;
+    }
+    if let Some(build) = build {
+        version = version.with_build(build).ok_or_else(|| invalid(build))?;
+    }
+
+    Ok(prefixes.canonical(&version.to_string()))
+}
+
+/// Bring a version given by the user into the canonical form that is given to the config files.
+fn canonical_version(version: &str) -> Result<String> {
+    Ok(prefixes()?.canonical(version))
+}
+
+/// Get prefixes of versions and tags as configured for the current repository.
+fn prefixes() -> Result<Prefixes> {
+    let repository = BumpState::default().get_repository()?;
+    let config = repository.config()?;
+    Ok(Prefixes::from_config(&config))
+}
+
+/// Describe `HEAD` by the nearest tag, without any prefix.
+fn describe(prefixes: &Prefixes, format: Option<&DescribeFormatOptions>) -> Result<String> {
+    let repository = BumpState::default().get_repository()?;
+
+    let described = repository
+        .describe(DescribeOptions::new().describe_tags())?
+        .format(format)?;
+
+    Ok(prefixes.bare(&described).to_string())
+}
+
+/// Check that the given files are consistent with a version, for use in Git hooks.
+///
+/// Every given file that has a mapping is bumped in memory, without running any hooks. If the
+/// contents would change, the file has drifted from the expected version, which is the version of
+/// the nearest tag if not given explicitly. Files without mappings are ignored.
+fn hook_check(files: &[PathBuf], expected: Option<String>, settings: Settings) -> Result<()> {
+    let prefixes = prefixes()?;
+    let version = match expected {
+        Some(version) => prefixes.canonical(&version),
+        None => prefixes.canonical(&describe(
+            &prefixes,
+            Some(DescribeFormatOptions::new().abbreviated_size(0)),
+        )?),
+    };
+
+    let mut bump_state = BumpState::new(Some(version.clone()), settings);
+    let map = bump_state.get_file_mapping()?;
+    let workdir = bump_state.get_workdir()?;
+    let lua = bump_state.get_lua()?;
+    let repository = bump_state.get_repository()?;
+    let paths = bump_state.get_paths()?;
+
+    let mappings = map
+        .deref()
+        .iter()
+        .filter_map(|(file, mapping)| Some((file.canonicalize().ok()?, (file, mapping))))
+        .collect::<HashMap<_, _>>();
+
+    let mut drifted = Vec::new();
+    for file in files {
+        let (file, mapping) = match file
+            .canonicalize()
+            .ok()
+            .and_then(|file| mappings.get(&file))
+        {
+            Some(entry) => entry,
+            None => continue,
+        };
+
+        if is_drifted(&lua, &repository, &workdir, mapping, &version, file)? {
+            eprintln!(
+                "{}: Version drift, expected version {}",
+                paths.show(file),
+                version
+            );
+            drifted.push(paths.show(file));
+        }
+    }
+
+    if !drifted.is_empty() {
+        eprintln!(
+            "Run `git bump --diff {}` to see the differences, and `git bump {}` to fix them",
+            version, version
+        );
+        return Err(Error::VersionDrift {
+            count: drifted.len(),
+            version,
+        });
+    }
+
+    Ok(())
+}
+
+/// Check that all mapped files already have exactly the contents bumping to a version would give.
+///
+/// Unlike the hook check, every mapping is evaluated, files are compared byte for byte, and files
+/// that would be created are reported as missing. Fails if any file differs or is missing.
+fn verify_files(version: &str, settings: Settings) -> Result<()> {
+    let version = prefixes()?.canonical(version);
+
+    let mut bump_state = BumpState::new(Some(version.clone()), settings);
+    let map = bump_state.get_file_mapping()?;
+    let missing_map = bump_state.get_missing_file_mapping()?;
+    let workdir = bump_state.get_workdir()?;
+    let lua = bump_state.get_lua()?;
+    let repository = bump_state.get_repository()?;
+    let paths = bump_state.get_paths()?;
+
+    let mut files = map.iter().chain(missing_map.iter()).collect::<Vec<_>>();
+    files.sort_by_key(|(file, _)| *file);
+
+    let mut failed = 0;
+    for (file, mapping) in files {
+        if !engine::is_enabled(&lua, mapping, &version)? {
+            continue;
+        }
+
+        let exists = Disk.exists(file);
+        let old_contents = match exists {
+            true => {
+                engine::read(&Disk, mapping, file).map_err(|source| Error::ReadFailed { source })?
+            }
+            false => Vec::new(),
+        };
+        let (contents, returned) = engine::apply(&lua, mapping, &version, &old_contents)?;
+        let line_ending = LineEnding::of(&repository, &workdir, file).filter(|_| !mapping.raw);
+        let contents = match line_ending {
+            Some(line_ending) => line_ending.apply(&contents),
+            None => contents,
+        };
+
+        // The formatter only runs on written files, so formatted contents cannot be compared
+        let status = match (exists, returned.create) {
+            _ if returned.skip => continue,
+            (false, false) => continue,
+            (false, true) => "missing",
+            (true, _) if mapping.format.is_some() => "formatted",
+            (true, _) if contents != old_contents => "differs",
+            (true, _) => "ok",
+        };
+        if !matches!(status, "ok" | "formatted") {
+            failed += 1;
+        }
+        println!("    {:<10}{}", status, paths.show(file));
+    }
+
+    if failed > 0 {
+        eprintln!(
+            "\nRun `git bump --diff {}` to see the differences, and `git bump {}` to fix them",
+            version, version
+        );
+        return Err(Error::VersionDrift {
+            count: failed,
+            version,
+        });
+    }
+
+    Ok(())
+}
+
+/// Replace the previous version in all tracked text files, after showing where it occurs.
+///
+/// The previous version defaults to the version of the nearest tag. Unless confirmed up front,
+/// nothing is replaced without asking.
+fn replace_everywhere(
+    version: &str,
+    previous: Option<String>,
+    include: &[String],
+    exclude: &[String],
+    yes: bool,
+    settings: Settings,
+) -> Result<()> {
+    let prefixes = prefixes()?;
+    let version = prefixes.canonical(version);
+    let previous = match previous {
+        Some(previous) => prefixes.canonical(&previous),
+        None => prefixes.canonical(&describe(
+            &prefixes,
+            Some(DescribeFormatOptions::new().abbreviated_size(0)),
+        )?),
+    };
+
+    let mut bump_state = BumpState::new(None, settings);
+    let repository = bump_state.get_repository()?;
+    let workdir = bump_state.get_workdir()?;
+    let paths = bump_state.get_paths()?;
+
+    let found = replace::search(&repository, &workdir, &previous, include, exclude)?;
+    if found.is_empty() {
+        eprintln!("No occurrences of {} found", previous);
+        return Ok(());
+    }
+    for file in &found {
+        for (number, line) in &file.lines {
+            println!("{}:{}: {}", paths.show(&file.file), number, line.trim());
+        }
+    }
+
+    let count = found.iter().map(|file| file.count).sum::<usize>();
+    let question = format!(
+        "Replace {} occurrence(s) of {} in {} file(s) with {}?",
+        count,
+        previous,
+        found.len(),
+        version
+    );
+    if !yes && !replace::confirm(&question)? {
+        eprintln!("Nothing was replaced");
+        return Ok(());
+    }
+
+    for file in &found {
+        std::fs::write(
+            workdir.join(&file.file),
+            replace::replace(&file.contents, &previous, &version),
+        )
+        .map_err(|source| Error::WriteFailed { source })?;
+    }
+    eprintln!(
+        "Replaced {} occurrence(s) in {} file(s)",
+        count,
+        found.len()
+    );
+
+    Ok(())
+}
+
+/// Check the environment and print the results.
+///
+/// Fails if any check failed, while warnings are only printed.
+fn run_doctor(settings: Settings) -> Result<()> {
+    let checks = doctor::diagnose(settings);
+    for check in &checks {
+        println!("{}", check);
+    }
+
+    let count = checks
+        .iter()
+        .filter(|check| check.status == doctor::Status::Fail)
+        .count();
+    if count > 0 {
+        return Err(Error::DoctorFailed { count });
+    }
+
+    Ok(())
+}
+
+/// Print operations reaching outside of the worktree that the config files would attempt.
+fn audit_configs(settings: Settings) -> Result<()> {
+    let mut bump_state = BumpState::new(None, settings);
+    let operations = audit::audit(&mut bump_state)?;
+
+    for operation in &operations {
+        println!("{}", operation);
+    }
+    if operations.is_empty() {
+        eprintln!("No operations outside of the worktree found");
+    }
+
+    Ok(())
+}
+
+/// Check config files and print the findings.
+///
+/// Fails if there are errors, or warnings if they are denied.
+fn lint_configs(deny_warnings: bool, settings: Settings) -> Result<()> {
+    let mut bump_state = BumpState::new(None, settings);
+    let findings = lint::lint(&mut bump_state)?;
+
+    for finding in &findings {
+        eprintln!("{}", finding);
+    }
+
+    let count = |severity| {
+        findings
+            .iter()
+            .filter(|finding| finding.severity == severity)
+            .count()
+    };
+    let (errors, warnings) = (count(Severity::Error), count(Severity::Warning));
+    if errors > 0 || (deny_warnings && warnings > 0) {
+        return Err(Error::LintFailed { errors, warnings });
+    }
+
+    Ok(())
+}
+
+/// Check if a file has drifted from a version, that is, if bumping it would change its contents.
+///
+/// Files of disabled mappings never drift, and neither do files with a formatter, since it only
+/// runs on written files. Just like when bumping, the line endings of the worktree are applied
+/// before comparing, unless the mapping is raw.
+fn is_drifted(
+    lua: &Lua,
+    repository: &Repository,
+    workdir: &Path,
+    mapping: &Mapping,
+    version: &str,
+    file: &Path,
+) -> Result<bool> {
+    if mapping.format.is_some() || !engine::is_enabled(lua, mapping, version)? {
+        return Ok(false);
+    }
+
+    let old_contents =
+        engine::read(&Disk, mapping, file).map_err(|source| Error::ReadFailed { source })?;
+    let (contents, returned) = engine::apply(lua, mapping, version, &old_contents)?;
+    let contents = match LineEnding::of(repository, workdir, file).filter(|_| !mapping.raw) {
+        Some(line_ending) => line_ending.apply(&contents),
+        None => contents,
+    };
+
+    Ok(!returned.skip && contents != old_contents)
+}
+
+/// Print metadata of the last bump, and whether its files have drifted from its version since.
+///
+/// Fails if any file has drifted, just like the hook check.
+fn print_status(settings: Settings) -> Result<()> {
+    let repository = BumpState::default().get_repository()?;
+    let last_bump = match LastBump::load(repository.path()) {
+        Some(last_bump) => last_bump,
+        None => {
+            println!("No bump recorded yet");
+            return Ok(());
+        }
+    };
+
+    println!(
+        "Last bump to {} on {}\n",
+        last_bump.version,
+        format_timestamp(last_bump.timestamp)
+    );
+
+    let mut bump_state = BumpState::new(Some(last_bump.version.clone()), settings);
+    let map = bump_state.get_file_mapping()?;
+    let workdir = bump_state.get_workdir()?;
+    let lua = bump_state.get_lua()?;
+
+    let mut drifted = 0;
+    for file in &last_bump.files {
+        let path = workdir.join(file);
+        let status = match map.get(&path) {
+            _ if !path.exists() => "missing",
+            None => "unmapped",
+            Some(mapping)
+                if is_drifted(
+                    &lua,
+                    &repository,
+                    &workdir,
+                    mapping,
+                    &last_bump.version,
+                    &path,
+                )? =>
+            {
+                drifted += 1;
+                "drifted"
+            }
+            Some(_) => "ok",
+        };
+        println!("    {:<10}{}", status, file);
+    }
+
+    if drifted > 0 {
+        return Err(Error::VersionDrift {
+            count: drifted,
+            version: last_bump.version,
+        });
+    }
+
+    Ok(())
+}
+
+/// Print candidates for the version to bump to, for shell completions.
+///
+/// The candidates are the next patch, minor, and major versions after the nearest tag, followed by
+/// the versions of all existing tags.
+fn complete_versions() -> Result<()> {
+    let prefixes = prefixes()?;
+    let mut candidates = Vec::new();
+
+    let current = describe(
+        &prefixes,
+        Some(DescribeFormatOptions::new().abbreviated_size(0)),
+    )
+    .ok()
+    .and_then(|current| SemVer::parse(&current));
+    if let Some(current) = current {
+        for increment in [Increment::Patch, Increment::Minor, Increment::Major] {
+            candidates.push(current.clone().increment(increment).to_string());
+        }
+    }
+
+    let repository = BumpState::default().get_repository()?;
+    let tags = repository.tag_names(None)?;
+    for tag in tags.iter().flatten() {
+        let version = prefixes.bare(tag);
+        if prefixes.tag(version) == tag && SemVer::parse(version).is_some() {
+            candidates.push(version.to_string());
+        }
+    }
+
+    let mut seen = HashSet::new();
+    for candidate in candidates {
+        let candidate = prefixes.canonical(&candidate);
+        if seen.insert(candidate.clone()) {
+            println!("{}", candidate);
+        }
+    }
+
+    Ok(())
+}
+
+/// Print release notes of a version, extracted from the changelog.
+fn print_notes(version: &str, changelog: Option<PathBuf>, settings: Settings) -> Result<()> {
+    let notes = release_notes(&mut BumpState::new(None, settings), version, changelog)?;
+    println!("{}", notes);
+    Ok(())
+}
+
+/// Extract release notes of a version from the changelog.
+///
+/// The changelog is taken from `bump.changelog` in the Git config if not given explicitly,
+/// defaulting to `CHANGELOG.md` in the root of the worktree.
+fn release_notes(
+    bump_state: &mut BumpState,
+    version: &str,
+    changelog: Option<PathBuf>,
+) -> Result<String> {
+    let repository = bump_state.get_repository()?;
+    let config = repository.config()?;
+
+    let changelog = match changelog {
+        Some(changelog) => std::env::current_dir()?.join(changelog),
+        None => bump_state.get_workdir()?.join(
+            config
+                .get_path("bump.changelog")
+                .unwrap_or_else(|_| PathBuf::from("CHANGELOG.md")),
+        ),
+    };
+
+    let contents =
+        std::fs::read_to_string(&changelog).map_err(|source| Error::ReadFailed { source })?;
+
+    match notes::extract(&contents, version, &Prefixes::from_config(&config)) {
+        Some(notes) => Ok(notes),
+        None => Err(Error::NotesNotFound {
+            version: version.to_string(),
+            changelog: bump_state.get_paths()?.show(&changelog),
+        }),
+    }
+}
+
+/// Print file paths that would be bumped.
+///
+/// In the long format, the description of each file and the config file that defined it are
+/// printed alongside the path.
+fn list_files(groups: &[String], long: bool, settings: Settings) -> Result<()> {
+    let mut bump_state = BumpState::new(None, settings);
+
+    let map = bump_state.get_file_mapping()?;
+    let paths = bump_state.get_paths()?;
+
+    let mut entries = map
+        .deref()
+        .iter()
+        .filter(|(_, mapping)| mapping.is_selected(groups))
+        .map(|(file, mapping)| (paths.show(file), mapping))
+        .collect::<Vec<_>>();
+    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let width = entries
+        .iter()
+        .map(|(file, _)| file.len())
+        .max()
+        .unwrap_or(0);
+
+    for (file, mapping) in entries {
+        if long {
+            println!(
+                "{:width$}  {}({})",
+                file,
+                mapping
+                    .description
+                    .as_ref()
+                    .map(|description| format!("{} ", description))
+                    .unwrap_or_default(),
+                paths.show(&mapping.source),
+                width = width
+            );
+        } else {
+            println!("{}", file);
+        }
+    }
+
+    Ok(())
+}
+
+/// Number of retries for writes of locked files, unless configured with `bump.writeRetries`.
+const WRITE_RETRIES: u32 = 5;
+
+/// Upper bound of `bump.writeRetries`, which adds up to almost two minutes of waiting.
+const MAX_WRITE_RETRIES: u32 = 10;
+
+/// Delay before the first retry of an operation on a locked file.
+const LOCK_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// Sample `git-bump.lua` with several ready-to-use recipes.
+const SAMPLE_CONFIG: &str = include_str!("../.git-bump.lua");
+
+/// Create `.git-bump.lua` in the root of the worktree.
+///
+/// The config is either the sample config, of all or of a single ecosystem, or generated from the
+/// files detected in the worktree.
+fn init_config(detect: bool, ecosystem: Option<Ecosystem>, settings: Settings) -> Result<()> {
+    let mut bump_state = BumpState::new(None, settings);
+    let workdir = bump_state.get_workdir()?;
+    let config = match (detect, ecosystem) {
+        (_, Some(ecosystem)) => sample::sample_config(ecosystem),
+        (true, None) => match init::detect_config(&workdir) {
+            Some((files, config)) => {
+                println!("Detected {}", files.join(", "));
+                config
+            }
+            None => {
+                eprintln!("Warning: No known files detected, using the sample config");
+                SAMPLE_CONFIG.to_string()
+            }
+        },
+        (false, None) => SAMPLE_CONFIG.to_string(),
+    };
+
+    write_config(&workdir, &*bump_state.get_paths()?, &config)
+}
+
+/// Create `.git-bump.lua` in the root of the worktree from the config of another tool.
+fn import_config(tool: import::Tool, settings: Settings) -> Result<()> {
+    let mut bump_state = BumpState::new(None, settings);
+    let workdir = bump_state.get_workdir()?;
+    let import = import::import(tool, &workdir)?;
+
+    println!("Imported {}", import.source);
+    for warning in &import.warnings {
+        eprintln!("Warning: Not converted: {}", warning);
+    }
+
+    write_config(&workdir, &*bump_state.get_paths()?, &import.config)
+}
+
+/// Write `.git-bump.lua` in the root of the worktree, unless it already exists.
+fn write_config(workdir: &Path, paths: &PathDisplay, config: &str) -> Result<()> {
+    let path = workdir.join(".git-bump.lua");
+    if path.exists() {
+        return Err(Error::ConfigExists {
+            file: paths.show(&path),
+        });
+    }
+
+    std::fs::write(&path, config).map_err(|source| Error::WriteFailed { source })?;
+    println!("Created {}", paths.show(&path));
+
+    Ok(())
+}
+
+/// Print sample `git-bump.lua`, either with all recipes or only with those of an ecosystem.
+fn print_sample_config(ecosystem: Option<Ecosystem>) {
+    match ecosystem {
+        Some(ecosystem) => print!("{}", sample::sample_config(ecosystem)),
+        None => println!("{}", SAMPLE_CONFIG),
+    }
+}
@@ -0,0 +1,103 @@
+use git2::Repository;
+
+use crate::prefix::Prefixes;
+
+/// Web links of a repository on a forge like GitHub or GitLab.
+pub(crate) struct Links {
+    /// Host name of the forge, like `github.com`.
+    pub(crate) host: String,
+    /// Path of the repository on the forge, like `owner/repo`.
+    pub(crate) path: String,
+    /// Whether the forge is a GitLab instance, which uses different URLs.
+    pub(crate) gitlab: bool,
+    prefixes: Prefixes,
+}
+
+impl Links {
+    /// Derive links from the `origin` remote of a repository, if any.
+    pub(crate) fn from_origin(repository: &Repository) -> Option<Self> {
+        let remote = repository.find_remote("origin").ok()?;
+        let prefixes = Prefixes::from_config(&repository.config().ok()?);
+        Links::from_remote_url(remote.url()?, prefixes)
+    }
+
+    /// Derive links from the URL of a remote.
+    ///
+    /// Both HTTP(S) and SSH URLs are supported, including the scp-like `git@host:path` form.
+    /// Returns `None` for other URLs, like local paths.
+    pub(crate) fn from_remote_url(url: &str, prefixes: Prefixes) -> Option<Self> {
+        let url = url.trim_end_matches('/');
+        let url = url.strip_suffix(".git").unwrap_or(url);
+
+        let (host, path) = if let Some((_, rest)) = url.split_once("://") {
+            let rest = rest.rsplit_once('@').map_or(rest, |(_, rest)| rest);
+            rest.split_once('/')?
+        } else {
+            let (authority, path) = url.split_once(':')?;
+            if authority.len() == 1 {
+                // Just like Git, treat a single letter as drive of a local Windows path
+                return None;
+            }
+            let host = authority
+                .rsplit_once('@')
+                .map_or(authority, |(_, host)| host);
+            (host, path.trim_start_matches('/'))
+        };
+
+        // Ports are only meaningful for the transport, not for the web page
+        let host = host.split_once(':').map_or(host, |(host, _)| host);
+        if host.is_empty() || path.is_empty() || host.contains('/') {
+            return None;
+        }
+
+        Some(Links {
+            host: host.to_string(),
+            path: path.to_string(),
+            gitlab: host.contains("gitlab"),
+            prefixes,
+        })
+    }
+
+    /// Get URL of the repository web page.
+    pub(crate) fn base(&self) -> String {
+        format!("https://{}/{}", self.host, self.path)
+    }
+
+    /// Get URL comparing the release tags of two versions.
+    pub(crate) fn compare(&self, prev: &str, next: &str) -> String {
+        format!(
+            "{}/{}compare/{}...{}",
+            self.base(),
+            if self.gitlab { "-/" } else { "" },
+            self.prefixes.tag(prev),
+            self.prefixes.tag(next)
+        )
+    }
+}
+
+#[test]
+fn build_compare_links() {
+    let compare = |url: &str| {
+        Links::from_remote_url(url, Prefixes::default())
+            .map(|links| links.compare("1.0.0", "1.1.0"))
+    };
+
+    let github = Some(String::from(
+        "https://github.com/owner/repo/compare/v1.0.0...v1.1.0",
+    ));
+    assert_eq!(compare("https://github.com/owner/repo.git"), github);
+    assert_eq!(compare("https://user@github.com/owner/repo/"), github);
+    assert_eq!(compare("git@github.com:owner/repo.git"), github);
+    assert_eq!(compare("ssh://git@github.com:22/owner/repo.git"), github);
+
+    assert_eq!(
+        compare("git@gitlab.com:group/sub/repo.git"),
+        Some(String::from(
+            "https://gitlab.com/group/sub/repo/-/compare/v1.0.0...v1.1.0"
+        ))
+    );
+
+    assert_eq!(compare("/srv/git/repo.git"), None);
+    assert_eq!(compare("../repo"), None);
+    assert_eq!(compare("C:\\repo"), None);
+}
@@ -0,0 +1,320 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use mlua::prelude::*;
+
+use crate::engine::{self, Disk};
+use crate::mapping::{
+    check_shapes, is_outside_worktree, key_components, resolve_parents, Mapping, OPTIONS,
+};
+use crate::state::State;
+use crate::Result;
+
+/// Versions the mappings are run with, to find functions whose result ignores the version.
+const VERSIONS: [&str; 2] = ["9876.54.3210", "9876.54.3211"];
+
+/// Severity of a finding, from the most to the least severe.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Severity {
+    Error,
+    Warning,
+    Info,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "info",
+        })
+    }
+}
+
+/// Problem found in a config file.
+pub(crate) struct Finding {
+    pub(crate) severity: Severity,
+    /// Config file, as shown in messages.
+    pub(crate) config: String,
+    pub(crate) line: Option<usize>,
+    pub(crate) key: Option<String>,
+    pub(crate) message: String,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.severity, self.config)?;
+        if let Some(line) = self.line {
+            write!(f, ":{}", line)?;
+        }
+        if let Some(key) = &self.key {
+            write!(f, ": {}", key)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// Check all config files, without writing any files or running any hooks.
+///
+/// Unlike when bumping, problems do not abort the check, so all of them are reported at once.
+pub(crate) fn lint(state: &mut State) -> Result<Vec<Finding>> {
+    let lua = state.get_lua()?;
+    let workdir = state.get_workdir()?;
+    let paths = state.get_paths()?;
+    state.set_context()?;
+
+    let mut findings = Vec::new();
+    let mut defined: HashMap<String, (PathBuf, String)> = HashMap::new();
+
+    for config in state.get_config_files()?.iter() {
+        let source = match fs::read_to_string(config) {
+            Ok(source) => source,
+            Err(_) => continue,
+        };
+        let name = paths.show(config);
+        let mut report = |severity, line, key: Option<&str>, message: String| {
+            findings.push(Finding {
+                severity,
+                config: name.clone(),
+                line,
+                key: key.map(String::from),
+                message,
+            })
+        };
+
+        for (line, message) in check_source(&source) {
+            report(Severity::Warning, Some(line), None, message);
+        }
+
+        let map = match engine::eval_config(&lua, &name, &source, Some(VERSIONS[0])) {
+            Ok(map) => map,
+            Err(err) => {
+                report(Severity::Error, None, None, err.to_string());
+                continue;
+            }
+        };
+
+        let scope = state.get_config_scope(config)?;
+        for (key, value) in map {
+            for (severity, message) in check_value(&value)? {
+                report(severity, None, Some(&key), message);
+            }
+
+            let path = match &scope {
+                Some(scope) if !Path::new(&key).is_absolute() => format!("{}/{}", scope, key),
+                _ => key.clone(),
+            };
+            let outside = is_outside_worktree(&path);
+            let path = resolve_parents(&key_components(&path)).join("/");
+
+            let chain = match &value {
+                LuaValue::Table(table) => table.get::<_, Option<bool>>("chain")?.unwrap_or(false),
+                _ => false,
+            };
+            match defined.insert(path.clone(), (config.clone(), key.clone())) {
+                Some(_) if chain => {}
+                Some((previous, _)) if previous != *config => report(
+                    Severity::Info,
+                    None,
+                    Some(&key),
+                    format!("Overrides the mapping from {}", paths.show(&previous)),
+                ),
+                Some((_, previous)) => report(
+                    Severity::Warning,
+                    None,
+                    Some(&key),
+                    format!("Refers to the same file as {}", previous),
+                ),
+                None => {}
+            }
+
+            let mapping = match Mapping::from_lua(&lua, &key, value, config) {
+                Ok(mapping) => mapping,
+                Err(_) => continue,
+            };
+            if !outside {
+                for (severity, message) in check_results(&lua, &mapping, &workdir, &path) {
+                    report(severity, None, Some(&key), message);
+                }
+            }
+            mapping.remove(&lua)?;
+        }
+    }
+
+    Ok(findings)
+}
+
+/// Check source of a config file for constructs that are better expressed otherwise.
+///
+/// Returns line numbers along with the messages.
+fn check_source(source: &str) -> Vec<(usize, String)> {
+    source
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| {
+            let code = line.split("--").next().unwrap_or_default();
+            code.contains("os.execute")
+        })
+        .map(|(index, _)| {
+            (
+                index + 1,
+                String::from(
+                    "`os.execute` runs a shell whose errors go unnoticed, use `pre_cmd` or \
+                     `post_cmd` instead",
+                ),
+            )
+        })
+        .collect()
+}
+
+/// Check value of a mapping, which must be a function, a table with a function and options, or a
+/// list of those.
+fn check_value(value: &LuaValue) -> Result<Vec<(Severity, String)>> {
+    let table = match value {
+        LuaValue::Function(_) => return Ok(Vec::new()),
+        LuaValue::Table(table)
+            if table.raw_len() > 0 && matches!(table.get("func")?, LuaValue::Nil) =>
+        {
+            let mut findings = Vec::new();
+            for value in table.clone().sequence_values::<LuaValue>() {
+                findings.extend(check_value(&value?)?);
+            }
+            return Ok(findings);
+        }
+        LuaValue::Table(table) => table,
+        value => {
+            return Ok(vec![(
+                Severity::Error,
+                format!(
+                    "Expected a function, a table with the function in its `func` member and options, or a list of those, got {}",
+                    value.type_name()
+                ),
+            )])
+        }
+    };
+
+    let mut findings = Vec::new();
+    for (option, expected, type_name) in check_shapes(table)? {
+        let message = match option {
+            "func" => String::from("Table has no function in its `func` member"),
+            option => format!(
+                "Option `{}` must be {}, got {}",
+                option, expected, type_name
+            ),
+        };
+        findings.push((Severity::Error, message));
+    }
+
+    for pair in table.clone().pairs::<LuaValue, LuaValue>() {
+        let (name, _) = pair?;
+        let known = match &name {
+            LuaValue::String(name) => OPTIONS
+                .iter()
+                .any(|(option, _)| *option == name.to_str().unwrap_or_default()),
+            _ => false,
+        };
+        if !known {
+            let name = match &name {
+                LuaValue::String(name) => name.to_string_lossy().into_owned(),
+                name => name.type_name().to_string(),
+            };
+            findings.push((Severity::Warning, format!("Unknown option `{}`", name)));
+        }
+    }
+
+    Ok(findings)
+}
+
+/// Check the results of a mapping by running it on its file.
+///
+/// The result has to depend on the version, so running it with two versions must give different
+/// results. It also has to be idempotent, so running it again on its own result with the same
+/// version must not change anything, otherwise bumping twice would add entries twice, for example.
+/// Mappings of missing files and disabled mappings are not checked.
+fn check_results(
+    lua: &Lua,
+    mapping: &Mapping,
+    workdir: &Path,
+    path: &str,
+) -> Vec<(Severity, String)> {
+    let contents = match engine::read(&Disk, mapping, &workdir.join(path)) {
+        Ok(contents) => contents,
+        Err(_) => return Vec::new(),
+    };
+    let severity = match mapping.optional {
+        true => Severity::Warning,
+        false => Severity::Error,
+    };
+    let apply = |version, contents: &[u8]| {
+        engine::is_enabled(lua, mapping, version).and_then(|enabled| {
+            enabled
+                .then(|| engine::apply(lua, mapping, version, contents))
+                .transpose()
+        })
+    };
+
+    let mut results = Vec::new();
+    for version in VERSIONS {
+        match apply(version, &contents) {
+            Ok(Some((result, _))) => results.push(result),
+            Ok(None) => return Vec::new(),
+            Err(err) => return vec![(severity, err.to_string())],
+        }
+    }
+
+    let mut findings = Vec::new();
+    if results[0] == results[1] {
+        findings.push((
+            Severity::Warning,
+            String::from("Result does not depend on the version, is the version parameter used?"),
+        ));
+    }
+    match apply(VERSIONS[0], &results[0]) {
+        Ok(Some((again, _))) if again != results[0] => findings.push((
+            Severity::Warning,
+            String::from(
+                "Result changes when bumping twice to the same version, is the content checked before adding to it?",
+            ),
+        )),
+        Ok(_) => {}
+        Err(err) => findings.push((severity, err.to_string())),
+    }
+
+    findings
+}
+
+#[test]
+fn lint_config_values() {
+    let lua = Lua::new();
+    let check = |code: &str| {
+        check_value(&lua.load(code).eval::<LuaValue>().unwrap())
+            .unwrap()
+            .into_iter()
+            .map(|(severity, _)| severity)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(check("function(version) return version end"), []);
+    assert_eq!(check("{func = function() end, optional = true}"), []);
+    assert_eq!(check("'1.2.3'"), [Severity::Error]);
+    assert_eq!(check("{optional = true}"), [Severity::Error]);
+    assert_eq!(
+        check("{func = function() end, optinal = true}"),
+        [Severity::Warning]
+    );
+    assert_eq!(
+        check("{function() end, {func = function() end, chain = true}, 42}"),
+        [Severity::Error]
+    );
+
+    let source = "-- os.execute is fine in comments\nos.execute('make')\n";
+    assert_eq!(
+        check_source(source)
+            .into_iter()
+            .map(|(line, _)| line)
+            .collect::<Vec<_>>(),
+        [2]
+    );
+}
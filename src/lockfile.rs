@@ -0,0 +1,157 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Mutex, PoisonError};
+
+use mlua::prelude::*;
+
+use crate::project::toml_section;
+use crate::{command, Error, Result};
+
+/// Lock that lets only one refresh run at a time.
+///
+/// Manifests of a workspace usually share a lockfile, so parallel refreshes would conflict.
+static REFRESH: Mutex<()> = Mutex::new(());
+
+/// Supported lockfile updaters.
+pub(crate) enum Lockfile {
+    Cargo,
+    Npm,
+    Poetry,
+}
+
+impl Lockfile {
+    /// Get lockfile updater from the `lockfile` member of a hooks table.
+    ///
+    /// The value can either be the name of the updater or `true`, in which case the updater is
+    /// derived from the file name of the manifest.
+    pub(crate) fn from_lua(value: LuaValue, file: &Path) -> Result<Option<Self>> {
+        let name = match value {
+            LuaValue::Nil | LuaValue::Boolean(false) => return Ok(None),
+            LuaValue::Boolean(true) => file
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            LuaValue::String(name) => name.to_str()?.to_string(),
+            value => {
+                return Err(Error::UnknownLockfile {
+                    name: value.type_name().to_string(),
+                })
+            }
+        };
+
+        match name.as_str() {
+            "cargo" | "Cargo.toml" => Ok(Some(Lockfile::Cargo)),
+            "npm" | "package.json" => Ok(Some(Lockfile::Npm)),
+            "poetry" | "pyproject.toml" => Ok(Some(Lockfile::Poetry)),
+            _ => Err(Error::UnknownLockfile { name }),
+        }
+    }
+
+    /// Find the lockfile belonging to the given manifest.
+    ///
+    /// The lockfile is searched for in the directory of the manifest and its parents up to the
+    /// root of the worktree, since workspaces share a single lockfile.
+    pub(crate) fn find(&self, manifest: &Path, workdir: &Path) -> Option<PathBuf> {
+        let file_name = match self {
+            Lockfile::Cargo => "Cargo.lock",
+            Lockfile::Npm => "package-lock.json",
+            Lockfile::Poetry => "poetry.lock",
+        };
+
+        manifest
+            .ancestors()
+            .skip(1)
+            .take_while(|dir| dir.starts_with(workdir))
+            .map(|dir| dir.join(file_name))
+            .find(|lockfile| lockfile.is_file())
+    }
+
+    /// Build command that refreshes the lockfile belonging to the given manifest.
+    fn command(&self, manifest: &Path, version: &str) -> Result<Command> {
+        let command = match self {
+            Lockfile::Cargo => {
+                let contents = std::fs::read_to_string(manifest)
+                    .map_err(|source| Error::ReadFailed { source })?;
+                // Virtual manifests of workspaces have no package that could be updated
+                let name =
+                    cargo_package_name(&contents).ok_or_else(|| Error::MissingPackageName {
+                        manifest: manifest.display().to_string(),
+                    })?;
+
+                let mut command = Command::new("cargo");
+                command
+                    .arg("update")
+                    .arg("--manifest-path")
+                    .arg(manifest)
+                    .arg("-p")
+                    .arg(name)
+                    .arg("--precise")
+                    .arg(version);
+                command
+            }
+            Lockfile::Npm => {
+                let mut command = Command::new("npm");
+                command.arg("install").arg("--package-lock-only");
+                command
+            }
+            Lockfile::Poetry => {
+                let mut command = Command::new("poetry");
+                command.arg("lock").arg("--no-update");
+                command
+            }
+        };
+
+        Ok(command)
+    }
+
+    /// Refresh the lockfile belonging to the given manifest.
+    ///
+    /// The command is run in the directory of the manifest. Its output is captured and only shown
+    /// if the command fails.
+    pub(crate) fn refresh(&self, manifest: &Path, version: &str) -> Result<()> {
+        let _refresh = REFRESH.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut command = self.command(manifest, version)?;
+        if let Some(dir) = manifest.parent() {
+            command.current_dir(dir);
+        }
+
+        let display = command::display(&command);
+        command::run(&mut command).map_err(|output| Error::LockfileRefreshFailed {
+            command: display,
+            output,
+        })?;
+
+        Ok(())
+    }
+}
+
+/// Get the package name from the `[package]` section of a `Cargo.toml`.
+///
+/// Returns `None` if there is no such section, like in virtual manifests of workspaces, or if the
+/// name is not given literally, like when it is inherited.
+pub(crate) fn cargo_package_name(contents: &str) -> Option<String> {
+    toml_section(contents, "package").lines().find_map(|line| {
+        let (key, value) = line.split_once('=')?;
+        let value = value.split('#').next()?.trim();
+        (key.trim() == "name")
+            .then(|| value.trim_matches(|c| c == '"' || c == '\'').to_string())
+            .filter(|name| !name.is_empty())
+    })
+}
+
+#[test]
+fn read_cargo_package_name() {
+    assert_eq!(
+        cargo_package_name(
+            "[package] # the crate\nname = \"foo\" # renamed\nversion = \"1.0.0\"\n"
+        ),
+        Some(String::from("foo"))
+    );
+    assert_eq!(
+        cargo_package_name(
+            "[workspace]\nmembers = [\"foo\"]\n\n[workspace.package]\nname = \"x\"\n"
+        ),
+        None
+    );
+    assert_eq!(cargo_package_name("[package]\nname = \"\"\n"), None);
+}
@@ -0,0 +1,455 @@
+use std::path::{Path, PathBuf};
+
+use mlua::prelude::*;
+
+use crate::formatter::Formatter;
+use crate::{bytecode, Error, Result};
+
+/// Accepted shape of a member of a mapping table.
+#[derive(Clone, Copy)]
+pub(crate) enum Shape {
+    Boolean,
+    String,
+    /// Single string or list of strings.
+    Strings,
+    Function,
+}
+
+impl Shape {
+    /// Check if a value has this shape.
+    fn matches(self, value: &LuaValue) -> bool {
+        match (self, value) {
+            (Shape::Boolean, LuaValue::Boolean(_))
+            | (Shape::String, LuaValue::String(_))
+            | (Shape::Strings, LuaValue::String(_))
+            | (Shape::Function, LuaValue::Function(_)) => true,
+            (Shape::Strings, LuaValue::Table(table)) => {
+                // Lists have no members besides their sequence
+                table.clone().pairs::<LuaValue, LuaValue>().count() == table.raw_len()
+                    && table
+                        .clone()
+                        .sequence_values::<LuaValue>()
+                        .all(|value| matches!(value, Ok(LuaValue::String(_))))
+            }
+            _ => false,
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            Shape::Boolean => "a boolean",
+            Shape::String => "a string",
+            Shape::Strings => "a string or a list of strings",
+            Shape::Function => "a function",
+        }
+    }
+}
+
+/// Members of a mapping table, along with their accepted shapes.
+pub(crate) const OPTIONS: &[(&str, Shape)] = &[
+    ("func", Shape::Function),
+    ("optional", Shape::Boolean),
+    ("group", Shape::Strings),
+    ("description", Shape::String),
+    ("enabled", Shape::Function),
+    ("needs_content", Shape::Boolean),
+    ("ensure_trailing_newline", Shape::Boolean),
+    ("chain", Shape::Boolean),
+    ("raw", Shape::Boolean),
+    ("current_version", Shape::Function),
+    ("source_of_truth", Shape::Boolean),
+    ("format", Shape::Strings),
+    ("format_failure", Shape::String),
+];
+
+/// Find members of a mapping table that do not have their accepted shape.
+///
+/// Returns the names of the members, along with the accepted shape and the actual type. Missing
+/// options are fine, except for `func`, and unknown members are ignored.
+pub(crate) fn check_shapes(table: &LuaTable) -> Result<Vec<(&'static str, &'static str, String)>> {
+    let mut invalid = Vec::new();
+    for (name, shape) in OPTIONS {
+        let value = table.get::<_, LuaValue>(*name)?;
+        if (*name != "func" && matches!(value, LuaValue::Nil)) || shape.matches(&value) {
+            continue;
+        }
+        invalid.push((*name, shape.describe(), value.type_name().to_string()));
+    }
+    Ok(invalid)
+}
+
+/// Fail on the first member of a mapping table that does not have its accepted shape.
+fn check_options(key: &str, table: &LuaTable) -> Result<()> {
+    if let Some((option, expected, type_name)) = check_shapes(table)?.into_iter().next() {
+        return Err(Error::InvalidOption {
+            key: key.to_string(),
+            option: option.to_string(),
+            expected: expected.to_string(),
+            type_name,
+        });
+    }
+    Ok(())
+}
+
+/// Bumping function of a single file, along with its options.
+///
+/// In a config file, a mapping can either be given as a plain function or as a table containing
+/// the function in its `func` member and any options as additional members. A list of such
+/// mappings forms a chain, where each function gets the contents returned by the previous one.
+pub(crate) struct Mapping {
+    pub(crate) key: String,
+    pub(crate) func: LuaRegistryKey,
+    pub(crate) source: PathBuf,
+    pub(crate) optional: bool,
+    pub(crate) groups: Vec<String>,
+    pub(crate) description: Option<String>,
+    pub(crate) enabled: Option<LuaRegistryKey>,
+    /// Whether the function needs the current contents of the file, which are not read otherwise.
+    pub(crate) needs_content: bool,
+    /// Whether a newline is appended to new contents that do not end with one.
+    pub(crate) ensure_trailing_newline: bool,
+    /// Whether contents are passed as byte strings without UTF-8 validation, and written back
+    /// verbatim, ignoring the line endings configured for the file.
+    pub(crate) raw: bool,
+    /// Function extracting the current version from the contents of the file, for
+    /// `ctx.current_version`.
+    pub(crate) current_version: Option<LuaRegistryKey>,
+    /// Whether the current version of this file is the one of the whole project.
+    pub(crate) source_of_truth: bool,
+    /// External formatter that is run on the file after writing it.
+    pub(crate) format: Option<Formatter>,
+    /// Whether the mapping is chained to a mapping of the same file from an earlier config,
+    /// instead of overriding it.
+    pub(crate) chain: bool,
+    /// Mappings that are run after this one, on its result.
+    pub(crate) chained: Vec<Mapping>,
+}
+
+impl Mapping {
+    /// Create mapping from an entry of the given config file.
+    pub(crate) fn from_lua(lua: &Lua, key: &str, value: LuaValue, source: &Path) -> Result<Self> {
+        let (func, options) = match value {
+            LuaValue::Function(func) => (func, None),
+            LuaValue::Table(table)
+                if table.raw_len() > 0 && matches!(table.get("func")?, LuaValue::Nil) =>
+            {
+                return Self::from_list(lua, key, table, source);
+            }
+            LuaValue::Table(table) => {
+                check_options(key, &table)?;
+                (table.get::<_, LuaFunction>("func")?, Some(table))
+            }
+            value => {
+                return Err(Error::InvalidMapping {
+                    key: key.to_string(),
+                    type_name: value.type_name().to_string(),
+                })
+            }
+        };
+
+        let needs_content = takes_content(lua, &func);
+
+        let mut mapping = Mapping {
+            key: key.to_string(),
+            func: lua.create_registry_value(func)?,
+            source: source.to_path_buf(),
+            optional: false,
+            groups: Vec::new(),
+            description: None,
+            enabled: None,
+            needs_content,
+            ensure_trailing_newline: true,
+            raw: false,
+            current_version: None,
+            source_of_truth: false,
+            format: None,
+            chain: false,
+            chained: Vec::new(),
+        };
+
+        if let Some(options) = options {
+            mapping.optional = options.get::<_, Option<bool>>("optional")?.unwrap_or(false);
+            mapping.groups = match options.get::<_, LuaValue>("group")? {
+                LuaValue::Nil => Vec::new(),
+                LuaValue::String(group) => vec![group.to_str()?.to_string()],
+                value => lua.unpack::<Vec<String>>(value)?,
+            };
+            mapping.description = options.get::<_, Option<String>>("description")?;
+            mapping.enabled = options
+                .get::<_, Option<LuaFunction>>("enabled")?
+                .map(|enabled| lua.create_registry_value(enabled))
+                .transpose()?;
+            if let Some(needs_content) = options.get::<_, Option<bool>>("needs_content")? {
+                mapping.needs_content = needs_content;
+            }
+            // Binary contents get no newline appended, unless asked for
+            mapping.raw = options.get::<_, Option<bool>>("raw")?.unwrap_or(false);
+            mapping.ensure_trailing_newline = options
+                .get::<_, Option<bool>>("ensure_trailing_newline")?
+                .unwrap_or(!mapping.raw);
+            mapping.current_version = options
+                .get::<_, Option<LuaFunction>>("current_version")?
+                .map(|current_version| lua.create_registry_value(current_version))
+                .transpose()?;
+            mapping.source_of_truth = options
+                .get::<_, Option<bool>>("source_of_truth")?
+                .unwrap_or(false);
+            mapping.format = Formatter::from_options(lua, key, &options)?;
+            mapping.chain = options.get::<_, Option<bool>>("chain")?.unwrap_or(false);
+        }
+
+        Ok(mapping)
+    }
+
+    /// Check if a mapping needs the current contents of its file, without creating it.
+    ///
+    /// This sorts out mappings of missing files cheaply, which cannot create their files if they
+    /// need the contents, so no registry values are created for them. The shapes of the options
+    /// are still checked, so invalid mappings fail no matter whether their files exist.
+    pub(crate) fn needs_content(lua: &Lua, key: &str, value: &LuaValue) -> Result<bool> {
+        let table = match value {
+            LuaValue::Function(func) => return Ok(takes_content(lua, func)),
+            LuaValue::Table(table) => table,
+            value => {
+                return Err(Error::InvalidMapping {
+                    key: key.to_string(),
+                    type_name: value.type_name().to_string(),
+                })
+            }
+        };
+
+        if table.raw_len() > 0 && matches!(table.get("func")?, LuaValue::Nil) {
+            // The first mapping of a chain decides for the whole chain
+            let mut needs_content = None;
+            for value in table.clone().sequence_values::<LuaValue>() {
+                let link = Self::needs_content(lua, key, &value?)?;
+                needs_content.get_or_insert(link);
+            }
+            return Ok(needs_content.unwrap_or(true));
+        }
+
+        check_options(key, table)?;
+        Formatter::from_options(lua, key, table)?;
+        Ok(match table.get::<_, Option<bool>>("needs_content")? {
+            Some(needs_content) => needs_content,
+            None => takes_content(lua, &table.get::<_, LuaFunction>("func")?),
+        })
+    }
+
+    /// Create chain from a list of mappings of the same file.
+    ///
+    /// The options of the chain as a whole, like `optional` or `group`, are those of its first
+    /// mapping. Only the `enabled` guards of the others are checked as well.
+    fn from_list(lua: &Lua, key: &str, list: LuaTable, source: &Path) -> Result<Self> {
+        let mut links = list
+            .sequence_values::<LuaValue>()
+            .map(|value| Self::from_lua(lua, key, value?, source))
+            .collect::<Result<Vec<_>>>()?
+            .into_iter();
+
+        let mut mapping = links.next().expect("list is not empty");
+        for link in links {
+            mapping.append(link);
+        }
+        Ok(mapping)
+    }
+
+    /// Append mapping, along with the ones chained to it, to the chain of this mapping.
+    pub(crate) fn append(&mut self, mut mapping: Mapping) {
+        let chained = std::mem::take(&mut mapping.chained);
+        self.chained.push(mapping);
+        self.chained.extend(chained);
+    }
+
+    /// Get this mapping and the ones chained to it, in the order they are run.
+    pub(crate) fn links(&self) -> impl Iterator<Item = &Mapping> {
+        std::iter::once(self).chain(&self.chained)
+    }
+
+    /// Check if mapping belongs to any of the selected groups.
+    ///
+    /// An empty selection matches every mapping.
+    pub(crate) fn is_selected(&self, groups: &[String]) -> bool {
+        groups.is_empty() || self.groups.iter().any(|group| groups.contains(group))
+    }
+
+    /// Remove values from Lua registry.
+    pub(crate) fn remove(self, lua: &Lua) -> Result<()> {
+        lua.remove_registry_value(self.func)?;
+        if let Some(enabled) = self.enabled {
+            lua.remove_registry_value(enabled)?;
+        }
+        if let Some(current_version) = self.current_version {
+            lua.remove_registry_value(current_version)?;
+        }
+        for mapping in self.chained {
+            mapping.remove(lua)?;
+        }
+        Ok(())
+    }
+}
+
+/// Check if a bumping function takes the contents of its file.
+///
+/// Functions that only take the version replace the whole file anyway.
+fn takes_content(lua: &Lua, func: &LuaFunction) -> bool {
+    !matches!(
+        bytecode::parameters(lua, func),
+        Some((parameters, false)) if parameters < 2
+    )
+}
+
+/// Split file name of a mapping into its path components.
+///
+/// Config keys may use forward slashes, backslashes, or a mix of both as separators, so configs
+/// written with Windows paths resolve to the same files everywhere. Empty and `.` components are
+/// dropped.
+pub(crate) fn key_components(key: &str) -> Vec<&str> {
+    key.split(['/', '\\'])
+        .filter(|component| !component.is_empty() && *component != ".")
+        .collect()
+}
+
+/// Resolve `..` components of a file name inside of the worktree.
+///
+/// This way, different spellings of the same file result in the same path, which is needed to
+/// detect overridden mappings.
+pub(crate) fn resolve_parents<'a>(components: &[&'a str]) -> Vec<&'a str> {
+    let mut resolved = Vec::new();
+    for component in components {
+        if *component == ".." {
+            resolved.pop();
+        } else {
+            resolved.push(*component);
+        }
+    }
+    resolved
+}
+
+/// Check if the file name of a mapping points outside of the worktree.
+///
+/// This is the case for absolute paths, including Windows paths with drive letters, and for
+/// relative paths that leave the worktree via `..`.
+pub(crate) fn is_outside_worktree(key: &str) -> bool {
+    let has_drive =
+        key.starts_with(|c: char| c.is_ascii_alphabetic()) && key.get(1..2) == Some(":");
+    if key.starts_with(['/', '\\']) || has_drive {
+        return true;
+    }
+
+    let mut depth = 0;
+    for component in key_components(key) {
+        if component == ".." {
+            if depth == 0 {
+                return true;
+            }
+            depth -= 1;
+        } else {
+            depth += 1;
+        }
+    }
+
+    false
+}
+
+/// Check if a file inside of the worktree by name resolves to a place outside of it.
+///
+/// Symlinks in the worktree may point anywhere, like to `/etc`, so the longest existing part of
+/// the path is resolved and compared to the worktree, which has to be canonical already.
+pub(crate) fn escapes_worktree(canonical_workdir: &Path, file: &Path) -> bool {
+    file.ancestors()
+        .find_map(|path| path.canonicalize().ok())
+        .is_some_and(|path| !path.starts_with(canonical_workdir))
+}
+
+#[test]
+fn normalize_key_separators() {
+    for key in [
+        "src/version.rs",
+        "src\\version.rs",
+        "./src\\/version.rs",
+        ".\\src\\version.rs",
+    ] {
+        assert_eq!(key_components(key), ["src", "version.rs"], "{}", key);
+    }
+}
+
+#[test]
+fn detect_keys_outside_worktree() {
+    for key in [
+        "/etc/passwd",
+        "\\server\\share",
+        "C:\\x",
+        "../x",
+        "a/../../x",
+    ] {
+        assert!(is_outside_worktree(key), "{}", key);
+    }
+    for key in ["x", "a/../x", "./a/b/../../x"] {
+        assert!(!is_outside_worktree(key), "{}", key);
+    }
+
+    assert_eq!(resolve_parents(&key_components("pkg/../a/./b")), ["a", "b"]);
+
+    #[cfg(unix)]
+    {
+        let tmpdir = crate::state::TempDir::create().unwrap();
+        let workdir = tmpdir.path().join("repo");
+        std::fs::create_dir(&workdir).unwrap();
+        std::fs::create_dir(workdir.join("src")).unwrap();
+        std::os::unix::fs::symlink(tmpdir.path(), workdir.join("outside")).unwrap();
+        let workdir = workdir.canonicalize().unwrap();
+
+        assert!(!escapes_worktree(&workdir, &workdir.join("src/version.rs")));
+        assert!(!escapes_worktree(
+            &workdir,
+            &workdir.join("missing/version.rs")
+        ));
+        assert!(escapes_worktree(
+            &workdir,
+            &workdir.join("outside/version.rs")
+        ));
+        assert!(escapes_worktree(&workdir, &workdir.join("outside")));
+    }
+}
+
+#[test]
+fn check_option_shapes() {
+    let lua = Lua::new();
+    let check = |code: &str| {
+        check_shapes(&lua.load(code).eval::<LuaTable>().unwrap())
+            .unwrap()
+            .into_iter()
+            .map(|(option, _, type_name)| format!("{} {}", option, type_name))
+            .collect::<Vec<_>>()
+    };
+
+    assert!(check("{func = print, group = {'a', 'b'}, optional = false}").is_empty());
+    assert_eq!(check("{optional = true}"), ["func nil"]);
+    assert_eq!(
+        check("{func = print, optional = 'yes', group = {'a', 1}}"),
+        ["optional string", "group table"]
+    );
+    assert_eq!(check("{func = print, group = {a = 'b'}}"), ["group table"]);
+}
+
+#[test]
+fn detect_needed_content() {
+    let lua = Lua::new();
+    let needs_content = |code: &str| {
+        let value = lua.load(code).eval::<LuaValue>().unwrap();
+        Mapping::needs_content(&lua, "x", &value)
+    };
+
+    assert!(needs_content("function(version, content) return content end").unwrap());
+    assert!(!needs_content("function(version) return version end").unwrap());
+    assert!(
+        !needs_content("{func = function(version, content) end, needs_content = false}").unwrap()
+    );
+    assert!(!needs_content("{function(version) end, function(v, c) end}").unwrap());
+    assert!(needs_content("{optional = true}").is_err());
+    assert!(needs_content("{func = print, optional = 'yes'}").is_err());
+    assert!(needs_content("{func = print, format = 'x', format_failure = 'never'}").is_err());
+    assert!(needs_content("{print, {func = print, raw = 1}}").is_err());
+    assert!(needs_content("42").is_err());
+}
@@ -0,0 +1,71 @@
+use crate::prefix::Prefixes;
+
+/// Extract the section of a version from a changelog.
+///
+/// Sections start with a second-level heading containing the version, optionally in brackets and
+/// with a prefix, like `## [1.2.3] - 2024-01-31` or `## v1.2.3`. They end with the next heading of
+/// the same or a higher level, or with the link reference definitions at the end of the file. The
+/// heading itself and surrounding blank lines are not part of the returned notes.
+pub(crate) fn extract(changelog: &str, version: &str, prefixes: &Prefixes) -> Option<String> {
+    let version = prefixes.bare(version);
+
+    let mut lines = changelog.lines();
+    lines.find(|line| heading_version(line, prefixes) == Some(version))?;
+
+    let notes = lines
+        .take_while(|line| !is_section_end(line))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    Some(notes.trim_matches('\n').to_string())
+}
+
+/// Get bare version of a second-level heading, if any.
+fn heading_version<'a>(line: &'a str, prefixes: &Prefixes) -> Option<&'a str> {
+    let title = line.strip_prefix("## ")?.trim_start();
+    let title = title.strip_prefix('[').unwrap_or(title);
+    let end = title
+        .find(|c: char| c == ']' || c.is_whitespace())
+        .unwrap_or(title.len());
+
+    Some(prefixes.bare(&title[..end]))
+}
+
+/// Check if a line ends a section.
+fn is_section_end(line: &str) -> bool {
+    line.starts_with("# ")
+        || line.starts_with("## ")
+        || (line.starts_with('[') && line.contains("]: "))
+}
+
+#[test]
+fn extract_notes_of_version() {
+    let changelog = "\
+# Changelog
+
+## [Unreleased]
+
+## [v1.2.3] - 2024-01-31
+
+### Fixed
+
+- Something.
+
+## 1.2.2
+
+- Other.
+
+[1.2.3]: https://example.com/compare/v1.2.2...v1.2.3
+";
+
+    let prefixes = Prefixes::default();
+    assert_eq!(
+        extract(changelog, "1.2.3", &prefixes).as_deref(),
+        Some("### Fixed\n\n- Something.")
+    );
+    assert_eq!(
+        extract(changelog, "v1.2.2", &prefixes).as_deref(),
+        Some("- Other.")
+    );
+    assert_eq!(extract(changelog, "1.2", &prefixes), None);
+}
@@ -0,0 +1,45 @@
+use std::env;
+use std::io::{IsTerminal, Write};
+use std::process::Stdio;
+
+use crate::{command, Result};
+
+/// Show text through the pager of the user.
+///
+/// The pager is looked up just like Git does it: `GIT_PAGER`, `core.pager`, `PAGER`, and finally
+/// `less`. Unless configured otherwise, `less` quits if the text fits on one screen, so short
+/// text appears as if it was printed directly. If stdout is not a terminal, or the pager cannot
+/// be started, the text is printed directly.
+pub(crate) fn page(text: &str, core_pager: Option<String>) -> Result<()> {
+    let pager = env::var("GIT_PAGER")
+        .ok()
+        .or(core_pager)
+        .or_else(|| env::var("PAGER").ok())
+        .unwrap_or_else(|| String::from("less"));
+
+    if text.is_empty() || !std::io::stdout().is_terminal() || pager.is_empty() || pager == "cat" {
+        print!("{}", text);
+        return Ok(());
+    }
+
+    let mut command = command::shell(&pager);
+    if env::var_os("LESS").is_none() {
+        command.env("LESS", "FRX");
+    }
+    if env::var_os("LV").is_none() {
+        command.env("LV", "-c");
+    }
+
+    match command.stdin(Stdio::piped()).spawn() {
+        Ok(mut child) => {
+            if let Some(mut stdin) = child.stdin.take() {
+                // the user might quit the pager before reading everything
+                let _ = stdin.write_all(text.as_bytes());
+            }
+            child.wait()?;
+        }
+        Err(_) => print!("{}", text),
+    }
+
+    Ok(())
+}
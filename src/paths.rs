@@ -0,0 +1,112 @@
+use std::env;
+use std::path::{Component, Path, PathBuf};
+
+use git2::Config;
+
+/// Presentation of files in the worktree in messages, listings, and diffs.
+///
+/// Just like in `git status`, paths are shown relative to the current directory, so they can be
+/// used in further commands right away, unless `status.relativePaths` is disabled, or full paths
+/// are requested. Files are still read and written via their full paths, no matter where git-bump
+/// was invoked.
+pub(crate) struct PathDisplay {
+    workdir: PathBuf,
+    /// Current directory relative to the worktree, or `None` to show paths relative to the
+    /// worktree.
+    current_dir: Option<PathBuf>,
+    /// Show full paths instead of relative ones.
+    absolute: bool,
+}
+
+impl PathDisplay {
+    /// Read the presentation from the Git config, for the current directory.
+    ///
+    /// If `absolute` is set, the Git config is ignored and full paths are shown.
+    pub(crate) fn from_config(workdir: &Path, config: &Config, absolute: bool) -> Self {
+        let current_dir = match config.get_bool("status.relativePaths").unwrap_or(true) {
+            true => env::current_dir()
+                .and_then(|dir| dir.canonicalize())
+                .ok()
+                .zip(workdir.canonicalize().ok())
+                .and_then(|(dir, workdir)| Some(dir.strip_prefix(workdir).ok()?.to_path_buf())),
+            false => None,
+        };
+
+        PathDisplay {
+            workdir: workdir.to_path_buf(),
+            current_dir,
+            absolute,
+        }
+    }
+
+    /// Show a file, given by its full path or relative to the worktree.
+    ///
+    /// Files outside of the worktree are shown as they are.
+    pub(crate) fn show(&self, file: &Path) -> String {
+        let path = match file.strip_prefix(&self.workdir) {
+            Ok(path) => path,
+            Err(_) if file.is_relative() => file,
+            Err(_) => return file.to_string_lossy().into_owned(),
+        };
+        if self.absolute {
+            return self.workdir.join(path).to_string_lossy().into_owned();
+        }
+
+        match &self.current_dir {
+            Some(current_dir) => relative_to(path, current_dir),
+            None => path.to_path_buf(),
+        }
+        .to_string_lossy()
+        .into_owned()
+    }
+}
+
+/// Get a path relative to a directory, where both are relative to the same base.
+fn relative_to(path: &Path, dir: &Path) -> PathBuf {
+    let path = path
+        .components()
+        .filter(|component| *component != Component::CurDir)
+        .collect::<Vec<_>>();
+    let dir = dir
+        .components()
+        .filter(|component| *component != Component::CurDir)
+        .collect::<Vec<_>>();
+    let common = path.iter().zip(&dir).take_while(|(a, b)| a == b).count();
+
+    let mut relative = PathBuf::new();
+    for _ in common..dir.len() {
+        relative.push(Component::ParentDir);
+    }
+    relative.extend(&path[common..]);
+    relative
+}
+
+#[test]
+fn show_paths_relative_to_current_directory() {
+    let paths = PathDisplay {
+        workdir: PathBuf::from("/repo"),
+        current_dir: Some(PathBuf::from("src/bin")),
+        absolute: false,
+    };
+    assert_eq!(paths.show(Path::new("/repo/src/bin/main.rs")), "main.rs");
+    assert_eq!(paths.show(Path::new("/repo/src/lib.rs")), "../lib.rs");
+    assert_eq!(paths.show(Path::new("VERSION")), "../../VERSION");
+    assert_eq!(
+        paths.show(Path::new("/elsewhere/VERSION")),
+        "/elsewhere/VERSION"
+    );
+
+    let paths = PathDisplay {
+        workdir: PathBuf::from("/repo"),
+        current_dir: None,
+        absolute: false,
+    };
+    assert_eq!(paths.show(Path::new("/repo/src/lib.rs")), "src/lib.rs");
+
+    let paths = PathDisplay {
+        workdir: PathBuf::from("/repo"),
+        current_dir: None,
+        absolute: true,
+    };
+    assert_eq!(paths.show(Path::new("VERSION")), "/repo/VERSION");
+}
@@ -0,0 +1,141 @@
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::PoisonError;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use mlua::prelude::*;
+
+use crate::engine::Disk;
+use crate::git::LineEnding;
+use crate::hooks::CURRENT_DIR;
+use crate::paths::PathDisplay;
+use crate::report::{FileOutcome, HookReport};
+use crate::state::{Settings, State};
+use crate::{BumpOptions, Bumper, Result};
+
+/// Result of bumping a single file on a worker thread, to be merged by the caller.
+pub(crate) struct Bumped {
+    pub(crate) result: Result<(FileOutcome, Vec<HookReport>)>,
+    /// Phase the file was in when bumping finished or failed.
+    pub(crate) phase: &'static str,
+    /// Shown changes, if changes are only previewed.
+    pub(crate) preview: String,
+    /// Written files, including refreshed lockfiles.
+    pub(crate) written: Vec<PathBuf>,
+    pub(crate) duration: Duration,
+    pub(crate) lua_duration: Duration,
+    pub(crate) bytes_written: usize,
+}
+
+/// Shared context of the worker threads.
+pub(crate) struct Pool<'a> {
+    pub(crate) jobs: usize,
+    pub(crate) settings: &'a Settings,
+    pub(crate) version: &'a str,
+    pub(crate) options: &'a BumpOptions,
+    /// Release notes, passed to the config files as `ctx.notes`.
+    pub(crate) notes: Option<&'a str>,
+    pub(crate) workdir: &'a Path,
+    pub(crate) paths: &'a PathDisplay,
+    pub(crate) line_endings: &'a HashMap<PathBuf, LineEnding>,
+    /// Number of retries for writes of locked files.
+    pub(crate) write_retries: u32,
+    /// Temporary directory of the run, shared by all workers.
+    pub(crate) tmpdir: &'a Path,
+}
+
+impl Pool<'_> {
+    /// Bump files on worker threads, keyed by file.
+    ///
+    /// Lua states cannot be shared between threads, so every worker loads the config files into a
+    /// state of its own and then takes files one by one from a shared queue. Global hooks are left
+    /// to the caller. Once a required file failed, the workers stop taking further files, and
+    /// files without a result are left to the caller as well.
+    pub(crate) fn bump(&self, files: &[&PathBuf]) -> Result<HashMap<PathBuf, Bumped>> {
+        let next = AtomicUsize::new(0);
+        let failed = AtomicBool::new(false);
+
+        let worker = || -> Result<Vec<(PathBuf, Bumped)>> {
+            let mut state = State::new(Some(self.version.to_string()), self.settings.clone());
+            state.share_tmpdir(self.tmpdir);
+            let map = {
+                let _current_dir = CURRENT_DIR.read().unwrap_or_else(PoisonError::into_inner);
+                state.get_file_mapping()?
+            };
+            let lua = state.get_lua()?;
+            if let Some(notes) = self.notes {
+                if let Some(ctx) = lua.globals().get::<_, Option<LuaTable>>("ctx")? {
+                    ctx.set("notes", notes)?;
+                }
+            }
+
+            let mut results = Vec::new();
+            while !failed.load(Ordering::SeqCst) {
+                let file = match files.get(next.fetch_add(1, Ordering::SeqCst)) {
+                    Some(file) => *file,
+                    None => break,
+                };
+                let mapping = match map.get(file) {
+                    Some(mapping) => mapping,
+                    None => continue,
+                };
+
+                let bumper = Bumper {
+                    lua: &lua,
+                    workspace: &Disk,
+                    workdir: self.workdir,
+                    paths: self.paths,
+                    line_endings: self.line_endings,
+                    version: self.version,
+                    options: self.options,
+                    write_retries: self.write_retries,
+                    editor: None,
+                    preview: Default::default(),
+                    written: Default::default(),
+                    phase: Cell::new(""),
+                    lua_duration: Default::default(),
+                    bytes_written: Default::default(),
+                };
+                let start = Instant::now();
+                let result = bumper.bump_file(file, mapping);
+                if result.is_err() && !mapping.optional {
+                    failed.store(true, Ordering::SeqCst);
+                }
+
+                results.push((
+                    file.clone(),
+                    Bumped {
+                        result,
+                        phase: bumper.phase.get(),
+                        preview: bumper.preview.take(),
+                        written: bumper.written.take(),
+                        duration: start.elapsed(),
+                        lua_duration: bumper.lua_duration.get(),
+                        bytes_written: bumper.bytes_written.get(),
+                    },
+                ));
+            }
+
+            Ok(results)
+        };
+
+        thread::scope(|scope| {
+            let workers = (0..self.jobs.min(files.len()))
+                .map(|_| scope.spawn(worker))
+                .collect::<Vec<_>>();
+
+            let mut bumped = HashMap::new();
+            for worker in workers {
+                match worker.join() {
+                    Ok(results) => bumped.extend(results?),
+                    Err(payload) => panic::resume_unwind(payload),
+                }
+            }
+            Ok(bumped)
+        })
+    }
+}
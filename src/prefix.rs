@@ -0,0 +1,81 @@
+use git2::Config;
+
+/// Prefixes of versions and tags.
+///
+/// Versions can be given with or without the prefix of tags on the command line. They are brought
+/// into a canonical form with the configured version prefix before they are given to the config
+/// files, so that recipes do not need to strip prefixes themselves.
+pub(crate) struct Prefixes {
+    /// Prefix of versions given to the config files, from `bump.versionPrefix`, empty by default.
+    pub(crate) version: String,
+    /// Prefix of release tags, from `bump.tagPrefix`, `v` by default.
+    pub(crate) tag: String,
+}
+
+impl Default for Prefixes {
+    fn default() -> Self {
+        Prefixes {
+            version: String::new(),
+            tag: String::from("v"),
+        }
+    }
+}
+
+impl Prefixes {
+    /// Read prefixes from the Git config.
+    pub(crate) fn from_config(config: &Config) -> Self {
+        let default = Prefixes::default();
+
+        Prefixes {
+            version: config
+                .get_string("bump.versionPrefix")
+                .unwrap_or(default.version),
+            tag: config.get_string("bump.tagPrefix").unwrap_or(default.tag),
+        }
+    }
+
+    /// Strip any known prefix from a version or tag name.
+    ///
+    /// Besides the configured prefixes, a `v` is always stripped, so both `v1.2.3` and `1.2.3` are
+    /// accepted everywhere. Prefixes are only stripped if they are followed by a digit.
+    pub(crate) fn bare<'a>(&self, version: &'a str) -> &'a str {
+        [self.tag.as_str(), self.version.as_str(), "v"]
+            .into_iter()
+            .filter(|prefix| !prefix.is_empty())
+            .filter_map(|prefix| version.strip_prefix(prefix))
+            .find(|bare| bare.starts_with(|c: char| c.is_ascii_digit()))
+            .unwrap_or(version)
+    }
+
+    /// Get canonical form of a version, as given to the config files.
+    pub(crate) fn canonical(&self, version: &str) -> String {
+        format!("{}{}", self.version, self.bare(version))
+    }
+
+    /// Get name of the release tag of a version.
+    pub(crate) fn tag(&self, version: &str) -> String {
+        format!("{}{}", self.tag, self.bare(version))
+    }
+}
+
+#[test]
+fn normalize_prefixes() {
+    let prefixes = Prefixes::default();
+    assert_eq!(prefixes.canonical("v1.2.3"), "1.2.3");
+    assert_eq!(prefixes.canonical("1.2.3"), "1.2.3");
+    assert_eq!(prefixes.canonical("version"), "version");
+    assert_eq!(prefixes.tag("1.2.3"), "v1.2.3");
+
+    let prefixes = Prefixes {
+        version: String::from("v"),
+        tag: String::from("release-"),
+    };
+    assert_eq!(prefixes.canonical("release-1.2.3"), "v1.2.3");
+    assert_eq!(prefixes.canonical("1.2.3"), "v1.2.3");
+    assert_eq!(prefixes.canonical("v1.2.3"), "v1.2.3");
+    assert_eq!(
+        prefixes.canonical("release-candidate"),
+        "vrelease-candidate"
+    );
+    assert_eq!(prefixes.tag("v1.2.3"), "release-1.2.3");
+}
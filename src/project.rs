@@ -0,0 +1,425 @@
+use std::ffi::OsStr;
+use std::fs;
+use std::path::Path;
+
+use crate::lockfile::cargo_package_name;
+
+/// Known project types along with the files that indicate them.
+///
+/// Paths are relative to the root of the worktree. A path starting with `*.` matches any file
+/// with that extension in the root.
+const PROJECT_TYPES: &[(&str, &[&str])] = &[
+    ("debian", &["debian/changelog"]),
+    ("go", &["go.mod"]),
+    ("helm", &["Chart.yaml"]),
+    ("java", &["pom.xml", "build.gradle", "build.gradle.kts"]),
+    ("node", &["package.json"]),
+    ("python", &["pyproject.toml", "setup.py", "setup.cfg"]),
+    ("rpm", &["*.spec"]),
+    ("rust", &["Cargo.toml"]),
+];
+
+/// Detect the types of the project in the given worktree.
+pub(crate) fn detect_project_types(workdir: &Path) -> Vec<&'static str> {
+    PROJECT_TYPES
+        .iter()
+        .filter(|(_, files)| files.iter().any(|file| exists(workdir, file)))
+        .map(|(name, _)| *name)
+        .collect()
+}
+
+/// Check if a file indicating a project type exists in the worktree.
+fn exists(workdir: &Path, file: &str) -> bool {
+    if let Some(extension) = file.strip_prefix("*.") {
+        fs::read_dir(workdir)
+            .map(|entries| {
+                entries.flatten().any(|entry| {
+                    entry.path().extension() == Some(OsStr::new(extension))
+                        && entry.path().is_file()
+                })
+            })
+            .unwrap_or(false)
+    } else {
+        workdir.join(file).is_file()
+    }
+}
+
+/// Find the member packages of the Cargo workspace in the given directory.
+///
+/// The members are taken from the `members` and `exclude` arrays of the `[workspace]` section of
+/// the manifest, where `*` and `?` match within a single path component. Returns the names of the
+/// packages along with their directories relative to the workspace root, which is `.` if the root
+/// manifest is a package itself.
+pub(crate) fn cargo_workspace_members(root: &Path) -> Vec<(String, String)> {
+    let manifest = match fs::read_to_string(root.join("Cargo.toml")) {
+        Ok(manifest) => manifest,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut members = Vec::new();
+    if let Some(name) = cargo_package_name(&manifest) {
+        members.push((name, String::from(".")));
+    }
+
+    let workspace = toml_section(&manifest, "workspace");
+    let excluded = toml_array(&workspace, "exclude");
+    let mut dirs = toml_array(&workspace, "members")
+        .iter()
+        .flat_map(|pattern| expand(root, pattern, "Cargo.toml"))
+        .filter(|dir| !excluded.contains(dir))
+        .collect::<Vec<_>>();
+    dirs.sort();
+    dirs.dedup();
+
+    for dir in dirs {
+        let name = fs::read_to_string(root.join(&dir).join("Cargo.toml"))
+            .ok()
+            .and_then(|manifest| cargo_package_name(&manifest));
+        if let Some(name) = name {
+            members.push((name, dir));
+        }
+    }
+
+    members
+}
+
+/// Find the member packages of the npm, Yarn, or pnpm workspace in the given directory.
+///
+/// The members are taken from the `workspaces` of the root `package.json`, either as array or as
+/// the `packages` of an object, or else from the `packages` list in `pnpm-workspace.yaml`, where
+/// patterns starting with `!` exclude members. Just like for Cargo workspaces, the names of the
+/// packages are returned along with their directories relative to the workspace root.
+pub(crate) fn npm_workspace_members(root: &Path) -> Vec<(String, String)> {
+    let manifest = fs::read_to_string(root.join("package.json")).unwrap_or_default();
+
+    let mut members = Vec::new();
+    if let Some(name) = json_member(&manifest, "name") {
+        members.push((name, String::from(".")));
+    }
+
+    let mut patterns = json_workspaces(&manifest);
+    if patterns.is_empty() {
+        patterns = fs::read_to_string(root.join("pnpm-workspace.yaml"))
+            .map(|yaml| yaml_packages(&yaml))
+            .unwrap_or_default();
+    }
+
+    let (excluded, included): (Vec<_>, Vec<_>) = patterns
+        .into_iter()
+        .partition(|pattern| pattern.starts_with('!'));
+    let excluded = excluded
+        .iter()
+        .flat_map(|pattern| expand(root, &pattern[1..], "package.json"))
+        .collect::<Vec<_>>();
+    let mut dirs = included
+        .iter()
+        .flat_map(|pattern| expand(root, pattern, "package.json"))
+        .filter(|dir| !excluded.contains(dir))
+        .collect::<Vec<_>>();
+    dirs.sort();
+    dirs.dedup();
+
+    for dir in dirs {
+        let name = fs::read_to_string(root.join(&dir).join("package.json"))
+            .ok()
+            .and_then(|manifest| json_member(&manifest, "name"));
+        if let Some(name) = name {
+            members.push((name, dir));
+        }
+    }
+
+    members
+}
+
+/// Get the first string member with the given name of a JSON document, which is the top-level one
+/// in any conventional `package.json`.
+fn json_member(json: &str, key: &str) -> Option<String> {
+    let (_, rest) = json.split_once(&format!("\"{}\"", key))?;
+    let rest = rest.trim_start().strip_prefix(':')?.trim_start();
+    let (value, _) = rest.strip_prefix('"')?.split_once('"')?;
+
+    Some(value.to_string())
+}
+
+/// Get the workspace patterns of a `package.json`, given either as array or as the `packages` of an
+/// object, as used by Yarn.
+fn json_workspaces(json: &str) -> Vec<String> {
+    let rest = match json.split_once("\"workspaces\"") {
+        Some((_, rest)) => rest.trim_start().trim_start_matches(':').trim_start(),
+        None => return Vec::new(),
+    };
+    let rest = match rest.starts_with('{') {
+        true => rest.split_once("\"packages\"").map_or("", |(_, rest)| rest),
+        false => rest,
+    };
+    let rest = match rest.find('[') {
+        Some(start) => &rest[start + 1..],
+        None => return Vec::new(),
+    };
+    let array = rest.split_once(']').map_or(rest, |(array, _)| array);
+
+    array
+        .split('"')
+        .skip(1)
+        .step_by(2)
+        .map(|pattern| pattern.trim_end_matches('/').to_string())
+        .collect()
+}
+
+/// Get the `packages` list of a `pnpm-workspace.yaml`.
+fn yaml_packages(yaml: &str) -> Vec<String> {
+    yaml.lines()
+        .skip_while(|line| line.trim_end() != "packages:")
+        .skip(1)
+        .take_while(|line| line.trim().is_empty() || line.starts_with([' ', '\t', '-']))
+        .filter_map(|line| line.trim().strip_prefix('-'))
+        .map(|pattern| {
+            let pattern = pattern.split(" #").next().unwrap_or_default().trim();
+            pattern
+                .trim_matches(['"', '\''])
+                .trim_end_matches('/')
+                .to_string()
+        })
+        .filter(|pattern| !pattern.is_empty())
+        .collect()
+}
+
+/// Get the lines of a section of a TOML or INI file, without its header.
+pub(crate) fn toml_section(toml: &str, name: &str) -> String {
+    let header = format!("[{}]", name);
+    // Headers may be followed by a comment
+    toml.lines()
+        .skip_while(|line| line.split('#').next().unwrap_or_default().trim() != header)
+        .skip(1)
+        .take_while(|line| !line.trim_start().starts_with('['))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Get the strings of an array in a TOML section, which may span multiple lines.
+fn toml_array(section: &str, key: &str) -> Vec<String> {
+    let array = section.lines().enumerate().find_map(|(index, line)| {
+        let (name, value) = line.split_once('=')?;
+        (name.trim() == key).then(|| {
+            let rest = section
+                .lines()
+                .skip(index + 1)
+                .collect::<Vec<_>>()
+                .join("\n");
+            format!("{}\n{}", value, rest)
+        })
+    });
+
+    let array = match array {
+        Some(array) => array,
+        None => return Vec::new(),
+    };
+    let array = match array.split_once(']') {
+        Some((array, _)) => array,
+        None => &array,
+    };
+
+    // Strings are at the odd positions between the quotes, comments are not expected in arrays
+    array
+        .split('"')
+        .skip(1)
+        .step_by(2)
+        .map(|path| path.trim_end_matches('/').to_string())
+        .collect()
+}
+
+/// Expand a path pattern to the matching directories with the given manifest, relative to the root.
+fn expand(root: &Path, pattern: &str, manifest: &str) -> Vec<String> {
+    let mut dirs = vec![String::new()];
+
+    for component in pattern.split('/').filter(|c| !c.is_empty() && *c != ".") {
+        let join = |dir: &str, name: &str| match dir {
+            "" => name.to_string(),
+            dir => format!("{}/{}", dir, name),
+        };
+
+        dirs = if component.contains(['*', '?']) {
+            dirs.iter()
+                .flat_map(|dir| {
+                    let mut names = fs::read_dir(root.join(dir))
+                        .into_iter()
+                        .flatten()
+                        .flatten()
+                        .filter(|entry| entry.path().is_dir())
+                        .filter_map(|entry| entry.file_name().into_string().ok())
+                        .filter(|name| wildcard_match(component, name))
+                        .map(|name| join(dir, &name))
+                        .collect::<Vec<_>>();
+                    names.sort();
+                    names
+                })
+                .collect()
+        } else {
+            dirs.iter().map(|dir| join(dir, component)).collect()
+        };
+    }
+
+    dirs.retain(|dir| !dir.is_empty() && root.join(dir).join(manifest).is_file());
+    dirs
+}
+
+/// Match a name against a pattern, where `*` matches any sequence and `?` any single character.
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let name = name.chars().collect::<Vec<_>>();
+
+    // Position of the last `*` in the pattern and of the name where it started matching
+    let (mut p, mut n) = (0, 0);
+    let mut star = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    p = star_p + 1;
+                    n = star_n + 1;
+                    star = Some((star_p, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[test]
+fn discover_cargo_workspace() {
+    use mlua::prelude::*;
+
+    use crate::state::TempDir;
+
+    let tmpdir = TempDir::create().unwrap();
+    let root = tmpdir.path();
+    for (dir, name) in [
+        ("crates/a", "a"),
+        ("crates/b", "b"),
+        ("crates/skip", "skip"),
+    ] {
+        fs::create_dir_all(root.join(dir)).unwrap();
+        let manifest = format!("[package]\nname = \"{}\"\nversion = \"1.0.0\"\n", name);
+        fs::write(root.join(dir).join("Cargo.toml"), manifest).unwrap();
+    }
+    fs::create_dir_all(root.join("crates/empty")).unwrap();
+    fs::write(
+        root.join("Cargo.toml"),
+        "[workspace]\nmembers = [\n    \"crates/*\",\n]\nexclude = [\"crates/skip\"]\n",
+    )
+    .unwrap();
+
+    assert_eq!(
+        cargo_workspace_members(root),
+        [
+            (String::from("a"), String::from("crates/a")),
+            (String::from("b"), String::from("crates/b"))
+        ]
+    );
+    assert!(wildcard_match("c*t?s", "crates"));
+    assert!(!wildcard_match("c*t?s", "crate"));
+
+    let lua = Lua::new();
+    crate::engine::load_helpers(&lua).unwrap();
+    let globals = lua.globals();
+    let ctx = lua.create_table().unwrap();
+    ctx.set("worktree", root.to_string_lossy().as_ref())
+        .unwrap();
+    globals.set("ctx", ctx).unwrap();
+
+    let manifest = concat!(
+        "[package]\nname = \"b\"\nversion = \"1.0.0\"\n\n",
+        "[dependencies]\na = { path = \"../a\", version = \"^1.0.0\" }\nc = \"1.0.0\"\n\n",
+        "[dev-dependencies.a]\npath = \"../a\"\nversion = \"1.0.0\"\n"
+    );
+    let bumped = lua
+        .load(r#"helpers.cargo_workspace{dependencies = true}["crates/b/Cargo.toml"]"#)
+        .eval::<LuaFunction>()
+        .unwrap()
+        .call::<_, String>(("2.0.0", manifest))
+        .unwrap();
+    assert_eq!(
+        bumped,
+        concat!(
+            "[package]\nname = \"b\"\nversion = \"2.0.0\"\n\n",
+            "[dependencies]\na = { path = \"../a\", version = \"^2.0.0\" }\nc = \"1.0.0\"\n\n",
+            "[dev-dependencies.a]\npath = \"../a\"\nversion = \"2.0.0\"\n"
+        )
+    );
+}
+
+#[test]
+fn discover_npm_workspace() {
+    use mlua::prelude::*;
+
+    use crate::state::TempDir;
+
+    let tmpdir = TempDir::create().unwrap();
+    let root = tmpdir.path();
+    for (dir, name) in [
+        ("packages/a", "@scope/a"),
+        ("packages/b", "b"),
+        ("tools/x", "x"),
+    ] {
+        fs::create_dir_all(root.join(dir)).unwrap();
+        let manifest = format!(
+            "{{\n  \"name\": \"{}\",\n  \"version\": \"1.0.0\"\n}}\n",
+            name
+        );
+        fs::write(root.join(dir).join("package.json"), manifest).unwrap();
+    }
+
+    fs::write(
+        root.join("package.json"),
+        "{\n  \"private\": true,\n  \"workspaces\": {\n    \"nohoist\": [\"**/x\"],\n    \"packages\": [\"packages/*\"]\n  }\n}\n",
+    )
+    .unwrap();
+    let expected = [
+        (String::from("@scope/a"), String::from("packages/a")),
+        (String::from("b"), String::from("packages/b")),
+    ];
+    assert_eq!(npm_workspace_members(root), expected);
+
+    fs::write(root.join("package.json"), "{\n  \"private\": true\n}\n").unwrap();
+    fs::write(
+        root.join("pnpm-workspace.yaml"),
+        "packages:\n  - 'packages/*'\n  - \"tools/*\" # tools\n  - '!tools/x'\n",
+    )
+    .unwrap();
+    assert_eq!(npm_workspace_members(root), expected);
+
+    let lua = Lua::new();
+    crate::engine::load_helpers(&lua).unwrap();
+    let ctx = lua.create_table().unwrap();
+    ctx.set("worktree", root.to_string_lossy().as_ref())
+        .unwrap();
+    lua.globals().set("ctx", ctx).unwrap();
+
+    let manifest = concat!(
+        "{\n  \"name\": \"b\",\n  \"version\": \"1.0.0\",\n",
+        "  \"dependencies\": {\n    \"@scope/a\": \"^1.0.0\",\n    \"left-pad\": \"1.0.0\"\n  },\n",
+        "  \"devDependencies\": {\n    \"@scope/a\": \"workspace:*\"\n  }\n}\n"
+    );
+    let bumped = lua
+        .load(r#"helpers.npm_workspace{dependencies = true}["packages/b/package.json"]"#)
+        .eval::<LuaFunction>()
+        .unwrap()
+        .call::<_, String>(("2.0.0", manifest))
+        .unwrap();
+    assert_eq!(
+        bumped,
+        manifest
+            .replace("1.0.0\",\n  \"dep", "2.0.0\",\n  \"dep")
+            .replace("^1.0.0", "^2.0.0")
+    );
+}
@@ -0,0 +1,150 @@
+use std::env;
+
+use crate::links::Links;
+use crate::report::json_string;
+use crate::{Error, Result};
+
+/// Request creating a release via the API of a forge.
+#[derive(Debug, PartialEq, Eq)]
+struct Request {
+    url: String,
+    /// Name and value of the authentication header.
+    auth: (&'static str, String),
+    body: String,
+}
+
+impl Request {
+    /// Build request for the forge of the given links.
+    ///
+    /// GitHub and GitHub Enterprise expect a bearer token, GitLab expects a private token in its
+    /// own header, and both name the release body differently.
+    fn new(links: &Links, token: String, tag: &str, name: &str, notes: &str) -> Self {
+        let (url, auth, notes_field) = if links.gitlab {
+            (
+                format!(
+                    "https://{}/api/v4/projects/{}/releases",
+                    links.host,
+                    links.path.replace('/', "%2F")
+                ),
+                ("PRIVATE-TOKEN", token),
+                "description",
+            )
+        } else {
+            let api = match links.host.as_str() {
+                "github.com" => String::from("https://api.github.com"),
+                host => format!("https://{}/api/v3", host),
+            };
+            (
+                format!("{}/repos/{}/releases", api, links.path),
+                ("Authorization", format!("Bearer {}", token)),
+                "body",
+            )
+        };
+
+        let body = format!(
+            r#"{{"tag_name":{},"name":{},"{}":{}}}"#,
+            json_string(tag),
+            json_string(name),
+            notes_field,
+            json_string(notes)
+        );
+
+        Request { url, auth, body }
+    }
+}
+
+/// Get API token for the forge of the given links from the environment.
+///
+/// The token is taken from `GITLAB_TOKEN` for GitLab, and from `GITHUB_TOKEN` or `GH_TOKEN`
+/// otherwise.
+pub(crate) fn token(links: &Links) -> Result<String> {
+    let variables: &[&str] = if links.gitlab {
+        &["GITLAB_TOKEN"]
+    } else {
+        &["GITHUB_TOKEN", "GH_TOKEN"]
+    };
+    variables
+        .iter()
+        .find_map(|variable| env::var(variable).ok())
+        .ok_or_else(|| Error::MissingToken {
+            variable: variables[0].to_string(),
+        })
+}
+
+/// Create a release for an already pushed tag.
+pub(crate) fn create(
+    links: &Links,
+    token: String,
+    tag: &str,
+    name: &str,
+    notes: &str,
+) -> Result<()> {
+    let request = Request::new(links, token, tag, name, notes);
+    let (header, value) = &request.auth;
+
+    match ureq::post(&request.url)
+        .set(header, value)
+        .set("Content-Type", "application/json")
+        .send_string(&request.body)
+    {
+        Ok(_) => Ok(()),
+        Err(ureq::Error::Status(status, response)) => Err(Error::ReleaseFailed {
+            reason: format!(
+                "{} {}",
+                status,
+                response.into_string().unwrap_or_default().trim()
+            ),
+        }),
+        Err(err) => Err(Error::ReleaseFailed {
+            reason: err.to_string(),
+        }),
+    }
+}
+
+#[test]
+fn build_release_requests() {
+    use crate::prefix::Prefixes;
+
+    let links = |url| Links::from_remote_url(url, Prefixes::default()).unwrap();
+
+    assert_eq!(
+        Request::new(
+            &links("git@github.com:owner/repo.git"),
+            String::from("secret"),
+            "v1.2.3",
+            "1.2.3",
+            "- Fixed \"it\"."
+        ),
+        Request {
+            url: String::from("https://api.github.com/repos/owner/repo/releases"),
+            auth: ("Authorization", String::from("Bearer secret")),
+            body: String::from(r#"{"tag_name":"v1.2.3","name":"1.2.3","body":"- Fixed \"it\"."}"#),
+        }
+    );
+
+    let request = Request::new(
+        &links("https://gitlab.example.com/group/repo.git"),
+        String::from("secret"),
+        "v1.2.3",
+        "1.2.3",
+        "",
+    );
+    assert_eq!(
+        request.url,
+        "https://gitlab.example.com/api/v4/projects/group%2Frepo/releases"
+    );
+    assert_eq!(request.auth, ("PRIVATE-TOKEN", String::from("secret")));
+    assert!(request.body.contains(r#""description":"""#));
+
+    let request = Request::new(
+        &links("https://git.example.com/owner/repo"),
+        String::new(),
+        "v1.2.3",
+        "1.2.3",
+        "",
+    );
+    assert_eq!(
+        request.url,
+        "https://git.example.com/api/v3/repos/owner/repo/releases"
+    );
+}
@@ -0,0 +1,183 @@
+use std::fs;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+use git2::{Pathspec, PathspecFlags, Repository};
+
+use crate::{Error, Result};
+
+/// Pathspecs of files that are never searched, since they are generated or vendored, in addition
+/// to the ones excluded by the user.
+const DEFAULT_EXCLUDES: &[&str] = &[
+    "*.lock",
+    "*-lock.json",
+    "*-lock.yaml",
+    "go.sum",
+    "vendor/*",
+    "*/vendor/*",
+    "node_modules/*",
+    "*/node_modules/*",
+    "third_party/*",
+    "*/third_party/*",
+];
+
+/// Bits of the mode of an index entry giving the type of the file.
+const FILE_TYPE: u32 = 0o170000;
+
+/// Type of regular files in the mode of an index entry, as opposed to symbolic links and
+/// submodules.
+const REGULAR_FILE: u32 = 0o100000;
+
+/// Tracked file containing the previous version.
+pub(crate) struct Found {
+    /// Path of the file, relative to the root of the worktree.
+    pub(crate) file: PathBuf,
+    pub(crate) contents: String,
+    /// Numbers and contents of the lines containing the version, numbered from 1.
+    pub(crate) lines: Vec<(usize, String)>,
+    /// Number of occurrences of the version.
+    pub(crate) count: usize,
+}
+
+/// Search tracked text files for the exact previous version.
+///
+/// Only files matching any of the included pathspecs, or all files if none are given, are
+/// searched, except for those matching any of the excluded pathspecs or the default ones for
+/// lockfiles and vendored code. Binary files and files that are not valid UTF-8 are skipped, just
+/// like symbolic links and submodules, whose targets might not even be part of the worktree.
+pub(crate) fn search(
+    repository: &Repository,
+    workdir: &Path,
+    previous: &str,
+    include: &[String],
+    exclude: &[String],
+) -> Result<Vec<Found>> {
+    let included = Pathspec::new(include)?;
+    let excluded = Pathspec::new(
+        DEFAULT_EXCLUDES
+            .iter()
+            .copied()
+            .chain(exclude.iter().map(String::as_str)),
+    )?;
+
+    let mut found = Vec::new();
+    for entry in repository.index()?.iter() {
+        if entry.mode & FILE_TYPE != REGULAR_FILE {
+            continue;
+        }
+        let file = PathBuf::from(String::from_utf8_lossy(&entry.path).into_owned());
+        if (!include.is_empty() && !included.matches_path(&file, PathspecFlags::DEFAULT))
+            || excluded.matches_path(&file, PathspecFlags::DEFAULT)
+        {
+            continue;
+        }
+
+        // Files excluded by sparse-checkout, deleted, or replaced by links in the worktree are
+        // skipped as well
+        let path = workdir.join(&file);
+        if fs::symlink_metadata(&path).map_or(true, |metadata| !metadata.is_file()) {
+            continue;
+        }
+        let contents = match fs::read(&path) {
+            Ok(contents) if !contents.contains(&0) => match String::from_utf8(contents) {
+                Ok(contents) => contents,
+                Err(_) => continue,
+            },
+            _ => continue,
+        };
+
+        let offsets = occurrences(&contents, previous);
+        if offsets.is_empty() {
+            continue;
+        }
+        let mut lines = Vec::new();
+        for offset in &offsets {
+            let number = contents[..*offset].matches('\n').count() + 1;
+            if lines.last().map(|(last, _)| *last) != Some(number) {
+                let start = contents[..*offset].rfind('\n').map_or(0, |start| start + 1);
+                let end = contents[*offset..]
+                    .find('\n')
+                    .map_or(contents.len(), |end| offset + end);
+                lines.push((number, contents[start..end].to_string()));
+            }
+        }
+
+        found.push(Found {
+            file,
+            count: offsets.len(),
+            lines,
+            contents,
+        });
+    }
+
+    Ok(found)
+}
+
+/// Find the byte offsets of the exact version in the contents.
+///
+/// Occurrences that are part of a longer version, like `1.2.3` in `11.2.3`, `1.2.30`, or
+/// `1.2.3.4`, are left out.
+fn occurrences(contents: &str, version: &str) -> Vec<usize> {
+    contents
+        .match_indices(version)
+        .map(|(offset, _)| offset)
+        .filter(|offset| {
+            let before = contents[..*offset].chars().next_back();
+            let mut after = contents[offset + version.len()..].chars();
+            let continues = match after.next() {
+                Some('.') => after.next().is_some_and(|c| c.is_ascii_digit()),
+                Some(c) => c.is_ascii_digit(),
+                None => false,
+            };
+            !before.is_some_and(|c| c.is_ascii_digit() || c == '.') && !continues
+        })
+        .collect()
+}
+
+/// Replace all exact occurrences of the previous version in the contents.
+pub(crate) fn replace(contents: &str, previous: &str, version: &str) -> String {
+    let mut replaced = String::with_capacity(contents.len());
+    let mut rest = 0;
+    for offset in occurrences(contents, previous) {
+        replaced.push_str(&contents[rest..offset]);
+        replaced.push_str(version);
+        rest = offset + previous.len();
+    }
+    replaced.push_str(&contents[rest..]);
+    replaced
+}
+
+/// Ask a yes or no question on the terminal, where anything but yes counts as no.
+///
+/// Without a terminal, there is nobody to ask, so this fails instead of silently refusing.
+pub(crate) fn confirm(question: &str) -> Result<bool> {
+    if !io::stdin().is_terminal() {
+        return Err(Error::NotConfirmed);
+    }
+
+    eprint!("{} [y/N] ", question);
+    io::stderr().flush()?;
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+#[test]
+fn replace_exact_versions() {
+    assert_eq!(
+        replace("v1.2.3, 1.2.3.\n11.2.3 1.2.30 1.2.3.4\n", "1.2.3", "1.3.0"),
+        "v1.3.0, 1.3.0.\n11.2.3 1.2.30 1.2.3.4\n"
+    );
+    assert_eq!(occurrences("1.2.3", "1.2.3"), [0]);
+
+    let excluded = Pathspec::new(DEFAULT_EXCLUDES).unwrap();
+    for file in ["Cargo.lock", "web/package-lock.json", "a/vendor/b/c.go"] {
+        assert!(
+            excluded.matches_path(Path::new(file), PathspecFlags::DEFAULT),
+            "{}",
+            file
+        );
+    }
+    assert!(!excluded.matches_path(Path::new("src/lock.rs"), PathspecFlags::DEFAULT));
+}
@@ -0,0 +1,287 @@
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Report of a whole bump, as returned by [`bump`](crate::bump).
+#[derive(Debug)]
+pub struct BumpReport {
+    /// Version the files were bumped to.
+    pub version: String,
+    /// Reports of all processed files, sorted by path.
+    pub files: Vec<FileReport>,
+    /// Outcomes of the global hooks.
+    pub hooks: Vec<HookReport>,
+    /// Time taken by evaluating the config files.
+    pub config_duration: Duration,
+    /// Time taken by the whole bump.
+    pub duration: Duration,
+}
+
+/// Report of a single file.
+#[derive(Debug)]
+pub struct FileReport {
+    /// Path of the file, relative to the root of the worktree.
+    pub path: PathBuf,
+    /// What happened to the file.
+    pub outcome: FileOutcome,
+    /// Outcomes of the hooks of the file.
+    pub hooks: Vec<HookReport>,
+    /// Time taken by bumping the file, including its hooks.
+    pub duration: Duration,
+    /// Time taken by the Lua functions of the mapping, without its hooks.
+    pub lua_duration: Duration,
+    /// Number of bytes written to the file.
+    pub bytes_written: usize,
+}
+
+/// What happened to a single file.
+#[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FileOutcome {
+    /// The file was written with changed contents.
+    Written,
+    /// The bumping function returned the contents unchanged.
+    Unchanged,
+    /// The changes were only shown, nothing was written.
+    Previewed,
+    /// The `enabled` guard of the mapping returned `false`, or the bumping function asked to skip
+    /// the file, so the file was not touched.
+    Disabled,
+    /// The file is not tracked by Git, so it was not touched.
+    Untracked,
+    /// The file does not exist, and the bumping function did not ask to create it.
+    Missing,
+    /// The file is excluded from the worktree by sparse-checkout, so it was not touched.
+    NotMaterialized,
+    /// Bumping the optional file failed, so it was skipped.
+    Skipped {
+        /// Phase in which bumping failed, like `func` or `post`.
+        phase: String,
+        /// Error message of the failure.
+        message: String,
+    },
+}
+
+/// Outcome of successfully run hooks.
+#[derive(Debug)]
+pub struct HookReport {
+    /// Either `pre` or `post`.
+    pub phase: &'static str,
+    /// Number of attempts needed, more than one if a post hook was retried.
+    pub attempts: u32,
+    /// Time taken by the hooks, including all attempts.
+    pub duration: Duration,
+    /// Captured output of the hook commands, empty for hook functions.
+    pub output: String,
+}
+
+impl BumpReport {
+    /// Get number of optional files that were skipped.
+    pub fn skipped(&self) -> usize {
+        self.files
+            .iter()
+            .filter(|file| matches!(file.outcome, FileOutcome::Skipped { .. }))
+            .count()
+    }
+
+    /// Render report as JSON object.
+    ///
+    /// Durations are given in milliseconds.
+    pub fn to_json(&self) -> String {
+        let files = self
+            .files
+            .iter()
+            .map(|file| {
+                let outcome = match &file.outcome {
+                    FileOutcome::Written => r#""outcome":"written""#.to_string(),
+                    FileOutcome::Unchanged => r#""outcome":"unchanged""#.to_string(),
+                    FileOutcome::Previewed => r#""outcome":"previewed""#.to_string(),
+                    FileOutcome::Disabled => r#""outcome":"disabled""#.to_string(),
+                    FileOutcome::Untracked => r#""outcome":"untracked""#.to_string(),
+                    FileOutcome::Missing => r#""outcome":"missing""#.to_string(),
+                    FileOutcome::NotMaterialized => r#""outcome":"not-materialized""#.to_string(),
+                    FileOutcome::Skipped { phase, message } => format!(
+                        r#""outcome":"skipped","phase":{},"message":{}"#,
+                        json_string(phase),
+                        json_string(message)
+                    ),
+                };
+
+                format!(
+                    r#"{{"path":{},{},"hooks":{},"duration_ms":{},"lua_ms":{},"bytes_written":{}}}"#,
+                    json_string(&file.path.to_string_lossy()),
+                    outcome,
+                    hooks_to_json(&file.hooks),
+                    file.duration.as_millis(),
+                    file.lua_duration.as_millis(),
+                    file.bytes_written
+                )
+            })
+            .collect::<Vec<_>>();
+
+        format!(
+            r#"{{"version":{},"files":[{}],"hooks":{},"config_ms":{},"duration_ms":{}}}"#,
+            json_string(&self.version),
+            files.join(","),
+            hooks_to_json(&self.hooks),
+            self.config_duration.as_millis(),
+            self.duration.as_millis()
+        )
+    }
+
+    /// Render durations and written bytes as table, with the slowest files first.
+    ///
+    /// Paths of files are rendered by `show`, which gets them relative to the root of the worktree.
+    pub fn timings(&self, show: impl Fn(&Path) -> String) -> String {
+        let mut files = self.files.iter().collect::<Vec<_>>();
+        files.sort_by_key(|file| std::cmp::Reverse(file.duration));
+
+        let paths = files
+            .iter()
+            .map(|file| show(&file.path))
+            .collect::<Vec<_>>();
+        let config_row = "Config evaluation";
+        let width = paths
+            .iter()
+            .map(|path| path.len())
+            .max()
+            .unwrap_or(0)
+            .max(config_row.len());
+
+        let mut table = format!(
+            "{:width$}  {:>9}  {:>9}  {:>9}\n",
+            "File", "Total", "Lua", "Written"
+        );
+        for (file, path) in files.iter().zip(&paths) {
+            let _ = writeln!(
+                table,
+                "{:width$}  {:>6} ms  {:>6} ms  {:>9}",
+                path,
+                file.duration.as_millis(),
+                file.lua_duration.as_millis(),
+                format!("{} B", file.bytes_written)
+            );
+        }
+        let _ = writeln!(
+            table,
+            "{:width$}  {:>6} ms",
+            config_row,
+            self.config_duration.as_millis()
+        );
+        let _ = writeln!(
+            table,
+            "{:width$}  {:>6} ms  {:>9}  {:>9}",
+            "Total",
+            self.duration.as_millis(),
+            "",
+            format!(
+                "{} B",
+                self.files
+                    .iter()
+                    .map(|file| file.bytes_written)
+                    .sum::<usize>()
+            )
+        );
+
+        table
+    }
+}
+
+/// Render hook reports as JSON array.
+fn hooks_to_json(hooks: &[HookReport]) -> String {
+    let hooks = hooks
+        .iter()
+        .map(|hook| {
+            format!(
+                r#"{{"phase":{},"attempts":{},"duration_ms":{},"output":{}}}"#,
+                json_string(hook.phase),
+                hook.attempts,
+                hook.duration.as_millis(),
+                json_string(&hook.output)
+            )
+        })
+        .collect::<Vec<_>>();
+
+    format!("[{}]", hooks.join(","))
+}
+
+/// Render string as quoted and escaped JSON string.
+pub(crate) fn json_string(s: &str) -> String {
+    let mut json = String::with_capacity(s.len() + 2);
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+#[test]
+fn render_report_as_json() {
+    let report = BumpReport {
+        version: String::from("1.2.3"),
+        files: vec![
+            FileReport {
+                path: PathBuf::from("VERSION"),
+                outcome: FileOutcome::Written,
+                hooks: vec![HookReport {
+                    phase: "post",
+                    attempts: 2,
+                    duration: Duration::from_millis(5),
+                    output: String::from("Published\n"),
+                }],
+                duration: Duration::from_millis(7),
+                lua_duration: Duration::from_millis(2),
+                bytes_written: 6,
+            },
+            FileReport {
+                path: PathBuf::from("CHANGES.md"),
+                outcome: FileOutcome::Skipped {
+                    phase: String::from("func"),
+                    message: String::from("bad \"quote\"\n"),
+                },
+                hooks: Vec::new(),
+                duration: Duration::from_millis(1),
+                lua_duration: Duration::from_millis(1),
+                bytes_written: 0,
+            },
+        ],
+        hooks: Vec::new(),
+        config_duration: Duration::from_millis(2),
+        duration: Duration::from_millis(10),
+    };
+
+    assert_eq!(report.skipped(), 1);
+    assert_eq!(
+        report.to_json(),
+        concat!(
+            r#"{"version":"1.2.3","files":["#,
+            r#"{"path":"VERSION","outcome":"written","#,
+            r#""hooks":[{"phase":"post","attempts":2,"duration_ms":5,"output":"Published\n"}],"#,
+            r#""duration_ms":7,"lua_ms":2,"bytes_written":6},"#,
+            r#"{"path":"CHANGES.md","outcome":"skipped","phase":"func","#,
+            r#""message":"bad \"quote\"\n","hooks":[],"duration_ms":1,"lua_ms":1,"bytes_written":0}"#,
+            r#"],"hooks":[],"config_ms":2,"duration_ms":10}"#
+        )
+    );
+    assert_eq!(
+        report.timings(|path| path.to_string_lossy().into_owned()),
+        concat!(
+            "File                   Total        Lua    Written\n",
+            "VERSION                 7 ms       2 ms        6 B\n",
+            "CHANGES.md              1 ms       1 ms        0 B\n",
+            "Config evaluation       2 ms\n",
+            "Total                  10 ms                   6 B\n",
+        )
+    );
+}
@@ -0,0 +1,99 @@
+use std::thread;
+use std::time::Duration;
+
+/// Upper bound of the delay between two attempts, unless the first delay is longer already.
+const MAX_DELAY: Duration = Duration::from_secs(60);
+
+/// Exponential backoff for operations that might fail only temporarily.
+pub(crate) struct Backoff {
+    /// Number of retries after the first attempt.
+    pub(crate) retries: u32,
+    /// Delay before the first retry, doubling with every further retry.
+    pub(crate) delay: Duration,
+}
+
+impl Backoff {
+    /// Get the delays before each retry.
+    ///
+    /// Doubling stops at [`MAX_DELAY`], so many retries neither overflow nor wait for hours.
+    fn delays(&self) -> impl Iterator<Item = Duration> {
+        let max = self.delay.max(MAX_DELAY);
+        std::iter::successors(Some(self.delay), move |delay| {
+            Some(delay.saturating_mul(2).min(max))
+        })
+        .take(self.retries as usize)
+    }
+
+    /// Run an operation, retrying it as long as it fails with retryable errors.
+    ///
+    /// Before each retry, `warn` is called with the number of the failed attempt, the delay, and
+    /// the error. Returns the result of the operation along with the number of attempts needed.
+    pub(crate) fn run<T, E>(
+        &self,
+        mut operation: impl FnMut() -> Result<T, E>,
+        retryable: impl Fn(&E) -> bool,
+        mut warn: impl FnMut(u32, Duration, &E),
+    ) -> Result<(T, u32), E> {
+        let mut delays = self.delays();
+        let mut attempt = 1;
+        loop {
+            match operation() {
+                Ok(value) => return Ok((value, attempt)),
+                Err(err) if retryable(&err) => match delays.next() {
+                    Some(delay) => {
+                        warn(attempt, delay, &err);
+                        thread::sleep(delay);
+                        attempt += 1;
+                    }
+                    None => return Err(err),
+                },
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
+
+#[test]
+fn retry_with_capped_backoff() {
+    let backoff = Backoff {
+        retries: 100,
+        delay: Duration::from_secs(1),
+    };
+    let delays = backoff.delays().collect::<Vec<_>>();
+    assert_eq!(delays.len(), 100);
+    assert_eq!(delays[..3], [1, 2, 4].map(Duration::from_secs));
+    assert_eq!(delays[99], MAX_DELAY);
+
+    let backoff = Backoff {
+        retries: 3,
+        delay: Duration::ZERO,
+    };
+    let mut results = vec![Ok(42), Err("locked"), Err("locked")];
+    let mut warnings = Vec::new();
+    let result = backoff.run(
+        || results.pop().unwrap(),
+        |err| *err == "locked",
+        |attempt, _, err| warnings.push((attempt, *err)),
+    );
+    assert_eq!(result, Ok((42, 3)));
+    assert_eq!(warnings, [(1, "locked"), (2, "locked")]);
+
+    let mut results = vec![Ok(42), Err("broken"), Err("locked")];
+    let result = backoff.run(
+        || results.pop().unwrap(),
+        |err| *err == "locked",
+        |_, _, _| {},
+    );
+    assert_eq!(result, Err("broken"));
+
+    let mut attempts = 0;
+    let result = backoff.run(
+        || -> Result<(), _> {
+            attempts += 1;
+            Err("locked")
+        },
+        |err| *err == "locked",
+        |_, _, _| {},
+    );
+    assert_eq!((result, attempts), (Err("locked"), 4));
+}
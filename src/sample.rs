@@ -0,0 +1,162 @@
+/// Ecosystems with sample configs of their own, printed instead of the full sample config.
+#[derive(Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub(crate) enum Ecosystem {
+    /// Cargo packages and workspaces
+    Rust,
+    /// npm, Yarn, and pnpm packages and workspaces
+    Node,
+    /// Python packages, built with setuptools, Poetry, or any other PEP 621 backend
+    Python,
+    /// Maven and Gradle projects
+    Java,
+}
+
+impl Ecosystem {
+    fn name(self) -> &'static str {
+        match self {
+            Ecosystem::Rust => "Rust",
+            Ecosystem::Node => "Node.js",
+            Ecosystem::Python => "Python",
+            Ecosystem::Java => "Java",
+        }
+    }
+}
+
+/// Recipe of the sample configs, as a single entry of the returned table.
+struct Recipe {
+    /// Ecosystems the recipe belongs to, or all of them if empty.
+    ecosystems: &'static [Ecosystem],
+    /// Lua code of the table entry, including its comments.
+    code: &'static str,
+}
+
+/// Registry of the recipes the sample configs of the ecosystems are built from.
+const RECIPES: &[Recipe] = &[
+    Recipe {
+        ecosystems: &[Ecosystem::Rust],
+        code: r#"-- version in the [package] section; for workspaces, use
+    -- helpers.cargo_workspace instead
+    ["Cargo.toml"] = function(version, content)
+        return helpers.cargo_toml(version, content), {lockfile = "cargo"}
+    end,"#,
+    },
+    Recipe {
+        ecosystems: &[Ecosystem::Node],
+        code: r#"-- top-level "version" member; for workspaces, use
+    -- helpers.npm_workspace instead, and use "yarn" or "pnpm" as lockfile
+    -- if the project does not use npm
+    ["package.json"] = function(version, content)
+        return helpers.package_json(version, content), {lockfile = "npm"}
+    end,"#,
+    },
+    Recipe {
+        ecosystems: &[Ecosystem::Python],
+        code: r#"-- version in the [project] section, or in the [tool.poetry]
+    -- section of Poetry projects
+    ["pyproject.toml"] = helpers.pyproject_toml,
+
+    -- version in the [metadata] section of setuptools projects
+    ["setup.cfg"] = {func = helpers.setup_cfg, optional = true},"#,
+    },
+    Recipe {
+        ecosystems: &[Ecosystem::Python],
+        code: r#"-- version attribute of the package; rename the key to match the
+    -- name of your package
+    ["src/package/__init__.py"] = function(version, content)
+        return (content:gsub(
+                   '(__version__%s*=%s*)(["\'])[^"\']*%2',
+                   function(prefix, quote)
+                return prefix .. quote .. version .. quote
+            end, 1
+               ))
+    end,"#,
+    },
+    Recipe {
+        ecosystems: &[Ecosystem::Java],
+        code: r#"-- version of the Maven project itself, skipping the version of
+    -- its parent POM, which comes first if there is one
+    ["pom.xml"] = function(version, content)
+        local _, parent_end = content:find("</parent>", 1, true)
+        local head = content:sub(1, parent_end or 0)
+        local tail = content:sub((parent_end or 0) + 1)
+
+        return head .. tail:gsub(
+                   "<version>[^<]*</version>",
+                   function()
+                return ("<version>%s</version>"):format(version)
+            end, 1
+               )
+    end,"#,
+    },
+    Recipe {
+        ecosystems: &[Ecosystem::Java],
+        code: r#"-- "version" property of Gradle builds, which set the version of
+    -- the project with `version = property("version")` or similar
+    ["gradle.properties"] = function(version, content)
+        return (("\n" .. content):gsub(
+                   "\n(version[ \t]*[=:][ \t]*)[^\n]*", function(prefix)
+                return "\n" .. prefix .. version
+            end, 1
+               ):sub(2))
+    end,"#,
+    },
+    Recipe {
+        ecosystems: &[],
+        code: r#"-- new heading for the version, in the Keep a Changelog format
+    ["CHANGELOG.md"] = {func = helpers.keep_a_changelog, optional = true},"#,
+    },
+];
+
+/// Build the sample config of an ecosystem from the recipes of the registry.
+pub(crate) fn sample_config(ecosystem: Ecosystem) -> String {
+    let recipes = RECIPES
+        .iter()
+        .filter(|recipe| recipe.ecosystems.is_empty() || recipe.ecosystems.contains(&ecosystem))
+        .map(|recipe| format!("    {}\n", recipe.code))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    format!(
+        "-- Sample config for {} projects, adjust to your needs. See the bundled\n-- helpers in the documentation for the details of each recipe.\n\nreturn {{\n{}}}\n",
+        ecosystem.name(),
+        recipes
+    )
+}
+
+#[test]
+fn build_sample_configs() {
+    use clap::ValueEnum;
+
+    for ecosystem in Ecosystem::value_variants() {
+        let config = sample_config(*ecosystem);
+        assert!(config.contains("CHANGELOG.md"));
+        assert!(config.lines().all(|line| line.len() <= 78), "{}", config);
+        mlua::Lua::new().load(&config).into_function().unwrap();
+    }
+
+    let lua = mlua::Lua::new();
+    lua.globals()
+        .set("helpers", lua.create_table().unwrap())
+        .unwrap();
+    let config = sample_config(Ecosystem::Java);
+    let recipes = lua.load(&config).eval::<mlua::Table>().unwrap();
+    let bump = |file: &str, content: &str| {
+        recipes
+            .get::<_, mlua::Function>(file)
+            .unwrap()
+            .call::<_, String>(("1.2.3", content))
+            .unwrap()
+    };
+
+    assert_eq!(
+        bump(
+            "pom.xml",
+            "<parent><version>9</version></parent>\n<version>1.0.0</version>\n"
+        ),
+        "<parent><version>9</version></parent>\n<version>1.2.3</version>\n"
+    );
+    assert_eq!(
+        bump("gradle.properties", "group=x\nversion = 1.0.0\n"),
+        "group=x\nversion = 1.2.3\n"
+    );
+}
@@ -0,0 +1,172 @@
+use std::fmt;
+
+/// Part of a version to increment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Increment {
+    Major,
+    Minor,
+    Patch,
+}
+
+/// Semantic version, see <https://semver.org>.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Version {
+    major: u64,
+    minor: u64,
+    patch: u64,
+    pre: Option<String>,
+    build: Option<String>,
+}
+
+impl Version {
+    /// Parse version string, allowing a leading `v`.
+    pub(crate) fn parse(version: &str) -> Option<Self> {
+        let version = version.strip_prefix('v').unwrap_or(version);
+
+        let (version, build) = match version.split_once('+') {
+            Some((version, build)) => (version, Some(build)),
+            None => (version, None),
+        };
+        let (core, pre) = match version.split_once('-') {
+            Some((core, pre)) => (core, Some(pre)),
+            None => (version, None),
+        };
+
+        let mut numbers = core.split('.').map(|number| number.parse().ok());
+        let (major, minor, patch) = match (numbers.next(), numbers.next(), numbers.next()) {
+            (Some(Some(major)), Some(Some(minor)), Some(Some(patch))) => (major, minor, patch),
+            _ => return None,
+        };
+
+        if numbers.next().is_some()
+            || [pre, build]
+                .into_iter()
+                .flatten()
+                .any(|identifiers| !is_valid_identifiers(identifiers))
+        {
+            return None;
+        }
+
+        Some(Version {
+            major,
+            minor,
+            patch,
+            pre: pre.map(String::from),
+            build: build.map(String::from),
+        })
+    }
+
+    /// Increment the given part of the version, dropping pre-release and build metadata.
+    ///
+    /// Just like with other tools, a pre-release of the resulting version is simply released, so
+    /// a minor increment turns `1.3.0-rc.1` into `1.3.0`, but `1.3.1-rc.1` into `1.4.0`.
+    pub(crate) fn increment(self, increment: Increment) -> Self {
+        let is_pre = self.pre.is_some();
+        let (major, minor, patch) = match increment {
+            Increment::Major if is_pre && self.minor == 0 && self.patch == 0 => (self.major, 0, 0),
+            Increment::Major => (self.major + 1, 0, 0),
+            Increment::Minor if is_pre && self.patch == 0 => (self.major, self.minor, 0),
+            Increment::Minor => (self.major, self.minor + 1, 0),
+            Increment::Patch if is_pre => (self.major, self.minor, self.patch),
+            Increment::Patch => (self.major, self.minor, self.patch + 1),
+        };
+
+        Version {
+            major,
+            minor,
+            patch,
+            pre: None,
+            build: None,
+        }
+    }
+
+    /// Set pre-release identifiers, returns `None` if they are not valid.
+    pub(crate) fn with_pre(self, pre: &str) -> Option<Self> {
+        is_valid_identifiers(pre).then(|| Version {
+            pre: Some(pre.to_string()),
+            ..self
+        })
+    }
+
+    /// Set build metadata, returns `None` if it is not valid.
+    pub(crate) fn with_build(self, build: &str) -> Option<Self> {
+        is_valid_identifiers(build).then(|| Version {
+            build: Some(build.to_string()),
+            ..self
+        })
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if let Some(pre) = &self.pre {
+            write!(f, "-{}", pre)?;
+        }
+        if let Some(build) = &self.build {
+            write!(f, "+{}", build)?;
+        }
+        Ok(())
+    }
+}
+
+/// Check dot-separated identifiers of pre-release or build metadata.
+fn is_valid_identifiers(identifiers: &str) -> bool {
+    identifiers.split('.').all(|identifier| {
+        !identifier.is_empty()
+            && identifier
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-')
+    })
+}
+
+#[test]
+fn parse_and_display_versions() {
+    for version in [
+        "1.2.3",
+        "1.2.3-rc.1",
+        "1.2.3+abc123",
+        "10.20.30-alpha-1.x+b.2",
+    ] {
+        assert_eq!(Version::parse(version).unwrap().to_string(), version);
+    }
+    assert_eq!(Version::parse("v1.2.3").unwrap().to_string(), "1.2.3");
+
+    for version in [
+        "1.2",
+        "1.2.3.4",
+        "1.x.3",
+        "1.2.3-",
+        "1.2.3-rc..1",
+        "1.2.3+a_b",
+    ] {
+        assert_eq!(Version::parse(version), None, "{}", version);
+    }
+}
+
+#[test]
+fn increment_versions() {
+    let next = |version: &str, increment| {
+        Version::parse(version)
+            .unwrap()
+            .increment(increment)
+            .to_string()
+    };
+
+    assert_eq!(next("1.2.3+abc", Increment::Major), "2.0.0");
+    assert_eq!(next("1.2.3", Increment::Minor), "1.3.0");
+    assert_eq!(next("1.2.3", Increment::Patch), "1.2.4");
+    assert_eq!(next("2.0.0-rc.1", Increment::Major), "2.0.0");
+    assert_eq!(next("1.3.0-rc.1", Increment::Minor), "1.3.0");
+    assert_eq!(next("1.3.1-rc.1", Increment::Minor), "1.4.0");
+    assert_eq!(next("1.2.4-rc.1", Increment::Patch), "1.2.4");
+
+    let version = Version::parse("1.2.3")
+        .unwrap()
+        .increment(Increment::Minor)
+        .with_pre("rc.1")
+        .unwrap()
+        .with_build("abc123")
+        .unwrap();
+    assert_eq!(version.to_string(), "1.3.0-rc.1+abc123");
+}
@@ -1,13 +1,53 @@
 use std::collections::HashMap;
+use std::env;
+use std::ffi::OsStr;
 use std::fs;
 use std::ops::Deref;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::process;
 use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use git2::Repository;
 use mlua::prelude::*;
+use unicode_normalization::UnicodeNormalization;
 
-use crate::{Error, Result};
+use crate::links::Links;
+use crate::mapping::{
+    escapes_worktree, is_outside_worktree, key_components, resolve_parents, Mapping,
+};
+use crate::paths::PathDisplay;
+use crate::{engine, git, project, Error, Result};
+
+/// Name of the Lua registry value with the `current_version` functions, keyed by file.
+const EXTRACTORS: &str = "git-bump.extractors";
+
+/// Name of the Lua registry value with the files of mappings marked as `source_of_truth`.
+const SOURCES_OF_TRUTH: &str = "git-bump.sources_of_truth";
+
+/// Settings that influence how the state is built, usually given on the command line.
+#[derive(Clone, Default)]
+pub struct Settings {
+    /// Fail if a config file overrides a mapping of a previous config file.
+    pub no_override: bool,
+    /// Ignore per-user config file.
+    pub no_user_config: bool,
+    /// Ignore per-repository config file in `$GIT_DIR`.
+    pub no_local_config: bool,
+    /// Ignore per-repository config file in the worktree.
+    pub no_shared_config: bool,
+    /// Allow mappings for files outside of the worktree.
+    pub allow_outside_worktree: bool,
+    /// Also use the config files in the worktrees of all repositories the current one is nested
+    /// in.
+    pub outer_config: bool,
+    /// Keep the paths of config files as they are found, instead of resolving symlinks.
+    pub no_canonicalize: bool,
+    /// Show full paths in messages, instead of paths relative to the current directory or the
+    /// worktree.
+    pub absolute_paths: bool,
+}
 
 /// State object for bumping actions.
 ///
@@ -18,17 +58,89 @@ use crate::{Error, Result};
 /// private. Use the `get_*` methods to access them.
 #[derive(Default)]
 pub(crate) struct State {
+    version: Option<String>,
+    settings: Settings,
     lua: Option<Rc<Lua>>,
     repository: Option<Rc<Repository>>,
     workdir: Option<Rc<PathBuf>>,
+    paths: Option<Rc<PathDisplay>>,
     config_files: Option<Rc<Vec<PathBuf>>>,
-    file_mapping: Option<Rc<HashMap<PathBuf, LuaRegistryKey>>>,
+    file_mapping: Option<Rc<HashMap<PathBuf, Mapping>>>,
+    /// Keys of mappings whose files do not exist, filled along with the file mapping.
+    missing_keys: Vec<String>,
+    /// Mappings of missing files that might create them, filled along with the file mapping.
+    missing_file_mapping: Rc<HashMap<PathBuf, Mapping>>,
+    tmpdir: Option<TempDir>,
+}
+
+/// Temporary directory of a single run, provided to the config files as `ctx.tmpdir`.
+///
+/// Tests use it as well, so their directories are removed even if they fail.
+pub(crate) struct TempDir {
+    path: PathBuf,
+    /// Whether the directory is removed along with the state, which is not the case for
+    /// directories shared with other states.
+    owned: bool,
+}
+
+impl TempDir {
+    /// Create a new, empty directory in the temporary directory of the system.
+    pub(crate) fn create() -> Result<Self> {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.subsec_nanos())
+            .unwrap_or_default();
+        let path = env::temp_dir().join(format!(
+            "git-bump-{}-{}-{}",
+            process::id(),
+            nanos,
+            COUNT.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir(&path)?;
+
+        Ok(TempDir { path, owned: true })
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        if self.owned {
+            let _ = fs::remove_dir_all(&self.path);
+        }
+    }
 }
 
 impl State {
+    /// Create state for bumping to the given version.
+    ///
+    /// The version is needed to evaluate config files that return a function instead of a table.
+    pub(crate) fn new(version: Option<String>, settings: Settings) -> Self {
+        State {
+            version,
+            settings,
+            ..Default::default()
+        }
+    }
+
     /// Get shared Lua instance.
-    pub(crate) fn get_lua(&mut self) -> Rc<Lua> {
-        Rc::clone(self.lua.get_or_insert_with(|| Rc::new(Lua::new())))
+    ///
+    /// The instance comes with the bundled helpers library and an empty global `hooks` table,
+    /// which can be filled by the config files with hooks that run once per bump.
+    pub(crate) fn get_lua(&mut self) -> Result<Rc<Lua>> {
+        if let Some(lua) = &self.lua {
+            Ok(Rc::clone(lua))
+        } else {
+            let lua = Lua::new();
+            engine::load_helpers(&lua)?;
+            lua.globals().set("hooks", lua.create_table()?)?;
+
+            Ok(Rc::clone(self.lua.insert(Rc::new(lua))))
+        }
     }
 
     /// Get Repository object.
@@ -57,36 +169,218 @@ impl State {
         }
     }
 
+    /// Get presentation of files in messages, which every path shown to the user goes through.
+    pub(crate) fn get_paths(&mut self) -> Result<Rc<PathDisplay>> {
+        if let Some(paths) = &self.paths {
+            Ok(Rc::clone(paths))
+        } else {
+            let paths = PathDisplay::from_config(
+                &self.get_workdir()?,
+                &self.get_repository()?.config()?,
+                self.settings.absolute_paths,
+            );
+            Ok(Rc::clone(self.paths.insert(Rc::new(paths))))
+        }
+    }
+
+    /// Get temporary directory of this run, which is removed along with the state.
+    pub(crate) fn get_tmpdir(&mut self) -> Result<PathBuf> {
+        if let Some(tmpdir) = &self.tmpdir {
+            Ok(tmpdir.path().to_path_buf())
+        } else {
+            Ok(self.tmpdir.insert(TempDir::create()?).path().to_path_buf())
+        }
+    }
+
+    /// Use the temporary directory of another state, which is left in place afterwards.
+    pub(crate) fn share_tmpdir(&mut self, path: &Path) {
+        self.tmpdir = Some(TempDir {
+            path: path.to_path_buf(),
+            owned: false,
+        });
+    }
+
+    /// Get name of the currently checked out branch.
+    ///
+    /// Returns `None` if `HEAD` is detached.
+    pub(crate) fn get_branch(&mut self) -> Result<Option<String>> {
+        let repository = self.get_repository()?;
+        let head = repository.find_reference("HEAD")?;
+
+        let branch = head
+            .symbolic_target()
+            .and_then(|target| target.strip_prefix("refs/heads/"))
+            .map(String::from);
+
+        Ok(branch)
+    }
+
     /// Get list of available configuration files.
     pub(crate) fn get_config_files(&mut self) -> Result<Rc<Vec<PathBuf>>> {
         if let Some(config_files) = &self.config_files {
             Ok(Rc::clone(config_files))
         } else {
-            let config_user =
-                home::home_dir().and_then(|p| p.join(".git-bump.lua").canonicalize().ok());
-            let config_repo_unshared = self
-                .get_repository()?
-                .path()
-                .join("git-bump.lua")
-                .canonicalize()
-                .ok();
-            let config_repo_shared = self
-                .get_workdir()?
-                .join(".git-bump.lua")
-                .canonicalize()
-                .ok();
-
-            let config_files = [config_user, config_repo_unshared, config_repo_shared]
+            let config_user = if self.settings.no_user_config {
+                None
+            } else {
+                home::home_dir().and_then(|p| self.locate(p.join(".git-bump.lua")))
+            };
+            let config_outer = if self.settings.outer_config {
+                self.get_outer_config_files()?
+            } else {
+                Vec::new()
+            };
+            let config_repo_unshared = if self.settings.no_local_config {
+                None
+            } else {
+                let path = self.get_repository()?.path().join("git-bump.lua");
+                self.locate(path)
+            };
+            let config_repo_shared = if self.settings.no_shared_config {
+                None
+            } else {
+                let path = self.get_workdir()?.join(".git-bump.lua");
+                self.locate(path)
+            };
+            let config_git = self.get_git_config_file()?;
+            let config_scoped = if self.settings.no_shared_config {
+                Vec::new()
+            } else {
+                self.find_scoped_config_files()?
+            };
+
+            let mut config_files = Vec::new();
+            let mut resolved = Vec::new();
+            for config in config_user
                 .into_iter()
-                .flatten()
-                .collect();
+                .chain(config_outer)
+                .chain(config_repo_unshared)
+                .chain(config_repo_shared)
+                .chain(config_git)
+                .chain(config_scoped)
+            {
+                // The file from the Git config might be one of the usual ones as well, and symlinks
+                // might point to another config, so the first path of every file is kept
+                let identity = config.canonicalize().unwrap_or_else(|_| config.clone());
+                if !resolved.contains(&identity) {
+                    resolved.push(identity);
+                    config_files.push(config);
+                }
+            }
 
             Ok(Rc::clone(self.config_files.insert(Rc::new(config_files))))
         }
     }
 
+    /// Get the path of a config file, if it exists.
+    ///
+    /// Symlinks are resolved, unless paths are to be kept as they are found.
+    fn locate(&self, path: PathBuf) -> Option<PathBuf> {
+        let resolved = path.canonicalize().ok()?;
+        match self.settings.no_canonicalize {
+            true => Some(path),
+            false => Some(resolved),
+        }
+    }
+
+    /// Get the config file set with `bump.config` in the Git config, if any.
+    ///
+    /// Just like for `core.hooksPath`, a leading `~/` stands for the home directory, and relative
+    /// paths are relative to the root of the worktree.
+    fn get_git_config_file(&mut self) -> Result<Option<PathBuf>> {
+        let path = match self.get_repository()?.config()?.get_path("bump.config") {
+            Ok(path) => self.get_workdir()?.join(path),
+            Err(_) => return Ok(None),
+        };
+
+        match self.locate(path.clone()) {
+            Some(path) => Ok(Some(path)),
+            None => Err(Error::MissingConfig {
+                file: self.get_paths()?.show(&path),
+            }),
+        }
+    }
+
+    /// Get config files in the worktrees of all repositories the current one is nested in.
+    ///
+    /// The files are ordered from the outermost to the innermost repository, so inner configs
+    /// override outer ones.
+    fn get_outer_config_files(&mut self) -> Result<Vec<PathBuf>> {
+        let mut config_files = Vec::new();
+
+        let mut dir = self.get_workdir()?.parent().map(Path::to_path_buf);
+        while let Some(outer) = dir.and_then(|dir| Repository::discover(dir).ok()) {
+            let workdir = match outer.workdir() {
+                Some(workdir) => workdir,
+                None => break,
+            };
+
+            config_files.extend(self.locate(workdir.join(".git-bump.lua")));
+            dir = workdir.parent().map(Path::to_path_buf);
+        }
+
+        config_files.reverse();
+        Ok(config_files)
+    }
+
+    /// Find config files in subdirectories of the worktree.
+    ///
+    /// Ignored directories and nested repositories are skipped. The files are sorted by path, so
+    /// configs of parent directories come before those of their subdirectories.
+    fn find_scoped_config_files(&mut self) -> Result<Vec<PathBuf>> {
+        let repository = self.get_repository()?;
+        let workdir = self.get_workdir()?;
+
+        let mut config_files = Vec::new();
+        let mut dirs = vec![workdir.to_path_buf()];
+        while let Some(dir) = dirs.pop() {
+            for entry in fs::read_dir(&dir).into_iter().flatten().flatten() {
+                let path = entry.path();
+                let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+                if !is_dir || entry.file_name() == ".git" || path.join(".git").exists() {
+                    continue;
+                }
+
+                let relative = path.strip_prefix(&*workdir).unwrap_or(&path);
+                if repository.is_path_ignored(relative).unwrap_or(false) {
+                    continue;
+                }
+
+                config_files.extend(self.locate(path.join(".git-bump.lua")));
+                dirs.push(path);
+            }
+        }
+
+        config_files.sort();
+        Ok(config_files)
+    }
+
+    /// Get directory of a config file in a subdirectory of the worktree, relative to the worktree.
+    ///
+    /// Keys of such configs are relative to their directory. Returns `None` for all other configs.
+    pub(crate) fn get_config_scope(&mut self, config: &Path) -> Result<Option<String>> {
+        if config.file_name() != Some(OsStr::new(".git-bump.lua")) {
+            return Ok(None);
+        }
+
+        // Paths of config files are either resolved or kept as they are, like the worktree itself
+        let workdir = self.get_workdir()?;
+        let resolved = workdir.canonicalize()?;
+        let scope = config
+            .parent()
+            .and_then(|dir| {
+                dir.strip_prefix(&*workdir)
+                    .or_else(|_| dir.strip_prefix(&resolved))
+                    .ok()
+            })
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .map(|dir| dir.to_string_lossy().replace('\\', "/"));
+
+        Ok(scope)
+    }
+
     /// Get map of existing files and Lua functions for bumping.
-    pub(crate) fn get_file_mapping(&mut self) -> Result<Rc<HashMap<PathBuf, LuaRegistryKey>>> {
+    pub(crate) fn get_file_mapping(&mut self) -> Result<Rc<HashMap<PathBuf, Mapping>>> {
         if let Some(file_mapping) = &self.file_mapping {
             Ok(Rc::clone(file_mapping))
         } else {
@@ -97,39 +391,468 @@ impl State {
                     .clone());
             }
 
-            let mut file_mapping = HashMap::new();
+            self.set_context()?;
+
+            let mut file_mapping: HashMap<PathBuf, Mapping> = HashMap::new();
+            let mut missing_file_mapping: HashMap<PathBuf, Mapping> = HashMap::new();
+            let mut identities: HashMap<FileIdentity, PathBuf> = HashMap::new();
+            let canonical_workdir = self.get_workdir()?.canonicalize()?;
             for config in self.get_config_files()?.deref() {
                 let content = fs::read_to_string(config);
                 match content {
                     Ok(content) => {
-                        let lua = self.get_lua();
-                        let result = match lua.load(&content).eval::<HashMap<String, LuaFunction>>()
-                        {
-                            Ok(map) => {
-                                for (file, func) in map {
-                                    let file = self.get_workdir()?.join(file);
-
-                                    if !file.exists() {
-                                        continue;
-                                    }
+                        let lua = self.get_lua()?;
+                        let scope = self.get_config_scope(config)?;
+                        let paths = self.get_paths()?;
+                        let version = self.version.as_deref();
+                        let result =
+                            match engine::eval_config(&lua, &paths.show(config), &content, version)
+                            {
+                                Ok(map) => {
+                                    for (key, value) in map {
+                                        let path = match &scope {
+                                            Some(scope) if !Path::new(&key).is_absolute() => {
+                                                format!("{}/{}", scope, key)
+                                            }
+                                            _ => key.clone(),
+                                        };
+
+                                        let workdir = self.get_workdir()?;
+                                        let components = key_components(&path);
+                                        let mut file = if !is_outside_worktree(&path) {
+                                            disk_name(&workdir, &resolve_parents(&components))
+                                        } else if !self.settings.allow_outside_worktree {
+                                            return Err(Error::OutsideWorktree {
+                                                key,
+                                                config: paths.show(config),
+                                            });
+                                        } else if Path::new(&path).is_absolute() {
+                                            PathBuf::from(&path)
+                                        } else {
+                                            workdir.join(components.iter().collect::<PathBuf>())
+                                        };
+                                        if !self.settings.allow_outside_worktree
+                                            && escapes_worktree(&canonical_workdir, &file)
+                                        {
+                                            return Err(Error::OutsideWorktree {
+                                                key,
+                                                config: paths.show(config),
+                                            });
+                                        }
+
+                                        let exists = file.exists();
+                                        if !exists {
+                                            if !self.missing_keys.contains(&path) {
+                                                self.missing_keys.push(path);
+                                            }
+
+                                            // Only functions that do not need the current contents
+                                            // can create files, so the mappings of others are not
+                                            // even created, which saves time for huge configs
+                                            if Mapping::needs_content(&lua, &key, &value)? {
+                                                if let Some(previous) =
+                                                    missing_file_mapping.remove(&file)
+                                                {
+                                                    previous.remove(&lua)?;
+                                                }
+                                                continue;
+                                            }
+                                        }
+                                        let mapping = Mapping::from_lua(&lua, &key, value, config)?;
+
+                                        // Symlinks and hard links are further paths to an existing
+                                        // file, which must not be written twice, so their mappings
+                                        // are keyed by the path the file was mapped with first
+                                        let mut alias = None;
+                                        if let Some(identity) =
+                                            exists.then(|| file_identity(&file)).flatten()
+                                        {
+                                            let first = identities
+                                                .entry(identity)
+                                                .or_insert_with(|| file.clone());
+                                            if *first != file {
+                                                alias = Some(std::mem::replace(
+                                                    &mut file,
+                                                    first.clone(),
+                                                ));
+                                            }
+                                        }
 
-                                    let func = lua.create_registry_value(func)?;
+                                        let file_mapping = match exists {
+                                            true => &mut file_mapping,
+                                            false => &mut missing_file_mapping,
+                                        };
 
-                                    if let Some(key) = file_mapping.insert(file, func) {
-                                        lua.remove_registry_value(key)?;
-                                    };
+                                        if mapping.chain {
+                                            if let Some(previous) = file_mapping.get_mut(&file) {
+                                                previous.append(mapping);
+                                                continue;
+                                            }
+                                        }
+                                        if let Some(previous) = file_mapping.get(&file) {
+                                            self.check_override(
+                                                &file,
+                                                alias.as_deref(),
+                                                previous,
+                                                &key,
+                                                config,
+                                            )?;
+                                        }
+
+                                        if let Some(mapping) = file_mapping.insert(file, mapping) {
+                                            mapping.remove(&lua)?;
+                                        };
+                                    }
+                                    Ok(())
                                 }
-                                Ok(())
-                            }
-                            Err(source) => Err(Error::LuaLoadingFailed { source }),
-                        };
+                                Err(err) => Err(err),
+                            };
                         result
                     }
                     Err(_) => continue,
                 }?;
             }
 
+            self.register_extractors(file_mapping.iter().chain(&missing_file_mapping))?;
+            self.missing_file_mapping = Rc::new(missing_file_mapping);
             Ok(Rc::clone(self.file_mapping.insert(Rc::new(file_mapping))))
         }
     }
+
+    /// Provide the `current_version` functions of the mappings to `ctx.current_version`.
+    ///
+    /// The functions are keyed by their files relative to the worktree, with forward slashes.
+    fn register_extractors<'a>(
+        &mut self,
+        mappings: impl Iterator<Item = (&'a PathBuf, &'a Mapping)>,
+    ) -> Result<()> {
+        let lua = self.get_lua()?;
+        let workdir = self.get_workdir()?;
+
+        let extractors = lua.create_table()?;
+        let mut sources = Vec::new();
+        for (file, mapping) in mappings {
+            if let Some(current_version) = &mapping.current_version {
+                let path = file
+                    .strip_prefix(&*workdir)
+                    .unwrap_or(file)
+                    .components()
+                    .map(|component| component.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                extractors.set(
+                    path.as_str(),
+                    lua.registry_value::<LuaFunction>(current_version)?,
+                )?;
+                if mapping.source_of_truth {
+                    sources.push(path);
+                }
+            }
+        }
+        sources.sort();
+
+        lua.set_named_registry_value(EXTRACTORS, extractors)?;
+        lua.set_named_registry_value(SOURCES_OF_TRUTH, sources)?;
+        Ok(())
+    }
+
+    /// Get map of missing files and Lua functions that might create them.
+    ///
+    /// Only mappings that do not need the current contents of their files are kept for missing
+    /// files, since there are no contents to pass to them.
+    pub(crate) fn get_missing_file_mapping(&mut self) -> Result<Rc<HashMap<PathBuf, Mapping>>> {
+        self.get_file_mapping()?;
+        Ok(Rc::clone(&self.missing_file_mapping))
+    }
+
+    /// Remove the functions of all mappings from the Lua registry, once all files are bumped.
+    ///
+    /// Huge configs would keep them alive for the global hooks and Git operations otherwise. The
+    /// file mapping is empty afterwards, and mappings still referenced elsewhere are only removed
+    /// once they are dropped.
+    pub(crate) fn release_mappings(&mut self) -> Result<()> {
+        let lua = self.get_lua()?;
+        let maps = [
+            self.file_mapping.replace(Rc::default()),
+            Some(std::mem::take(&mut self.missing_file_mapping)),
+        ];
+        for map in maps
+            .into_iter()
+            .flatten()
+            .filter_map(|map| Rc::try_unwrap(map).ok())
+        {
+            for (_, mapping) in map {
+                mapping.remove(&lua)?;
+            }
+        }
+        lua.expire_registry_values();
+        Ok(())
+    }
+
+    /// Get keys of mappings whose files do not exist, relative to the worktree.
+    ///
+    /// Such mappings are left out of the file mapping, which is loaded if needed.
+    pub(crate) fn get_missing_keys(&mut self) -> Result<Vec<String>> {
+        self.get_file_mapping()?;
+        Ok(self.missing_keys.clone())
+    }
+
+    /// Report that a mapping is overridden by a later config file.
+    ///
+    /// Fails instead if overriding is not allowed by the settings. If both mappings come from the
+    /// same config file, their keys are different spellings of the same file, and the later one in
+    /// sorted order wins. If the key of the later mapping resolved to another path of the same
+    /// file, like a symlink, that path is given as alias, and the conflict is reported along with
+    /// the keys and configs of both mappings.
+    fn check_override(
+        &mut self,
+        file: &Path,
+        alias: Option<&Path>,
+        previous: &Mapping,
+        key: &str,
+        config: &Path,
+    ) -> Result<()> {
+        let paths = self.get_paths()?;
+        if let Some(alias) = alias {
+            if previous.source != config && self.settings.no_override {
+                return Err(Error::MappingOverridden {
+                    file: paths.show(file),
+                    previous: paths.show(&previous.source),
+                    config: paths.show(config),
+                });
+            }
+
+            eprintln!(
+                "Warning: Mapping {} from {} refers to {}, which is the same file as {} of mapping {} from {}, using {} from {}",
+                key,
+                paths.show(config),
+                paths.show(alias),
+                paths.show(file),
+                previous.key,
+                paths.show(&previous.source),
+                key,
+                paths.show(config)
+            );
+            return Ok(());
+        }
+
+        if previous.source == config {
+            eprintln!(
+                "Warning: Mappings {} and {} in {} refer to the same file {}, using {}",
+                previous.key,
+                key,
+                paths.show(config),
+                paths.show(file),
+                key
+            );
+            return Ok(());
+        }
+
+        if self.settings.no_override {
+            return Err(Error::MappingOverridden {
+                file: paths.show(file),
+                previous: paths.show(&previous.source),
+                config: paths.show(config),
+            });
+        }
+
+        eprintln!(
+            "Info: Mapping for {} from {} is overridden by {}",
+            paths.show(file),
+            paths.show(&previous.source),
+            paths.show(config)
+        );
+
+        Ok(())
+    }
+
+    /// Provide global `ctx` table with information about the repository to the config files.
+    pub(crate) fn set_context(&mut self) -> Result<()> {
+        let lua = self.get_lua()?;
+        let workdir = self.get_workdir()?;
+
+        let ctx = lua.create_table()?;
+        ctx.set("worktree", workdir.to_string_lossy().as_ref())?;
+        ctx.set(
+            "name",
+            workdir
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned()),
+        )?;
+        ctx.set("branch", self.get_branch()?)?;
+        ctx.set("tmpdir", self.get_tmpdir()?.to_string_lossy().as_ref())?;
+        ctx.set(
+            "project_types",
+            lua.create_table_from(
+                project::detect_project_types(&workdir)
+                    .into_iter()
+                    .map(|project_type| (project_type, true)),
+            )?,
+        )?;
+
+        let git_dir = self.get_repository()?.path().to_path_buf();
+        let worktree = workdir.to_path_buf();
+        ctx.set(
+            "current_version",
+            lua.create_function(move |lua, file: Option<String>| {
+                current_version(lua, &git_dir, &worktree, file)
+            })?,
+        )?;
+
+        lua.globals().set("ctx", ctx)?;
+
+        let links = lua.create_table()?;
+        let origin = Links::from_origin(self.get_repository()?.deref());
+        links.set(
+            "compare",
+            lua.create_function(move |_, (prev, next): (String, String)| {
+                Ok(origin.as_ref().map(|links| links.compare(&prev, &next)))
+            })?,
+        )?;
+        lua.globals().set("links", links)?;
+
+        Ok(())
+    }
+}
+
+/// Extract the current version of a file with the `current_version` function of its mapping.
+///
+/// Without a file, the file of the mapping marked as `source_of_truth` is used. The contents are
+/// taken from `HEAD`, so the version is the same before and after files were bumped, or from the
+/// worktree for files that are not committed yet.
+fn current_version(
+    lua: &Lua,
+    git_dir: &Path,
+    workdir: &Path,
+    file: Option<String>,
+) -> LuaResult<Option<String>> {
+    let failed = |message: String| LuaError::RuntimeError(message);
+
+    let extractors: Option<LuaTable> = lua.named_registry_value(EXTRACTORS)?;
+    let extractors = extractors.ok_or_else(|| {
+        failed(String::from(
+            "ctx.current_version is only available in bumping functions",
+        ))
+    })?;
+    let path = match file {
+        Some(file) => resolve_parents(&key_components(&file)).join("/"),
+        None => {
+            let sources: Vec<String> = lua.named_registry_value(SOURCES_OF_TRUTH)?;
+            match <[String; 1]>::try_from(sources) {
+                Ok([source]) => source,
+                Err(sources) if sources.is_empty() => return Err(failed(String::from(
+                    "No mapping is marked as source_of_truth, pass a file to ctx.current_version",
+                ))),
+                Err(sources) => {
+                    return Err(failed(format!(
+                        "Several mappings are marked as source_of_truth: {}",
+                        sources.join(", ")
+                    )))
+                }
+            }
+        }
+    };
+
+    let extract = extractors
+        .get::<_, Option<LuaFunction>>(path.as_str())?
+        .ok_or_else(|| failed(format!("The mapping of {} has no current_version", path)))?;
+    let contents = Repository::open(git_dir)
+        .ok()
+        .and_then(|repository| git::committed_contents(&repository, Path::new(&path)))
+        .map_or_else(|| fs::read(workdir.join(&path)), Ok)
+        .map_err(LuaError::external)?;
+
+    extract.call::<_, Option<String>>(lua.create_string(&contents)?)
+}
+
+/// Identity of an existing file, shared by all paths leading to it.
+#[derive(PartialEq, Eq, Hash)]
+enum FileIdentity {
+    /// Device and inode number, which also identify hard links.
+    #[cfg(unix)]
+    Inode(u64, u64),
+    /// Path with all symlinks resolved.
+    #[cfg(not(unix))]
+    Resolved(PathBuf),
+}
+
+/// Get identity of a file, or `None` if it cannot be determined.
+fn file_identity(file: &Path) -> Option<FileIdentity> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let metadata = fs::metadata(file).ok()?;
+        Some(FileIdentity::Inode(metadata.dev(), metadata.ino()))
+    }
+    #[cfg(not(unix))]
+    {
+        file.canonicalize().ok().map(FileIdentity::Resolved)
+    }
+}
+
+/// Get path of a file with the names used on disk.
+///
+/// File names are compared in their Unicode normalization form, since macOS stores names
+/// decomposed, while config files are usually written composed. If the file exists with the given
+/// spelling although the names on disk differ, the file system is case-insensitive, so keys like
+/// `Version` and `VERSION` are resolved to the same file as well.
+fn disk_name(workdir: &Path, components: &[&str]) -> PathBuf {
+    let ignore_case = workdir
+        .join(components.iter().collect::<PathBuf>())
+        .exists();
+
+    let mut path = workdir.to_path_buf();
+    for component in components {
+        let names = fs::read_dir(&path)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        let normalized = component.nfc().collect::<String>();
+        let name = names
+            .iter()
+            .find(|name| name == component)
+            .or_else(|| names.iter().find(|name| name.nfc().eq(normalized.chars())))
+            .or_else(|| {
+                let lowercase = normalized.to_lowercase();
+                names.iter().find(|name| {
+                    ignore_case && name.nfc().collect::<String>().to_lowercase() == lowercase
+                })
+            })
+            .map(String::as_str)
+            .unwrap_or(component);
+
+        path.push(name);
+    }
+
+    path
+}
+
+#[test]
+fn resolve_disk_names() {
+    let tmpdir = TempDir::create().unwrap();
+    let workdir = tmpdir.path();
+    fs::create_dir_all(workdir.join("Sub")).unwrap();
+    fs::write(workdir.join("Sub").join("Version"), "").unwrap();
+    fs::write(workdir.join("Sub").join("Cafe\u{301}.txt"), "").unwrap();
+
+    assert_eq!(
+        disk_name(workdir, &["Sub", "Caf\u{e9}.txt"]),
+        workdir.join("Sub").join("Cafe\u{301}.txt")
+    );
+    assert_eq!(
+        disk_name(workdir, &["Sub", "missing"]),
+        workdir.join("Sub").join("missing")
+    );
+
+    // Case is only ignored on case-insensitive file systems, where the given spelling exists.
+    let ignore_case = workdir.join("SUB").join("VERSION").exists();
+    assert_eq!(
+        disk_name(workdir, &["SUB", "VERSION"]) == workdir.join("Sub").join("Version"),
+        ignore_case
+    );
 }
@@ -0,0 +1,179 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::report::json_string;
+use crate::Result;
+
+/// Metadata of the last bump, persisted in `$GIT_DIR/git-bump/state.toml`.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct LastBump {
+    pub(crate) version: String,
+    /// Seconds since the Unix epoch.
+    pub(crate) timestamp: u64,
+    /// Bumped files, relative to the worktree.
+    pub(crate) files: Vec<String>,
+}
+
+impl LastBump {
+    /// Create metadata of a bump that happened just now.
+    pub(crate) fn now(version: &str, files: Vec<String>) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+
+        LastBump {
+            version: version.to_string(),
+            timestamp,
+            files,
+        }
+    }
+
+    /// Get path of the state file in the given Git directory.
+    fn path(git_dir: &Path) -> PathBuf {
+        git_dir.join("git-bump").join("state.toml")
+    }
+
+    /// Load metadata of the last bump, if any.
+    pub(crate) fn load(git_dir: &Path) -> Option<Self> {
+        Self::from_toml(&fs::read_to_string(Self::path(git_dir)).ok()?)
+    }
+
+    /// Save metadata, replacing that of the previous bump.
+    pub(crate) fn save(&self, git_dir: &Path) -> Result<()> {
+        let path = Self::path(git_dir);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_toml())?;
+
+        Ok(())
+    }
+
+    /// Render as TOML. Since strings are escaped just like in JSON, they are valid TOML as well.
+    fn to_toml(&self) -> String {
+        let files = self
+            .files
+            .iter()
+            .map(|file| format!("    {},\n", json_string(file)))
+            .collect::<String>();
+
+        format!(
+            "version = {}\ntimestamp = {}\nfiles = [\n{}]\n",
+            json_string(&self.version),
+            self.timestamp,
+            files
+        )
+    }
+
+    /// Parse the TOML written by [`LastBump::to_toml`].
+    fn from_toml(toml: &str) -> Option<Self> {
+        let mut version = None;
+        let mut timestamp = None;
+        let mut files = Vec::new();
+        let mut in_files = false;
+
+        for line in toml.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            if in_files {
+                match line {
+                    "]" => in_files = false,
+                    line => files.push(parse_string(line.trim_end_matches(','))?),
+                }
+                continue;
+            }
+
+            let (key, value) = line.split_once('=')?;
+            match (key.trim(), value.trim()) {
+                ("version", value) => version = Some(parse_string(value)?),
+                ("timestamp", value) => timestamp = Some(value.parse().ok()?),
+                ("files", "[") => in_files = true,
+                _ => {}
+            }
+        }
+
+        Some(LastBump {
+            version: version?,
+            timestamp: timestamp?,
+            files,
+        })
+    }
+}
+
+/// Parse a basic TOML string, with escape sequences as written by [`json_string`].
+fn parse_string(value: &str) -> Option<String> {
+    let inner = value.strip_prefix('"')?.strip_suffix('"')?;
+
+    let mut string = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            string.push(c);
+            continue;
+        }
+        match chars.next()? {
+            'n' => string.push('\n'),
+            'r' => string.push('\r'),
+            't' => string.push('\t'),
+            'u' => {
+                let code = chars.by_ref().take(4).collect::<String>();
+                string.push(char::from_u32(u32::from_str_radix(&code, 16).ok()?)?);
+            }
+            c => string.push(c),
+        }
+    }
+
+    Some(string)
+}
+
+/// Format seconds since the Unix epoch as date and time in UTC.
+pub(crate) fn format_timestamp(timestamp: u64) -> String {
+    let (days, seconds) = (timestamp / 86400, timestamp % 86400);
+
+    // Convert days to a civil date, following the algorithm `civil_from_days` described in
+    // http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719468;
+    let era = z / 146097;
+    let day_of_era = z % 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+#[test]
+fn persist_last_bump() {
+    let last_bump = LastBump {
+        version: String::from("1.2.3"),
+        timestamp: 1700000000,
+        files: vec![String::from("VERSION"), String::from("sub/\"quoted\".txt")],
+    };
+
+    let toml = last_bump.to_toml();
+    assert_eq!(
+        toml,
+        "version = \"1.2.3\"\ntimestamp = 1700000000\nfiles = [\n    \"VERSION\",\n    \"sub/\\\"quoted\\\".txt\",\n]\n"
+    );
+    assert_eq!(LastBump::from_toml(&toml), Some(last_bump));
+
+    assert_eq!(format_timestamp(0), "1970-01-01 00:00:00 UTC");
+    assert_eq!(format_timestamp(1700000000), "2023-11-14 22:13:20 UTC");
+    assert_eq!(format_timestamp(951782400), "2000-02-29 00:00:00 UTC");
+}
@@ -0,0 +1,152 @@
+use std::io;
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Text};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::diff::{self, DiffMode};
+use crate::engine::Change;
+
+const HELP: &str = " ↑/↓ select  space toggle  a all  PgUp/PgDn scroll  enter apply  q abort ";
+
+/// State of the review screen.
+struct Review<'a> {
+    changes: &'a [&'a Change],
+    included: Vec<bool>,
+    list: ListState,
+    scroll: u16,
+}
+
+impl Review<'_> {
+    fn selected(&self) -> usize {
+        self.list.selected().unwrap_or(0)
+    }
+
+    fn select(&mut self, index: usize) {
+        self.list.select(Some(index));
+        self.scroll = 0;
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, help] =
+            Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+        let [files, changes] =
+            Layout::horizontal([Constraint::Percentage(30), Constraint::Percentage(70)])
+                .areas(main);
+
+        let items = self
+            .changes
+            .iter()
+            .zip(&self.included)
+            .map(|(change, included)| {
+                let mark = if *included { "[x]" } else { "[ ]" };
+                let suffix = if change.old == change.new {
+                    " (unchanged)"
+                } else {
+                    ""
+                };
+                ListItem::new(format!("{} {}{}", mark, change.file, suffix))
+            })
+            .collect::<Vec<_>>();
+        let included = self.included.iter().filter(|included| **included).count();
+        let list = List::new(items)
+            .block(Block::bordered().title(format!(
+                " Files ({}/{}) ",
+                included,
+                self.changes.len()
+            )))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, files, &mut self.list);
+
+        let change = &self.changes[self.selected()];
+        // The file name is already shown in the title, so leave out the header lines
+        let mut text = diff::render(
+            &change.file,
+            &change.old,
+            &change.new,
+            DiffMode::Unified,
+            false,
+        )
+        .lines()
+        .skip(2)
+        .map(|line| {
+            let style = match line.chars().next() {
+                Some('+') => Style::new().fg(Color::Green),
+                Some('-') => Style::new().fg(Color::Red),
+                Some('@') => Style::new().fg(Color::Cyan),
+                _ => Style::new(),
+            };
+            Line::styled(line.to_string(), style)
+        })
+        .collect::<Text>();
+        if text.lines.is_empty() {
+            text = Text::styled("No changes", Style::new().add_modifier(Modifier::DIM));
+        }
+        let paragraph = Paragraph::new(text)
+            .block(Block::bordered().title(format!(" {} ", change.file)))
+            .scroll((self.scroll, 0));
+        frame.render_widget(paragraph, changes);
+
+        frame.render_widget(
+            Line::styled(HELP, Style::new().add_modifier(Modifier::DIM)),
+            help,
+        );
+    }
+}
+
+/// Show the changes of all files in a full-screen terminal interface to choose which to apply.
+///
+/// Returns for each change if it is included, or `None` if the review was aborted.
+pub(crate) fn review(changes: &[&Change]) -> io::Result<Option<Vec<bool>>> {
+    if changes.is_empty() {
+        return Ok(Some(Vec::new()));
+    }
+
+    let mut terminal = ratatui::init();
+    let result = run(&mut terminal, changes);
+    ratatui::restore();
+
+    result
+}
+
+fn run(terminal: &mut DefaultTerminal, changes: &[&Change]) -> io::Result<Option<Vec<bool>>> {
+    let mut review = Review {
+        changes,
+        included: vec![true; changes.len()],
+        list: ListState::default().with_selected(Some(0)),
+        scroll: 0,
+    };
+
+    loop {
+        terminal.draw(|frame| review.draw(frame))?;
+
+        let key = match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => key,
+            _ => continue,
+        };
+
+        let selected = review.selected();
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(None),
+            KeyCode::Enter => return Ok(Some(review.included)),
+            KeyCode::Char(' ') => review.included[selected] = !review.included[selected],
+            KeyCode::Char('a') => {
+                let all = review.included.iter().all(|included| *included);
+                review
+                    .included
+                    .iter_mut()
+                    .for_each(|included| *included = !all);
+            }
+            KeyCode::Down | KeyCode::Char('j') => review.select((selected + 1) % changes.len()),
+            KeyCode::Up | KeyCode::Char('k') => {
+                review.select((selected + changes.len() - 1) % changes.len())
+            }
+            KeyCode::PageDown => review.scroll = review.scroll.saturating_add(10),
+            KeyCode::PageUp => review.scroll = review.scroll.saturating_sub(10),
+            _ => {}
+        }
+    }
+}